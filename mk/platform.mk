@@ -99,11 +99,20 @@ CFG_RLIB_GLOB=lib$(1)-*.rlib
 
 include $(wildcard $(CFG_SRC_DIR)mk/cfg/*.mk)
 
+# The runtimes `-Z sanitizer`, `-C profile-generate` and `-Z instrument-coverage`
+# link, which are built from compiler-rt in mk/rt.mk for the only target the
+# sanitizers support.
+SANITIZER_RUNTIMES_x86_64-unknown-linux-gnu := \
+  librustc_asan.a librustc_lsan.a librustc_msan.a librustc_tsan.a \
+  librustc_profiler.a
+
 define ADD_INSTALLED_OBJECTS
   INSTALLED_OBJECTS_$(1) += $$(CFG_INSTALLED_OBJECTS_$(1))
   REQUIRED_OBJECTS_$(1) += $$(CFG_THIRD_PARTY_OBJECTS_$(1))
   INSTALLED_OBJECTS_$(1) += $$(call CFG_STATIC_LIB_NAME_$(1),compiler-rt)
   REQUIRED_OBJECTS_$(1) += $$(call CFG_STATIC_LIB_NAME_$(1),compiler-rt)
+  INSTALLED_OBJECTS_$(1) += $$(SANITIZER_RUNTIMES_$(1))
+  REQUIRED_OBJECTS_$(1) += $$(SANITIZER_RUNTIMES_$(1))
 endef
 
 $(foreach target,$(CFG_TARGET), \
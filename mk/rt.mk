@@ -595,6 +595,48 @@ $$(COMPRT_LIB_$(1)): $$(COMPRT_OBJS_$(1))
 	@$$(call E, link: $$@)
 	$$(Q)$$(call CFG_CREATE_ARCHIVE_$(1),$$@) $$^
 
+################################################################################
+# compiler-rt sanitizers and profiler
+#
+# Unlike the builtins above, these go through compiler-rt's CMake build system,
+# see `sanitizers` in `src/bootstrap/native.rs`. They're only built for the
+# targets listed in SANITIZER_RUNTIMES (see mk/platform.mk), and copied to
+# `librustc_*.a`, which is the name the compiler links them by.
+################################################################################
+
+ifneq ($$(SANITIZER_RUNTIMES_$(1)),)
+
+SANITIZERS_BUILD_DIR_$(1) := $$(RT_OUTPUT_DIR_$(1))/sanitizers
+SANITIZERS_DONE_$(1) := $$(SANITIZERS_BUILD_DIR_$(1))/sanitizers-finished-building
+SANITIZERS_LIB_DIR_$(1) := $$(SANITIZERS_BUILD_DIR_$(1))/lib/linux
+
+$$(SANITIZERS_DONE_$(1)): $$(LLVM_CONFIG_$$(CFG_BUILD)) $$(MKFILE_DEPS)
+	@$$(call E, cmake: compiler-rt sanitizers for $(1))
+	$$(Q)rm -rf $$(SANITIZERS_BUILD_DIR_$(1))
+	$$(Q)mkdir -p $$(SANITIZERS_BUILD_DIR_$(1))
+	$$(Q)cd $$(SANITIZERS_BUILD_DIR_$(1)) && $$(CFG_CMAKE) $(S)src/compiler-rt \
+		-DCMAKE_BUILD_TYPE=Release \
+		-DCMAKE_C_COMPILER="$$(CC_$(1))" \
+		-DCMAKE_CXX_COMPILER="$$(CXX_$(1))" \
+		-DLLVM_CONFIG_PATH="$$(LLVM_CONFIG_$$(CFG_BUILD))" \
+		-DCOMPILER_RT_DEFAULT_TARGET_TRIPLE=$(1) \
+		-DCOMPILER_RT_DEFAULT_TARGET_ONLY=ON \
+		-DCOMPILER_RT_BUILD_BUILTINS=OFF \
+		-DCOMPILER_RT_BUILD_SANITIZERS=ON \
+		-DCOMPILER_RT_INCLUDE_TESTS=OFF
+	$$(Q)$$(CFG_CMAKE) --build $$(SANITIZERS_BUILD_DIR_$(1))
+	$$(Q)touch $$@
+
+$$(RT_OUTPUT_DIR_$(1))/librustc_profiler.a: $$(SANITIZERS_DONE_$(1))
+	$$(Q)cp $$(SANITIZERS_LIB_DIR_$(1))/libclang_rt.profile-x86_64.a $$@
+
+SANITIZERS_ONLY_$(1) := $$(filter-out librustc_profiler.a,$$(SANITIZER_RUNTIMES_$(1)))
+$$(SANITIZERS_ONLY_$(1):%=$$(RT_OUTPUT_DIR_$(1))/%): \
+		$$(RT_OUTPUT_DIR_$(1))/librustc_%.a: $$(SANITIZERS_DONE_$(1))
+	$$(Q)cp $$(SANITIZERS_LIB_DIR_$(1))/libclang_rt.$$*-x86_64.a $$@
+
+endif
+
 ################################################################################
 # libbacktrace
 #
@@ -42,6 +42,7 @@ pub fn std<'a>(build: &'a Build, target: &str, compiler: &Compiler<'a>) {
     t!(fs::create_dir_all(&libdir));
     copy(&build.compiler_rt_built.borrow()[target],
          &libdir.join(staticlib("compiler-rt", target)));
+    copy_sanitizers(build, target, &libdir);
 
     // Some platforms have startup objects that may be required to produce the
     // libstd dynamic library, for example.
@@ -89,6 +90,7 @@ pub fn std_link(build: &Build,
         t!(fs::create_dir_all(&libdir));
         copy(&build.compiler_rt_built.borrow()[target],
              &libdir.join(staticlib("compiler-rt", target)));
+        copy_sanitizers(build, target, &libdir);
     }
     add_to_sysroot(&out_dir, &libdir);
 
@@ -97,6 +99,22 @@ pub fn std_link(build: &Build,
     }
 }
 
+/// Copies the sanitizer and profiler runtimes into the sysroot as
+/// `librustc_*.a`, which is where `-Z sanitizer`, `-C profile-generate` and
+/// `-Z instrument-coverage` expect to find them when linking.
+fn copy_sanitizers(build: &Build, target: &str, into: &Path) {
+    if target != "x86_64-unknown-linux-gnu" {
+        return
+    }
+    let src = build.sanitizers_out(target).join("lib/linux");
+    for name in ["asan", "lsan", "msan", "tsan"].iter() {
+        copy(&src.join(format!("libclang_rt.{}-x86_64.a", name)),
+             &into.join(format!("librustc_{}.a", name)));
+    }
+    copy(&src.join("libclang_rt.profile-x86_64.a"),
+         &into.join("librustc_profiler.a"));
+}
+
 /// Copies the crt(1,i,n).o startup objects
 ///
 /// Only required for musl targets that statically link to libc
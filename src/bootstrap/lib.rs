@@ -254,6 +254,7 @@ impl Build {
                 }
                 CompilerRt { _dummy } => {
                     native::compiler_rt(self, target.target);
+                    native::sanitizers(self, target.target);
                 }
                 TestHelpers { _dummy } => {
                     native::test_helpers(self, target.target);
@@ -774,6 +775,11 @@ impl Build {
         self.out.join(target).join("compiler-rt")
     }
 
+    /// Root output directory for the sanitizer runtimes compiled for `target`
+    fn sanitizers_out(&self, target: &str) -> PathBuf {
+        self.compiler_rt_out(target).join("sanitizers")
+    }
+
     /// Root output directory for rust_test_helpers library compiled for
     /// `target`
     fn test_helpers_out(&self, target: &str) -> PathBuf {
@@ -526,6 +526,50 @@ pub fn compiler_rt(build: &Build, target: &str) {
     cfg.compile("libcompiler-rt.a");
 }
 
+/// Compiles the sanitizer runtimes of compiler-rt, which the compiler links
+/// into executables built with `-Z sanitizer`, along with the profiler runtime
+/// for `-C profile-generate` which compiler-rt builds with them.
+///
+/// Unlike the builtins above, these do go through compiler-rt's CMake build
+/// system, as they're far more sources with far more flags, and they only
+/// need to build on the one target `-Z sanitizer` supports.
+pub fn sanitizers(build: &Build, target: &str) {
+    if target != "x86_64-unknown-linux-gnu" {
+        return
+    }
+
+    let dst = build.sanitizers_out(target);
+    let done_stamp = dst.join("sanitizers-finished-building");
+    if fs::metadata(&done_stamp).is_ok() {
+        return
+    }
+
+    println!("Building sanitizers for {}", target);
+
+    let _ = fs::remove_dir_all(&dst.join("build"));
+    t!(fs::create_dir_all(&dst.join("build")));
+
+    let mut cfg = cmake::Config::new(build.src.join("src/compiler-rt"));
+    if build.config.ninja {
+        cfg.generator("Ninja");
+    }
+    cfg.target(target)
+       .host(&build.config.build)
+       .out_dir(&dst)
+       .profile("Release")
+       .define("LLVM_CONFIG_PATH", build.llvm_config(&build.config.build))
+       .define("COMPILER_RT_DEFAULT_TARGET_TRIPLE", target)
+       .define("COMPILER_RT_DEFAULT_TARGET_ONLY", "ON")
+       .define("COMPILER_RT_BUILD_BUILTINS", "OFF")
+       .define("COMPILER_RT_BUILD_SANITIZERS", "ON")
+       .define("COMPILER_RT_INCLUDE_TESTS", "OFF")
+       .define("CMAKE_C_COMPILER", build.cc(target))
+       .define("CMAKE_CXX_COMPILER", build.cxx(target));
+    cfg.build();
+
+    t!(File::create(&done_stamp));
+}
+
 /// Compiles the `rust_test_helpers.c` library which we used in various
 /// `run-pass` test suites for ABI testing.
 pub fn test_helpers(build: &Build, target: &str) {
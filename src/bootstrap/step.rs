@@ -348,7 +348,10 @@ impl<'a> Step<'a> {
                 vec![self.libstd(compiler),
                      self.target(host).rustc(compiler.stage)]
             }
-            Source::CompilerRt { _dummy } => Vec::new(),
+            Source::CompilerRt { _dummy } => {
+                // The sanitizer runtimes are configured with `llvm-config`.
+                vec![self.target(&build.config.build).llvm(())]
+            }
             Source::Llvm { _dummy } => Vec::new(),
             Source::TestHelpers { _dummy } => Vec::new(),
             Source::DebuggerScripts { stage: _ } => Vec::new(),
@@ -47,6 +47,7 @@ TYPE_KIND_PTR               = 15
 TYPE_KIND_FIXED_SIZE_VEC    = 16
 
 ENCODED_ENUM_PREFIX = "RUST$ENCODED$ENUM$"
+ENCODED_NICHE_PREFIX = "RUST$ENCODED$NICHE$"
 ENUM_DISR_FIELD_NAME = "RUST$ENUM$DISR"
 
 # Slice related constants
@@ -193,7 +194,8 @@ class Type(object):
             if first_variant_name is None:
                 return TYPE_KIND_SINGLETON_ENUM
             else:
-                assert first_variant_name.startswith(ENCODED_ENUM_PREFIX)
+                assert (first_variant_name.startswith(ENCODED_ENUM_PREFIX) or
+                        first_variant_name.startswith(ENCODED_NICHE_PREFIX))
                 return TYPE_KIND_COMPRESSED_ENUM
         else:
             return TYPE_KIND_REGULAR_ENUM
@@ -254,17 +256,32 @@ class Value(object):
 class EncodedEnumInfo(object):
     """
     This class provides facilities for handling enum values with compressed
-    encoding where a non-null field in one variant doubles as the discriminant.
+    encoding where a non-null field in one variant doubles as the discriminant,
+    or where the values a field in one variant never holds (its niche) are used
+    for the other variants.
     """
 
     def __init__(self, enum_val):
         assert enum_val.type.get_type_kind() == TYPE_KIND_COMPRESSED_ENUM
         variant_name = enum_val.type.get_fields()[0].name
+        self.__enum_val = enum_val
+        if variant_name.startswith(ENCODED_NICHE_PREFIX):
+            # RUST$ENCODED$NICHE$<field indices>$<niche start>$<variant names>,
+            # where the name of the variant holding the niche is left empty.
+            parts = variant_name[len(ENCODED_NICHE_PREFIX):].split("$")
+            numbers = 0
+            while numbers < len(parts) and parts[numbers].isdigit():
+                numbers += 1
+            self.__disr_field_indices = [int(index) for index in parts[:numbers - 1]]
+            self.__niche_start = int(parts[numbers - 1])
+            self.__niche_variant_names = parts[numbers:]
+            self.__null_variant_name = None
+            return
         last_separator_index = variant_name.rfind("$")
         start_index = len(ENCODED_ENUM_PREFIX)
         indices_substring = variant_name[start_index:last_separator_index].split("$")
-        self.__enum_val = enum_val
         self.__disr_field_indices = [int(index) for index in indices_substring]
+        self.__niche_start = None
         self.__null_variant_name = variant_name[last_separator_index + 1:]
 
     def is_null_variant(self):
@@ -279,6 +296,14 @@ class EncodedEnumInfo(object):
         if discriminant_val.type.get_dwarf_type_kind() == DWARF_TYPE_CODE_STRUCT:
             discriminant_val = discriminant_val.get_child_at_index(0)
 
+        if self.__niche_start is not None:
+            index = discriminant_val.as_integer() - self.__niche_start
+            if 0 <= index < len(self.__niche_variant_names):
+                self.__null_variant_name = self.__niche_variant_names[index]
+            else:
+                self.__null_variant_name = ""
+            return self.__null_variant_name != ""
+
         return discriminant_val.as_integer() == 0
 
     def get_non_null_variant_val(self):
@@ -18,6 +18,7 @@ use syntax::visit::Visitor;
 #[derive(Copy, Clone, PartialEq)]
 enum Target {
     Fn,
+    Method,
     Struct,
     Enum,
     Other,
@@ -85,14 +86,37 @@ impl<'a> CheckAttrVisitor<'a> {
         }
     }
 
+    fn check_must_use(&self, attr: &ast::Attribute, target: Target) {
+        match target {
+            Target::Fn | Target::Method | Target::Struct | Target::Enum => {}
+            Target::Other => {
+                // This used to be accepted silently, so only warn.
+                self.sess.span_warn(attr.span,
+                                    "`must_use` attribute should be applied to function, \
+                                     method, struct or enum");
+            }
+        }
+    }
+
     fn check_attribute(&self, attr: &ast::Attribute, target: Target) {
         let name: &str = &attr.name();
         match name {
             "inline" => self.check_inline(attr, target),
             "repr" => self.check_repr(attr, target),
+            "must_use" => self.check_must_use(attr, target),
             _ => (),
         }
     }
+
+    // Associated items have only ever had `must_use` checked, other
+    // attributes are accepted on them as before.
+    fn check_associated_attributes(&self, attrs: &[ast::Attribute], target: Target) {
+        for attr in attrs {
+            if attr.check_name("must_use") {
+                self.check_must_use(attr, target);
+            }
+        }
+    }
 }
 
 impl<'a> Visitor for CheckAttrVisitor<'a> {
@@ -103,6 +127,24 @@ impl<'a> Visitor for CheckAttrVisitor<'a> {
         }
         visit::walk_item(self, item);
     }
+
+    fn visit_trait_item(&mut self, trait_item: &ast::TraitItem) {
+        let target = match trait_item.node {
+            ast::TraitItemKind::Method(..) => Target::Method,
+            _ => Target::Other,
+        };
+        self.check_associated_attributes(&trait_item.attrs, target);
+        visit::walk_trait_item(self, trait_item);
+    }
+
+    fn visit_impl_item(&mut self, impl_item: &ast::ImplItem) {
+        let target = match impl_item.node {
+            ast::ImplItemKind::Method(..) => Target::Method,
+            _ => Target::Other,
+        };
+        self.check_associated_attributes(&impl_item.attrs, target);
+        visit::walk_impl_item(self, impl_item);
+    }
 }
 
 pub fn check_crate(sess: &Session, krate: &ast::Crate) {
@@ -479,6 +479,16 @@ impl<'a, 'gcx, 'tcx> InferCtxt<'a, 'gcx, 'tcx> {
                     err.span_note(sp, &format!("Perhaps two different versions \
                                                 of crate `{}` are being used?",
                                                crate_name));
+                    self.note_crate_origin(err, "expected", did1);
+                    self.note_crate_origin(err, "found", did2);
+                    let cstore = &self.tcx.sess.cstore;
+                    let original_name = cstore.original_crate_name(did1.krate);
+                    if original_name == cstore.original_crate_name(did2.krate) {
+                        err.note(&format!("crate `{}` is a duplicate dependency; make every \
+                                           crate in the dependency graph use the same \
+                                           version of it",
+                                          original_name));
+                    }
                 }
             }
         };
@@ -503,6 +513,37 @@ impl<'a, 'gcx, 'tcx> InferCtxt<'a, 'gcx, 'tcx> {
         }
     }
 
+    /// Explains which crate, and which `extern crate` item, the item
+    /// `def_id` of a type mismatch comes from.
+    fn note_crate_origin(&self, err: &mut DiagnosticBuilder, which: &str, def_id: DefId) {
+        let cstore = &self.tcx.sess.cstore;
+        let src = cstore.used_crate_source(def_id.krate);
+        let mut msg = format!("`{}` ({}) is defined in crate `{}` with SVH `{}` and \
+                               disambiguator `{}`",
+                              self.tcx.item_path_str(def_id),
+                              which,
+                              cstore.original_crate_name(def_id.krate),
+                              cstore.crate_hash(def_id.krate),
+                              cstore.crate_disambiguator(def_id.krate));
+        if let Some(&(ref path, _)) = src.dylib.as_ref().or(src.rlib.as_ref())
+                                                        .or(src.rmeta.as_ref()) {
+            msg.push_str(&format!(", loaded from `{}`", path.display()));
+        }
+        err.note(&msg);
+
+        if let Some(extern_crate) = cstore.extern_crate(def_id.krate) {
+            if extern_crate.direct {
+                err.span_note(extern_crate.span,
+                              &format!("the {} crate is loaded by this `extern crate`", which));
+            } else {
+                err.span_note(extern_crate.span,
+                              &format!("the {} crate is loaded as a dependency of the crate \
+                                        named by this `extern crate`",
+                                       which));
+            }
+        }
+    }
+
     fn note_error_origin(&self,
                          err: &mut DiagnosticBuilder<'tcx>,
                          origin: &TypeOrigin)
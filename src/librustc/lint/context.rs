@@ -31,7 +31,7 @@ use ty::TyCtxt;
 use session::{config, early_error, Session};
 use lint::{Level, LevelSource, Lint, LintId, LintPass, LintSource};
 use lint::{EarlyLintPassObject, LateLintPassObject};
-use lint::{Default, CommandLine, Node, Allow, Warn, Deny, Forbid};
+use lint::{Default, CommandLine, ConfigFile, Node, Allow, Warn, Deny, Forbid};
 use lint::builtin;
 use util::nodemap::FnvHashMap;
 
@@ -253,7 +253,35 @@ impl LintStore {
         }
     }
 
+    /// The lints to set to each level of `levels`, which come from the
+    /// `--lint-config` file. Lint groups come first, so that the levels of
+    /// individual lints take precedence over those of their groups.
+    fn config_levels(&self, sess: &Session, levels: &[(String, Level)])
+                     -> Vec<(LintId, Level)> {
+        let mut groups = vec![];
+        let mut lints = vec![];
+        for &(ref lint_name, level) in levels {
+            match self.find_lint(lint_name, sess, None) {
+                Ok(lint_id) => lints.push((lint_id, level)),
+                Err(FindLintError::Removed) => {}
+                Err(FindLintError::NotFound) => {
+                    if let Some(&(ref v, _)) = self.lint_groups.get(&lint_name[..]) {
+                        groups.extend(v.iter().map(|&lint_id| (lint_id, level)));
+                    }
+                    // Otherwise the lint doesn't exist, which is reported by
+                    // `check_lint_names_config`.
+                }
+            }
+        }
+        groups.extend(lints);
+        groups
+    }
+
     pub fn process_command_line(&mut self, sess: &Session) {
+        for (lint_id, level) in self.config_levels(sess, &sess.opts.lint_config.levels) {
+            self.set_level(lint_id, (level, ConfigFile));
+        }
+
         for &(ref lint_name, level) in &sess.opts.lint_opts {
             check_lint_name_cmdline(sess, self,
                                     &lint_name[..], level);
@@ -288,6 +316,8 @@ impl LintStore {
                 *level = cmp::min(*level, cap);
             }
         }
+
+        check_lint_names_config(sess, self);
     }
 }
 
@@ -425,6 +455,9 @@ pub fn raw_struct_lint<'a>(sess: &'a Session,
                         Allow => bug!()
                     }, name.replace("_", "-"))
         },
+        ConfigFile => {
+            format!("{} [{} = \"{}\" in --lint-config]", msg, name, level.as_str())
+        },
         Node(src) => {
             def = Some(src);
             msg.to_string()
@@ -615,6 +648,10 @@ pub trait LintContext: Sized {
                         LintSource::CommandLine => {
                             diag_builder.note("`forbid` lint level was set on command line")
                         }
+                        LintSource::ConfigFile => {
+                            diag_builder.note("`forbid` lint level was set in the lint \
+                                               configuration file")
+                        }
                     }.emit()
                 } else if now != level {
                     let src = self.lints().get_level_source(lint_id).1;
@@ -635,6 +672,63 @@ pub trait LintContext: Sized {
             self.mut_lints().set_level(lint, lvlsrc);
         }
     }
+
+    /// Merge the lint levels which the `--lint-config` file sets for the
+    /// source file containing `span` (if any) into the current lint context,
+    /// call the provided function, then reset the lints in effect to their
+    /// previous state. The lint attributes in that file take precedence, so
+    /// this has to be called before `with_lint_attrs`.
+    fn with_lint_config_levels<F>(&mut self,
+                                  span: Option<Span>,
+                                  f: F)
+        where F: FnOnce(&mut Self),
+    {
+        let levels = match span {
+            Some(span) if !self.sess().opts.lint_config.paths.is_empty() => {
+                let sess = self.sess();
+                let file = sess.working_dir.join(sess.codemap().span_to_filename(span));
+                let levels = sess.opts.lint_config.levels_for_file(&file);
+                self.lints().config_levels(sess, &levels)
+            }
+            _ => vec![],
+        };
+
+        let mut pushed = 0;
+        for (lint_id, level) in levels {
+            let (now, src) = self.lints().get_level_source(lint_id);
+            // The command line and lint attributes take precedence over
+            // the config file.
+            let overridable = match src {
+                Default | ConfigFile => true,
+                CommandLine | Node(_) => false,
+            };
+            if now != level && overridable {
+                self.level_stack().push((lint_id, (now, src)));
+                pushed += 1;
+                self.mut_lints().set_level(lint_id, (level, ConfigFile));
+            }
+        }
+
+        f(self);
+
+        // rollback
+        for _ in 0..pushed {
+            let (lint, lvlsrc) = self.level_stack().pop().unwrap();
+            self.mut_lints().set_level(lint, lvlsrc);
+        }
+    }
+}
+
+/// If `inner` is the span of the contents of a module item spanning `outer`,
+/// returns it if the module was loaded from a file of its own, for which the
+/// `--lint-config` file may set specific lint levels.
+fn mod_file_span(sess: &Session, outer: Span, inner: Span) -> Option<Span> {
+    let codemap = sess.codemap();
+    if codemap.span_to_filename(outer) != codemap.span_to_filename(inner) {
+        Some(inner)
+    } else {
+        None
+    }
 }
 
 
@@ -749,11 +843,17 @@ impl<'a, 'tcx, 'v> hir_visit::Visitor<'v> for LateContext<'a, 'tcx> {
     }
 
     fn visit_item(&mut self, it: &hir::Item) {
-        self.with_lint_attrs(&it.attrs, |cx| {
-            run_lints!(cx, check_item, late_passes, it);
-            cx.visit_ids(|v| v.visit_item(it));
-            hir_visit::walk_item(cx, it);
-            run_lints!(cx, check_item_post, late_passes, it);
+        let file_span = match it.node {
+            hir::ItemMod(ref m) => mod_file_span(self.sess(), it.span, m.inner),
+            _ => None,
+        };
+        self.with_lint_config_levels(file_span, |cx| {
+            cx.with_lint_attrs(&it.attrs, |cx| {
+                run_lints!(cx, check_item, late_passes, it);
+                cx.visit_ids(|v| v.visit_item(it));
+                hir_visit::walk_item(cx, it);
+                run_lints!(cx, check_item_post, late_passes, it);
+            })
         })
     }
 
@@ -911,10 +1011,16 @@ impl<'a, 'tcx, 'v> hir_visit::Visitor<'v> for LateContext<'a, 'tcx> {
 
 impl<'a> ast_visit::Visitor for EarlyContext<'a> {
     fn visit_item(&mut self, it: &ast::Item) {
-        self.with_lint_attrs(&it.attrs, |cx| {
-            run_lints!(cx, check_item, early_passes, it);
-            ast_visit::walk_item(cx, it);
-            run_lints!(cx, check_item_post, early_passes, it);
+        let file_span = match it.node {
+            ast::ItemKind::Mod(ref m) => mod_file_span(self.sess(), it.span, m.inner),
+            _ => None,
+        };
+        self.with_lint_config_levels(file_span, |cx| {
+            cx.with_lint_attrs(&it.attrs, |cx| {
+                run_lints!(cx, check_item, early_passes, it);
+                ast_visit::walk_item(cx, it);
+                run_lints!(cx, check_item_post, early_passes, it);
+            })
         })
     }
 
@@ -1184,6 +1290,29 @@ fn check_lint_name_cmdline(sess: &Session, lint_cx: &LintStore,
     }
 }
 
+// Checks the validity of lint names from the `--lint-config` file. Unlike
+// those from the command line, these are reported through the usual lints,
+// so that a configuration file shared between crates can set the levels of
+// lints which only some of the compilers using it know about.
+fn check_lint_names_config(sess: &Session, lint_cx: &LintStore) {
+    let config = &sess.opts.lint_config;
+    let names = config.levels.iter()
+                             .chain(config.paths.iter().flat_map(|&(_, ref levels)| levels));
+    for &(ref lint_name, _) in names {
+        let (lint, msg) = match check_lint_name(lint_cx, lint_name) {
+            CheckLintNameResult::Ok => continue,
+            CheckLintNameResult::Warning(msg) => (builtin::RENAMED_AND_REMOVED_LINTS, msg),
+            CheckLintNameResult::NoLint => {
+                (builtin::UNKNOWN_LINTS, format!("unknown lint: `{}`", lint_name))
+            }
+        };
+        let lvlsrc = lint_cx.get_level_source(LintId::of(lint));
+        let mut db = raw_struct_lint(sess, lint_cx, lint, lvlsrc, None, &msg);
+        db.note("requested in the lint configuration file");
+        db.emit();
+    }
+}
+
 
 /// Perform lint checking on a crate.
 ///
@@ -1196,18 +1325,20 @@ pub fn check_crate<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
     let mut cx = LateContext::new(tcx, krate, access_levels);
 
     // Visit the whole crate.
-    cx.with_lint_attrs(&krate.attrs, |cx| {
-        cx.visit_ids(|v| {
-            hir_visit::walk_crate(v, krate);
-        });
+    cx.with_lint_config_levels(Some(krate.module.inner), |cx| {
+        cx.with_lint_attrs(&krate.attrs, |cx| {
+            cx.visit_ids(|v| {
+                hir_visit::walk_crate(v, krate);
+            });
 
-        // since the root module isn't visited as an item (because it isn't an
-        // item), warn for it here.
-        run_lints!(cx, check_crate, late_passes, krate);
+            // since the root module isn't visited as an item (because it isn't an
+            // item), warn for it here.
+            run_lints!(cx, check_crate, late_passes, krate);
 
-        hir_visit::walk_crate(cx, krate);
+            hir_visit::walk_crate(cx, krate);
 
-        run_lints!(cx, check_crate_post, late_passes, krate);
+            run_lints!(cx, check_crate_post, late_passes, krate);
+        })
     });
 
     // If we missed any lints added to the session, then there's a bug somewhere
@@ -1228,21 +1359,23 @@ pub fn check_ast_crate(sess: &Session, krate: &ast::Crate) {
     let mut cx = EarlyContext::new(sess, krate);
 
     // Visit the whole crate.
-    cx.with_lint_attrs(&krate.attrs, |cx| {
-        // Lints may be assigned to the whole crate.
-        if let Some(lints) = cx.sess.lints.borrow_mut().remove(&ast::CRATE_NODE_ID) {
-            for (lint_id, span, msg) in lints {
-                cx.span_lint(lint_id.lint, span, &msg[..])
+    cx.with_lint_config_levels(Some(krate.module.inner), |cx| {
+        cx.with_lint_attrs(&krate.attrs, |cx| {
+            // Lints may be assigned to the whole crate.
+            if let Some(lints) = cx.sess.lints.borrow_mut().remove(&ast::CRATE_NODE_ID) {
+                for (lint_id, span, msg) in lints {
+                    cx.span_lint(lint_id.lint, span, &msg[..])
+                }
             }
-        }
 
-        // since the root module isn't visited as an item (because it isn't an
-        // item), warn for it here.
-        run_lints!(cx, check_crate, early_passes, krate);
+            // since the root module isn't visited as an item (because it isn't an
+            // item), warn for it here.
+            run_lints!(cx, check_crate, early_passes, krate);
 
-        ast_visit::walk_crate(cx, krate);
+            ast_visit::walk_crate(cx, krate);
 
-        run_lints!(cx, check_crate_post, early_passes, krate);
+            run_lints!(cx, check_crate_post, early_passes, krate);
+        })
     });
 
     // Put the lint store back in the session.
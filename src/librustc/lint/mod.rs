@@ -309,6 +309,9 @@ pub enum LintSource {
 
     /// Lint level was set by a command-line flag.
     CommandLine,
+
+    /// Lint level was set by the file given to `--lint-config`.
+    ConfigFile,
 }
 
 pub type LevelSource = (Level, LintSource);
@@ -57,12 +57,13 @@ pub struct LinkMeta {
     pub crate_hash: Svh,
 }
 
-// Where a crate came from on the local filesystem. One of these two options
+// Where a crate came from on the local filesystem. One of these three options
 // must be non-None.
 #[derive(PartialEq, Clone, Debug)]
 pub struct CrateSource {
     pub dylib: Option<(PathBuf, PathKind)>,
     pub rlib: Option<(PathBuf, PathKind)>,
+    pub rmeta: Option<(PathBuf, PathKind)>,
     pub cnum: ast::CrateNum,
 }
 
@@ -226,6 +227,8 @@ pub trait CrateStore<'tcx> {
     fn native_libraries(&self, cnum: ast::CrateNum) -> Vec<(NativeLibraryKind, String)>;
     fn reachable_ids(&self, cnum: ast::CrateNum) -> Vec<DefId>;
     fn is_no_builtins(&self, cnum: ast::CrateNum) -> bool;
+    /// The crates the crate `cnum` depends on directly.
+    fn crate_dependencies(&self, cnum: ast::CrateNum) -> Vec<ast::CrateNum>;
 
     // resolve
     fn def_index_for_def_key(&self,
@@ -427,6 +430,8 @@ impl<'tcx> CrateStore<'tcx> for DummyCrateStore {
         { bug!("native_libraries") }
     fn reachable_ids(&self, cnum: ast::CrateNum) -> Vec<DefId> { bug!("reachable_ids") }
     fn is_no_builtins(&self, cnum: ast::CrateNum) -> bool { bug!("is_no_builtins") }
+    fn crate_dependencies(&self, cnum: ast::CrateNum) -> Vec<ast::CrateNum>
+        { bug!("crate_dependencies") }
 
     // resolve
     fn def_key(&self, def: DefId) -> hir_map::DefKey { bug!("def_key") }
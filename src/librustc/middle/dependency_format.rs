@@ -99,8 +99,16 @@ pub fn calculate(sess: &session::Session) {
     sess.abort_if_errors();
 }
 
-fn calculate_type(sess: &session::Session,
-                  ty: config::CrateType) -> DependencyList {
+/// Decides how each upstream crate is linked into an output of type `ty`.
+/// Unlike `calculate`, this neither records nor verifies the result.
+pub fn calculate_type(sess: &session::Session,
+                      ty: config::CrateType) -> DependencyList {
+    // Nothing is going to be linked if we're only emitting metadata, and the
+    // upstream crates may well only be available as `.rmeta` files.
+    if !sess.opts.output_types.should_trans() {
+        return Vec::new();
+    }
+
     match ty {
         // If the global prefer_dynamic switch is turned off, first attempt
         // static linkage (this can fail).
@@ -183,7 +191,7 @@ fn calculate_type(sess: &session::Session,
         if src.dylib.is_none() &&
            !formats.contains_key(&cnum) &&
            sess.cstore.is_explicitly_linked(cnum) {
-            assert!(src.rlib.is_some());
+            assert!(src.rlib.is_some() || src.rmeta.is_some());
             info!("adding staticlib: {}", sess.cstore.crate_name(cnum));
             add_library(sess, cnum, RequireStatic, &mut formats);
             ret[cnum as usize - 1] = Linkage::Static;
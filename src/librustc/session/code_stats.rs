@@ -0,0 +1,122 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Statistics about the generated code, gathered during translation and
+//! printed once it's done, for options such as `-Z print-type-sizes`.
+
+use util::nodemap::FnvHashSet;
+
+use std::cmp;
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum DataTypeKind {
+    Struct,
+    Enum,
+    Closure,
+}
+
+/// The layout of one field, with all sizes in bytes.
+#[derive(PartialEq, Eq, Hash, Debug)]
+pub struct FieldInfo {
+    pub name: String,
+    pub offset: u64,
+    pub size: u64,
+    pub align: u64,
+}
+
+/// The layout of one variant of an enum, or of a struct's fields.
+#[derive(PartialEq, Eq, Hash, Debug)]
+pub struct VariantInfo {
+    /// `None` for structs and closures, which have no variants.
+    pub name: Option<String>,
+    /// The size of the fields, up to the end of the last one in memory,
+    /// excluding the discriminant.
+    pub size: u64,
+    pub fields: Vec<FieldInfo>,
+}
+
+#[derive(PartialEq, Eq, Hash, Debug)]
+pub struct TypeSizeInfo {
+    pub kind: DataTypeKind,
+    pub type_description: String,
+    pub align: u64,
+    pub overall_size: u64,
+    /// The size of the discriminant, if it has a field of its own.
+    pub discr_size: Option<u64>,
+    pub variants: Vec<VariantInfo>,
+}
+
+pub struct CodeStats {
+    type_sizes: FnvHashSet<TypeSizeInfo>,
+}
+
+impl CodeStats {
+    pub fn new() -> CodeStats {
+        CodeStats { type_sizes: FnvHashSet() }
+    }
+
+    pub fn record_type_size(&mut self, info: TypeSizeInfo) {
+        self.type_sizes.insert(info);
+    }
+
+    /// Print the types recorded, largest first, with the padding between
+    /// and after their fields.
+    pub fn print_type_sizes(&self) {
+        let mut sorted: Vec<_> = self.type_sizes.iter().collect();
+        // Break ties by name, to keep the output deterministic.
+        sorted.sort_by(|a, b| {
+            (b.overall_size, &a.type_description).cmp(&(a.overall_size, &b.type_description))
+        });
+
+        for info in sorted {
+            println!("print-type-size type: `{}`: {} bytes, alignment: {} bytes",
+                     info.type_description, info.overall_size, info.align);
+            let indent = "    ";
+
+            let discr_size = if let Some(discr_size) = info.discr_size {
+                println!("print-type-size {}discriminant: {} bytes", indent, discr_size);
+                discr_size
+            } else {
+                0
+            };
+
+            let mut max_end = discr_size;
+            for variant in &info.variants {
+                let field_indent = if let Some(ref name) = variant.name {
+                    println!("print-type-size {}variant `{}`: {} bytes",
+                             indent, name, variant.size);
+                    "        "
+                } else {
+                    indent
+                };
+
+                let mut fields: Vec<_> = variant.fields.iter().collect();
+                fields.sort_by_key(|field| field.offset);
+                let mut end = discr_size;
+                for field in fields {
+                    if field.offset > end {
+                        println!("print-type-size {}padding: {} bytes",
+                                 field_indent, field.offset - end);
+                    }
+                    println!("print-type-size {}field `.{}`: {} bytes, \
+                              offset: {} bytes, alignment: {} bytes",
+                             field_indent, field.name, field.size, field.offset, field.align);
+                    end = cmp::max(end, field.offset + field.size);
+                }
+                max_end = cmp::max(max_end, end);
+            }
+
+            if info.overall_size > max_end {
+                println!("print-type-size {}end padding: {} bytes",
+                         indent, info.overall_size - max_end);
+            }
+        }
+    }
+}
@@ -17,6 +17,7 @@ pub use self::Passes::*;
 pub use self::DebugInfoLevel::*;
 
 use session::{early_error, early_warn, Session};
+use session::lint_config::LintConfig;
 use session::search_paths::SearchPaths;
 
 use rustc_back::target::Target;
@@ -42,7 +43,7 @@ use std::env;
 use std::fmt;
 use std::hash::{Hasher, SipHasher};
 use std::iter::FromIterator;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 pub struct Config {
     pub target: Target,
@@ -76,6 +77,7 @@ pub enum OutputType {
     Object,
     Exe,
     DepInfo,
+    Metadata,
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -94,7 +96,8 @@ impl OutputType {
     fn is_compatible_with_codegen_units_and_single_output_file(&self) -> bool {
         match *self {
             OutputType::Exe |
-            OutputType::DepInfo => true,
+            OutputType::DepInfo |
+            OutputType::Metadata => true,
             OutputType::Bitcode |
             OutputType::Assembly |
             OutputType::LlvmAssembly |
@@ -110,6 +113,7 @@ impl OutputType {
             OutputType::Object => "obj",
             OutputType::Exe => "link",
             OutputType::DepInfo => "dep-info",
+            OutputType::Metadata => "metadata",
         }
     }
 
@@ -120,6 +124,7 @@ impl OutputType {
             OutputType::LlvmAssembly => "ll",
             OutputType::Object => "o",
             OutputType::DepInfo => "d",
+            OutputType::Metadata => "rmeta",
             OutputType::Exe => "",
         }
     }
@@ -152,6 +157,20 @@ impl OutputTypes {
     pub fn values<'a>(&'a self) -> BTreeMapValuesIter<'a, OutputType, Option<PathBuf>> {
         self.0.values()
     }
+
+    // True if any of the output types require translation to LLVM. Metadata
+    // and dep-info can both be produced straight after analysis.
+    pub fn should_trans(&self) -> bool {
+        self.0.keys().any(|k| match *k {
+            OutputType::Bitcode |
+            OutputType::Assembly |
+            OutputType::LlvmAssembly |
+            OutputType::Object |
+            OutputType::Exe => true,
+            OutputType::Metadata |
+            OutputType::DepInfo => false,
+        })
+    }
 }
 
 
@@ -256,6 +275,7 @@ top_level_options!(
         debuginfo: DebugInfoLevel [TRACKED],
         lint_opts: Vec<(String, lint::Level)> [TRACKED],
         lint_cap: Option<lint::Level> [TRACKED],
+        lint_config: LintConfig [TRACKED],
         describe_lints: bool [UNTRACKED],
         output_types: OutputTypes [TRACKED],
         // FIXME(mw): We track this for now but it actually doesn't make too
@@ -303,6 +323,9 @@ pub enum PrintRequest {
     TargetFeatures,
     RelocationModels,
     CodeModels,
+    DiagnosticsJson,
+    CrateGraph,
+    CrateGraphDot,
 }
 
 pub enum Input {
@@ -339,6 +362,10 @@ pub struct OutputFilenames {
 /// marker right before the index of the codegen unit.
 pub const NUMBERED_CODEGEN_UNIT_MARKER: &'static str = ".cgu-";
 
+/// The number of codegen units used without `-C codegen-units` when not
+/// optimizing, so that LLVM can translate them in parallel.
+pub const DEFAULT_DEBUG_CODEGEN_UNITS: usize = 16;
+
 impl OutputFilenames {
     pub fn path(&self, flavor: OutputType) -> PathBuf {
         self.outputs.get(&flavor).and_then(|p| p.to_owned())
@@ -423,6 +450,7 @@ pub fn basic_options() -> Options {
         debuginfo: NoDebugInfo,
         lint_opts: Vec::new(),
         lint_cap: None,
+        lint_config: LintConfig::default(),
         describe_lints: false,
         output_types: OutputTypes(BTreeMap::new()),
         search_paths: SearchPaths::new(),
@@ -454,7 +482,19 @@ impl Options {
 
     pub fn single_codegen_unit(&self) -> bool {
         self.incremental.is_none() ||
-        self.cg.codegen_units == 1
+        self.codegen_units() == 1
+    }
+
+    /// The number of codegen units to split the crate into, outside of
+    /// incremental compilation, where there is one per source module.
+    pub fn codegen_units(&self) -> usize {
+        self.cg.codegen_units.unwrap_or(1)
+    }
+
+    /// True if the debuginfo of each codegen unit goes into a `.dwo` file of
+    /// its own rather than into its object file.
+    pub fn split_dwarf(&self) -> bool {
+        self.cg.split_debuginfo != SplitDebuginfo::Off && self.debuginfo != NoDebugInfo
     }
 }
 
@@ -508,6 +548,49 @@ impl PanicStrategy {
     }
 }
 
+/// The format of the files written by `-Z dump-mir`.
+#[derive(Clone, Copy, PartialEq, Hash, Debug)]
+pub enum MirDumpFormat {
+    /// One `.mir` text file for each pass.
+    Text,
+    /// One `.html` report for each function, covering every pass.
+    Html,
+}
+
+/// The LLVM sanitizer to instrument code with and link the runtime of, for
+/// `-Z sanitizer`.
+#[derive(Clone, Copy, PartialEq, Hash, Debug)]
+pub enum Sanitizer {
+    Address,
+    Leak,
+    Memory,
+    Thread,
+}
+
+impl Sanitizer {
+    /// The name of the compiler-rt runtime, as in `librustc_asan.a`.
+    pub fn runtime_name(&self) -> &'static str {
+        match *self {
+            Sanitizer::Address => "asan",
+            Sanitizer::Leak => "lsan",
+            Sanitizer::Memory => "msan",
+            Sanitizer::Thread => "tsan",
+        }
+    }
+}
+
+/// Where the DWARF debuginfo goes, for `-C split-debuginfo`.
+#[derive(Clone, Copy, PartialEq, Hash, Debug)]
+pub enum SplitDebuginfo {
+    /// Embedded in the object files and linked into the output, as usual.
+    Off,
+    /// Split out into a `.dwo` file per codegen unit, which are then packed
+    /// into a single `.dwp` file next to the linked output.
+    Packed,
+    /// Split out into a `.dwo` file per codegen unit, left where they are.
+    Unpacked,
+}
+
 /// Declare a macro that will define all CodegenOptions/DebuggingOptions fields and parsers all
 /// at once. The goal of this macro is to define an interface that can be
 /// programmatically used by the option parser in order to initialize the struct
@@ -607,6 +690,7 @@ macro_rules! options {
             Some("one of: `y`, `yes`, `on`, `n`, `no`, or `off`");
         pub const parse_string: Option<&'static str> = Some("a string");
         pub const parse_opt_string: Option<&'static str> = Some("a string");
+        pub const parse_opt_pathbuf: Option<&'static str> = Some("a path");
         pub const parse_list: Option<&'static str> = Some("a space-separated list of strings");
         pub const parse_opt_list: Option<&'static str> = Some("a space-separated list of strings");
         pub const parse_uint: Option<&'static str> = Some("a number");
@@ -614,13 +698,22 @@ macro_rules! options {
             Some("a space-separated list of passes, or `all`");
         pub const parse_opt_uint: Option<&'static str> =
             Some("a number");
+        pub const parse_opt_number: Option<&'static str> = Some("a number");
         pub const parse_panic_strategy: Option<&'static str> =
             Some("either `panic` or `abort`");
+        pub const parse_mir_dump_format: Option<&'static str> =
+            Some("either `text` or `html`");
+        pub const parse_sanitizer: Option<&'static str> =
+            Some("one of: `address`, `leak`, `memory` or `thread`");
+        pub const parse_split_debuginfo: Option<&'static str> =
+            Some("one of: `off`, `packed` or `unpacked`");
     }
 
     #[allow(dead_code)]
     mod $mod_set {
-        use super::{$struct_name, Passes, SomePasses, AllPasses, PanicStrategy};
+        use super::{$struct_name, Passes, SomePasses, AllPasses, PanicStrategy, MirDumpFormat,
+                    Sanitizer, SplitDebuginfo};
+        use std::path::PathBuf;
 
         $(
             pub fn $opt(cg: &mut $struct_name, v: Option<&str>) -> bool {
@@ -661,6 +754,13 @@ macro_rules! options {
             }
         }
 
+        fn parse_opt_pathbuf(slot: &mut Option<PathBuf>, v: Option<&str>) -> bool {
+            match v {
+                Some(s) => { *slot = Some(PathBuf::from(s)); true },
+                None => false,
+            }
+        }
+
         fn parse_string(slot: &mut String, v: Option<&str>) -> bool {
             match v {
                 Some(s) => { *slot = s.to_string(); true },
@@ -707,6 +807,13 @@ macro_rules! options {
             }
         }
 
+        fn parse_opt_number(slot: &mut Option<usize>, v: Option<&str>) -> bool {
+            match v.and_then(|s| s.parse().ok()) {
+                Some(i) => { *slot = Some(i); true },
+                None => false
+            }
+        }
+
         fn parse_passes(slot: &mut Passes, v: Option<&str>) -> bool {
             match v {
                 Some("all") => {
@@ -733,6 +840,36 @@ macro_rules! options {
             }
             true
         }
+
+        fn parse_mir_dump_format(slot: &mut MirDumpFormat, v: Option<&str>) -> bool {
+            match v {
+                Some("text") => *slot = MirDumpFormat::Text,
+                Some("html") => *slot = MirDumpFormat::Html,
+                _ => return false
+            }
+            true
+        }
+
+        fn parse_sanitizer(slot: &mut Option<Sanitizer>, v: Option<&str>) -> bool {
+            match v {
+                Some("address") => *slot = Some(Sanitizer::Address),
+                Some("leak") => *slot = Some(Sanitizer::Leak),
+                Some("memory") => *slot = Some(Sanitizer::Memory),
+                Some("thread") => *slot = Some(Sanitizer::Thread),
+                _ => return false
+            }
+            true
+        }
+
+        fn parse_split_debuginfo(slot: &mut SplitDebuginfo, v: Option<&str>) -> bool {
+            match v {
+                Some("off") => *slot = SplitDebuginfo::Off,
+                Some("packed") => *slot = SplitDebuginfo::Packed,
+                Some("unpacked") => *slot = SplitDebuginfo::Unpacked,
+                _ => return false
+            }
+            true
+        }
     }
 ) }
 
@@ -785,8 +922,9 @@ options! {CodegenOptions, CodegenSetter, basic_codegen_options,
          "metadata to mangle symbol names with"),
     extra_filename: String = ("".to_string(), parse_string, [UNTRACKED],
          "extra data to put in each output filename"),
-    codegen_units: usize = (1, parse_uint, [UNTRACKED],
-        "divide crate into N units to optimize in parallel"),
+    codegen_units: Option<usize> = (None, parse_opt_number, [UNTRACKED],
+        "divide crate into N units to optimize in parallel \
+         (default: 16 without optimizations, 1 otherwise)"),
     remark: Passes = (SomePasses(Vec::new()), parse_passes, [UNTRACKED],
         "print remarks for these optimization passes (space separated, or \"all\")"),
     no_stack_check: bool = (false, parse_bool, [UNTRACKED],
@@ -802,6 +940,14 @@ options! {CodegenOptions, CodegenSetter, basic_codegen_options,
         "set the inlining threshold for"),
     panic: PanicStrategy = (PanicStrategy::Unwind, parse_panic_strategy,
         [TRACKED], "panic strategy to compile crate with"),
+    profile_generate: Option<PathBuf> = (None, parse_opt_pathbuf, [TRACKED],
+        "instrument the generated code to write execution profiles into the given \
+         directory, for use with `-C profile-use` after merging with `llvm-profdata`"),
+    profile_use: Option<PathBuf> = (None, parse_opt_pathbuf, [TRACKED],
+        "use the given `.profdata` file to guide optimizations"),
+    split_debuginfo: SplitDebuginfo = (SplitDebuginfo::Off, parse_split_debuginfo, [TRACKED],
+        "split the DWARF debuginfo out of the objects into `.dwo` files, and whether to \
+         pack them into a `.dwp` file when linking (Linux only)"),
 }
 
 options! {DebuggingOptions, DebuggingSetter, basic_debugging_options,
@@ -820,6 +966,9 @@ options! {DebuggingOptions, DebuggingSetter, basic_debugging_options,
         "The output of `-Z time-llvm-passes` will only reflect timings of \
          re-translated modules when used with incremental compilation" )],
         "measure time of each LLVM pass"),
+    self_profile: Option<String> = (None, parse_opt_string, [UNTRACKED],
+        "record the time spent in each pass, item and LLVM module, and write it to \
+         the given file as a Chrome trace"),
     input_stats: bool = (false, parse_bool, [UNTRACKED],
         "gather statistics about the input"),
     trans_stats: bool = (false, parse_bool, [UNTRACKED_WITH_WARNING(true,
@@ -832,6 +981,8 @@ options! {DebuggingOptions, DebuggingSetter, basic_debugging_options,
         "skip LLVM verification"),
     borrowck_stats: bool = (false, parse_bool, [UNTRACKED],
         "gather borrowck statistics"),
+    borrowck_mir: bool = (false, parse_bool, [UNTRACKED],
+        "also run the MIR-based borrow checker, alongside the AST-based one"),
     no_landing_pads: bool = (false, parse_bool, [TRACKED],
         "omit landing pads for unwinding"),
     debug_llvm: bool = (false, parse_bool, [UNTRACKED],
@@ -873,6 +1024,9 @@ options! {DebuggingOptions, DebuggingSetter, basic_debugging_options,
           "treat all errors that occur as bugs"),
     continue_parse_after_error: bool = (false, parse_bool, [TRACKED],
           "attempt to recover from parse errors (experimental)"),
+    apply_suggestions: bool = (false, parse_bool, [UNTRACKED],
+          "rewrite the source files in place, applying the suggestions of \
+           diagnostics which are known to be machine applicable"),
     incremental: Option<String> = (None, parse_opt_string, [UNTRACKED],
           "enable incremental compilation (experimental)"),
     incremental_info: bool = (false, parse_bool, [UNTRACKED],
@@ -903,12 +1057,23 @@ options! {DebuggingOptions, DebuggingSetter, basic_debugging_options,
           "show spans for compiler debugging (expr|pat|ty)"),
     print_trans_items: Option<String> = (None, parse_opt_string, [UNTRACKED],
           "print the result of the translation item collection pass"),
+    print_type_sizes: bool = (false, parse_bool, [UNTRACKED],
+          "print the size, alignment and field offsets of each type laid out"),
     mir_opt_level: Option<usize> = (None, parse_opt_uint, [TRACKED],
           "set the MIR optimization level (0-3)"),
     dump_mir: Option<String> = (None, parse_opt_string, [UNTRACKED],
           "dump MIR state at various points in translation"),
     dump_mir_dir: Option<String> = (None, parse_opt_string, [UNTRACKED],
           "the directory the MIR is dumped into"),
+    dump_mir_format: MirDumpFormat = (MirDumpFormat::Text, parse_mir_dump_format, [UNTRACKED],
+          "the format of the dumped MIR: `text` files for each pass, or an `html` report \
+           for each function"),
+    sanitizer: Option<Sanitizer> = (None, parse_sanitizer, [TRACKED],
+          "instrument code with a sanitizer and link its runtime into executables \
+           (x86_64-unknown-linux-gnu only)"),
+    instrument_coverage: bool = (false, parse_bool, [TRACKED],
+          "instrument code to count line coverage in gcov's format, writing `.gcno` notes \
+           next to the outputs and `.gcda` data when the program exits"),
 }
 
 pub fn default_lib_output() -> CrateType {
@@ -1150,11 +1315,12 @@ pub fn rustc_short_optgroups() -> Vec<RustcOptGroup> {
                "NAME"),
         opt::multi_s("", "emit", "Comma separated list of types of output for \
                               the compiler to emit",
-                 "[asm|llvm-bc|llvm-ir|obj|link|dep-info]"),
+                 "[asm|llvm-bc|llvm-ir|obj|link|dep-info|metadata]"),
         opt::multi_s("", "print", "Comma separated list of compiler information to \
                                print on stdout",
                  "[crate-name|file-names|sysroot|cfg|target-list|target-cpus|\
-                   target-features|relocation-models|code-models]"),
+                   target-features|relocation-models|code-models|diagnostics-json|\
+                   crate-graph|crate-graph-dot]"),
         opt::flagmulti_s("g",  "",  "Equivalent to -C debuginfo=2"),
         opt::flagmulti_s("O", "", "Equivalent to -C opt-level=2"),
         opt::opt_s("o", "", "Write output to <filename>", "FILENAME"),
@@ -1209,6 +1375,7 @@ pub fn rustc_optgroups() -> Vec<RustcOptGroup> {
                            `hir` (the HIR), `hir,identified`, or
                            `hir,typed` (HIR with types for each node).",
                           "TYPE"),
+        opt::opt("", "lint-config", "Read lint levels from a TOML or JSON file", "PATH"),
 
         // new options here should **not** use the `_ubnr` functions, all new
         // unstable options should use the short variants to indicate that they
@@ -1301,6 +1468,13 @@ pub fn build_session_options_and_crate_config(matches: &getopts::Matches)
         })
     });
 
+    let lint_config = matches.opt_str("lint-config").map(|path| {
+        LintConfig::read(Path::new(&path)).unwrap_or_else(|e| {
+            early_error(error_format, &format!("failed to read lint configuration `{}`: {}",
+                                               path, e))
+        })
+    }).unwrap_or(LintConfig::default());
+
     let debugging_opts = build_debugging_options(matches, error_format);
 
     let mir_opt_level = debugging_opts.mir_opt_level.unwrap_or(1);
@@ -1317,6 +1491,7 @@ pub fn build_session_options_and_crate_config(matches: &getopts::Matches)
                     "obj" => OutputType::Object,
                     "link" => OutputType::Exe,
                     "dep-info" => OutputType::DepInfo,
+                    "metadata" => OutputType::Metadata,
                     part => {
                         early_error(error_format, &format!("unknown emission type: `{}`",
                                                     part))
@@ -1335,27 +1510,35 @@ pub fn build_session_options_and_crate_config(matches: &getopts::Matches)
 
     // Issue #30063: if user requests llvm-related output to one
     // particular path, disable codegen-units.
-    if matches.opt_present("o") && cg.codegen_units != 1 {
-        let incompatible: Vec<_> = output_types.iter()
+    let incompatible: Vec<_> = if matches.opt_present("o") {
+        output_types.iter()
             .map(|ot_path| ot_path.0)
             .filter(|ot| {
                 !ot.is_compatible_with_codegen_units_and_single_output_file()
-            }).collect();
-        if !incompatible.is_empty() {
-            for ot in &incompatible {
-                early_warn(error_format, &format!("--emit={} with -o incompatible with \
-                                                 -C codegen-units=N for N > 1",
-                                                ot.shorthand()));
-            }
-            early_warn(error_format, "resetting to default -C codegen-units=1");
-            cg.codegen_units = 1;
+            }).collect()
+    } else {
+        Vec::new()
+    };
+    if !incompatible.is_empty() && cg.codegen_units.map_or(false, |n| n != 1) {
+        for ot in &incompatible {
+            early_warn(error_format, &format!("--emit={} with -o incompatible with \
+                                             -C codegen-units=N for N > 1",
+                                            ot.shorthand()));
         }
+        early_warn(error_format, "resetting to -C codegen-units=1");
+        cg.codegen_units = Some(1);
     }
 
-    if cg.codegen_units < 1 {
+    if cg.codegen_units == Some(0) {
         early_error(error_format, "Value for codegen units must be a positive nonzero integer");
     }
 
+    // Outputs such as object files are written once per codegen unit, so
+    // don't split the crate up unless asked to when they're requested.
+    let per_unit_outputs = output_types.keys().any(|ot| {
+        !ot.is_compatible_with_codegen_units_and_single_output_file()
+    });
+
     let mut prints = Vec::<PrintRequest>::new();
     if cg.target_cpu.as_ref().map_or(false, |s| s == "help") {
         prints.push(PrintRequest::TargetCPUs);
@@ -1374,11 +1557,42 @@ pub fn build_session_options_and_crate_config(matches: &getopts::Matches)
         cg.code_model = None;
     }
 
-    let cg = cg;
-
     let sysroot_opt = matches.opt_str("sysroot").map(|m| PathBuf::from(&m));
     let target = matches.opt_str("target").unwrap_or(
         host_triple().to_string());
+    // The sanitizer runtimes are only built for this one target.
+    if debugging_opts.sanitizer.is_some() && target != "x86_64-unknown-linux-gnu" {
+        early_error(error_format, "sanitizers are only supported on the \
+                                   `x86_64-unknown-linux-gnu` target");
+    }
+    if cg.profile_generate.is_some() {
+        if cg.profile_use.is_some() {
+            early_error(error_format, "-C profile-generate and -C profile-use \
+                                       cannot both be provided");
+        }
+    }
+    // The profiler runtime is built along with the sanitizer runtimes.
+    if target != "x86_64-unknown-linux-gnu" {
+        if cg.profile_generate.is_some() {
+            early_error(error_format, "-C profile-generate is only supported on the \
+                                       `x86_64-unknown-linux-gnu` target");
+        }
+        if debugging_opts.instrument_coverage {
+            early_error(error_format, "-Z instrument-coverage is only supported on the \
+                                       `x86_64-unknown-linux-gnu` target");
+        }
+    }
+    // The `.dwo` files are extracted with binutils, which only handle ELF.
+    if cg.split_debuginfo != SplitDebuginfo::Off && !target.contains("linux") {
+        early_error(error_format, "-C split-debuginfo is only supported on Linux targets");
+    }
+    if let Some(ref path) = cg.profile_use {
+        if !path.is_file() {
+            early_error(error_format, &format!("profile data file `{}` passed to \
+                                                -C profile-use does not exist",
+                                               path.display()));
+        }
+    }
     let opt_level = {
         if matches.opt_present("O") {
             if cg.opt_level.is_some() {
@@ -1407,6 +1621,24 @@ pub fn build_session_options_and_crate_config(matches: &getopts::Matches)
             }
         }
     };
+
+    // LLVM only adds the profiling passes to an optimizing pipeline.
+    if opt_level == OptLevel::No {
+        if cg.profile_generate.is_some() || cg.profile_use.is_some() {
+            early_warn(error_format, "-C profile-generate and -C profile-use have no \
+                                      effect without optimizations");
+        }
+    }
+
+    // Optimized builds keep a single codegen unit by default, so that LLVM
+    // can inline across the whole crate, as does LTO, which requires it.
+    if cg.codegen_units.is_none() {
+        let parallel = opt_level == OptLevel::No && !cg.lto && !per_unit_outputs;
+        cg.codegen_units = Some(if parallel { DEFAULT_DEBUG_CODEGEN_UNITS } else { 1 });
+    }
+
+    let cg = cg;
+
     let debug_assertions = cg.debug_assertions.unwrap_or(opt_level == OptLevel::No);
     let debuginfo = if matches.opt_present("g") {
         if cg.debuginfo.is_some() {
@@ -1425,6 +1657,12 @@ pub fn build_session_options_and_crate_config(matches: &getopts::Matches)
             }
         }
     };
+    // The coverage notes are derived from the line tables of the debuginfo.
+    let debuginfo = if debugging_opts.instrument_coverage && debuginfo == NoDebugInfo {
+        LimitedDebugInfo
+    } else {
+        debuginfo
+    };
 
     let mut search_paths = SearchPaths::new();
     for s in &matches.opt_strs("L") {
@@ -1462,6 +1700,9 @@ pub fn build_session_options_and_crate_config(matches: &getopts::Matches)
             "target-features" => PrintRequest::TargetFeatures,
             "relocation-models" => PrintRequest::RelocationModels,
             "code-models" => PrintRequest::CodeModels,
+            "diagnostics-json" => PrintRequest::DiagnosticsJson,
+            "crate-graph" => PrintRequest::CrateGraph,
+            "crate-graph-dot" => PrintRequest::CrateGraphDot,
             req => {
                 early_error(error_format, &format!("unknown print request `{}`", req))
             }
@@ -1500,6 +1741,7 @@ pub fn build_session_options_and_crate_config(matches: &getopts::Matches)
         debuginfo: debuginfo,
         lint_opts: lint_opts,
         lint_cap: lint_cap,
+        lint_config: lint_config,
         describe_lints: describe_lints,
         output_types: OutputTypes(output_types),
         search_paths: search_paths,
@@ -1666,7 +1908,8 @@ mod dep_tracking {
     use std::hash::{Hash, SipHasher};
     use std::path::PathBuf;
     use super::{Passes, PanicStrategy, CrateType, OptLevel, DebugInfoLevel,
-                OutputTypes, Externs, ErrorOutputType};
+                OutputTypes, Externs, ErrorOutputType, Sanitizer, SplitDebuginfo};
+    use session::lint_config::LintConfig;
     use syntax::feature_gate::UnstableFeatures;
 
     pub trait DepTrackingHash {
@@ -1708,6 +1951,8 @@ mod dep_tracking {
     impl_dep_tracking_hash_via_hash!(Option<String>);
     impl_dep_tracking_hash_via_hash!(Option<lint::Level>);
     impl_dep_tracking_hash_via_hash!(Option<PathBuf>);
+    impl_dep_tracking_hash_via_hash!(Option<Sanitizer>);
+    impl_dep_tracking_hash_via_hash!(SplitDebuginfo);
     impl_dep_tracking_hash_via_hash!(CrateType);
     impl_dep_tracking_hash_via_hash!(PanicStrategy);
     impl_dep_tracking_hash_via_hash!(Passes);
@@ -1717,6 +1962,7 @@ mod dep_tracking {
     impl_dep_tracking_hash_via_hash!(Externs);
     impl_dep_tracking_hash_via_hash!(OutputTypes);
     impl_dep_tracking_hash_via_hash!(cstore::NativeLibraryKind);
+    impl_dep_tracking_hash_via_hash!(LintConfig);
 
     impl_dep_tracking_hash_for_sortable_vec_of!(String);
     impl_dep_tracking_hash_for_sortable_vec_of!(CrateType);
@@ -2015,6 +2261,20 @@ mod tests {
         assert_eq!(v3.dep_tracking_hash(), v3.clone().dep_tracking_hash());
     }
 
+    #[test]
+    fn test_lint_config_tracking_hash() {
+        let reference = super::basic_options();
+        let mut opts = super::basic_options();
+
+        opts.lint_config.levels = vec![(String::from("unused"), lint::Deny)];
+        assert!(reference.dep_tracking_hash() != opts.dep_tracking_hash());
+
+        opts = reference.clone();
+        opts.lint_config.paths = vec![(PathBuf::from("/src/gen"),
+                                       vec![(String::from("dead_code"), lint::Allow)])];
+        assert!(reference.dep_tracking_hash() != opts.dep_tracking_hash());
+    }
+
     #[test]
     fn test_lints_tracking_hash_different_construction_order() {
         let mut v1 = super::basic_options();
@@ -2226,7 +2486,7 @@ mod tests {
         opts.cg.extra_filename = String::from("extra-filename");
         assert_eq!(reference.dep_tracking_hash(), opts.dep_tracking_hash());
 
-        opts.cg.codegen_units = 42;
+        opts.cg.codegen_units = Some(42);
         assert_eq!(reference.dep_tracking_hash(), opts.dep_tracking_hash());
 
         opts.cg.remark = super::SomePasses(vec![String::from("pass1"),
@@ -2317,6 +2577,18 @@ mod tests {
         opts = reference.clone();
         opts.cg.panic = PanicStrategy::Abort;
         assert!(reference.dep_tracking_hash() != opts.dep_tracking_hash());
+
+        opts = reference.clone();
+        opts.cg.profile_generate = Some(PathBuf::from("/tmp/profiles"));
+        assert!(reference.dep_tracking_hash() != opts.dep_tracking_hash());
+
+        opts = reference.clone();
+        opts.cg.profile_use = Some(PathBuf::from("/tmp/default.profdata"));
+        assert!(reference.dep_tracking_hash() != opts.dep_tracking_hash());
+
+        opts = reference.clone();
+        opts.cg.split_debuginfo = super::SplitDebuginfo::Unpacked;
+        assert!(reference.dep_tracking_hash() != opts.dep_tracking_hash());
     }
 
     #[test]
@@ -2333,12 +2605,16 @@ mod tests {
         assert_eq!(reference.dep_tracking_hash(), opts.dep_tracking_hash());
         opts.debugging_opts.time_llvm_passes = true;
         assert_eq!(reference.dep_tracking_hash(), opts.dep_tracking_hash());
+        opts.debugging_opts.self_profile = Some(String::from("trace.json"));
+        assert_eq!(reference.dep_tracking_hash(), opts.dep_tracking_hash());
         opts.debugging_opts.input_stats = true;
         assert_eq!(reference.dep_tracking_hash(), opts.dep_tracking_hash());
         opts.debugging_opts.trans_stats = true;
         assert_eq!(reference.dep_tracking_hash(), opts.dep_tracking_hash());
         opts.debugging_opts.borrowck_stats = true;
         assert_eq!(reference.dep_tracking_hash(), opts.dep_tracking_hash());
+        opts.debugging_opts.borrowck_mir = true;
+        assert_eq!(reference.dep_tracking_hash(), opts.dep_tracking_hash());
         opts.debugging_opts.debug_llvm = true;
         assert_eq!(reference.dep_tracking_hash(), opts.dep_tracking_hash());
         opts.debugging_opts.meta_stats = true;
@@ -2389,10 +2665,16 @@ mod tests {
         assert_eq!(reference.dep_tracking_hash(), opts.dep_tracking_hash());
         opts.debugging_opts.print_trans_items = Some(String::from("abc"));
         assert_eq!(reference.dep_tracking_hash(), opts.dep_tracking_hash());
+        opts.debugging_opts.print_type_sizes = true;
+        assert_eq!(reference.dep_tracking_hash(), opts.dep_tracking_hash());
         opts.debugging_opts.dump_mir = Some(String::from("abc"));
         assert_eq!(reference.dep_tracking_hash(), opts.dep_tracking_hash());
         opts.debugging_opts.dump_mir_dir = Some(String::from("abc"));
         assert_eq!(reference.dep_tracking_hash(), opts.dep_tracking_hash());
+        opts.debugging_opts.dump_mir_format = super::MirDumpFormat::Html;
+        assert_eq!(reference.dep_tracking_hash(), opts.dep_tracking_hash());
+        opts.debugging_opts.apply_suggestions = true;
+        assert_eq!(reference.dep_tracking_hash(), opts.dep_tracking_hash());
 
         // Make sure changing a [TRACKED] option changes the hash
         opts = reference.clone();
@@ -2438,5 +2720,13 @@ mod tests {
         opts = reference.clone();
         opts.debugging_opts.mir_opt_level = Some(1);
         assert!(reference.dep_tracking_hash() != opts.dep_tracking_hash());
+
+        opts = reference.clone();
+        opts.debugging_opts.sanitizer = Some(super::Sanitizer::Address);
+        assert!(reference.dep_tracking_hash() != opts.dep_tracking_hash());
+
+        opts = reference.clone();
+        opts.debugging_opts.instrument_coverage = true;
+        assert!(reference.dep_tracking_hash() != opts.dep_tracking_hash());
     }
 }
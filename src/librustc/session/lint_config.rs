@@ -0,0 +1,334 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Lint levels read from the file given to `--lint-config`.
+//!
+//! The file is read as JSON if its name ends in `.json`, and as TOML
+//! otherwise. Both have the same structure, for example in TOML:
+//!
+//! ```toml
+//! # Levels of lints and lint groups for the whole crate.
+//! [lints]
+//! unused = "deny"
+//! missing-docs = "warn"
+//!
+//! # Levels for the source files under a path only. Relative paths are
+//! # relative to the directory containing the configuration file.
+//! [paths."src/generated"]
+//! dead-code = "allow"
+//! ```
+//!
+//! and in JSON:
+//!
+//! ```json
+//! {
+//!     "lints": { "unused": "deny", "missing-docs": "warn" },
+//!     "paths": { "src/generated": { "dead-code": "allow" } }
+//! }
+//! ```
+//!
+//! Only the part of TOML needed for this is understood: table headers, and
+//! keys with string values.
+//!
+//! Levels from the configuration file are applied before lint attributes,
+//! which can override them as usual. The crate-wide levels are in turn
+//! overridden by the `-A`/`-W`/`-D`/`-F` command line flags, while the
+//! levels for a path apply to the modules loaded from the files under it.
+
+use lint;
+
+use serialize::json::Json;
+use std::collections::BTreeMap;
+use std::env;
+use std::fs::File;
+use std::io::Read;
+use std::iter::Peekable;
+use std::path::{Path, PathBuf};
+use std::str::Chars;
+
+#[derive(Clone, Debug, Default, Hash, PartialEq)]
+pub struct LintConfig {
+    /// Levels of lints and lint groups for the whole crate.
+    pub levels: Vec<(String, lint::Level)>,
+    /// Levels for the source files under an absolute path.
+    pub paths: Vec<(PathBuf, Vec<(String, lint::Level)>)>,
+}
+
+impl LintConfig {
+    pub fn read(path: &Path) -> Result<LintConfig, String> {
+        let mut src = String::new();
+        File::open(path).and_then(|mut f| f.read_to_string(&mut src))
+                        .map_err(|e| e.to_string())?;
+
+        let json = if path.extension().map_or(false, |ext| ext == "json") {
+            Json::from_str(&src).map_err(|e| e.to_string())?
+        } else {
+            parse_toml(&src)?
+        };
+
+        let cwd = env::current_dir().map_err(|e| {
+            format!("couldn't determine the current directory: {}", e)
+        })?;
+        let base = cwd.join(path.parent().unwrap_or(Path::new("")));
+        LintConfig::from_json(json, &base)
+    }
+
+    fn from_json(json: Json, base: &Path) -> Result<LintConfig, String> {
+        let mut config = LintConfig::default();
+        for (key, value) in expect_table(json, "the top level")? {
+            match &key[..] {
+                "lints" => config.levels = parse_levels(value, "lints")?,
+                "paths" => {
+                    for (path, levels) in expect_table(value, "paths")? {
+                        let what = format!("paths.\"{}\"", path);
+                        config.paths.push((base.join(path), parse_levels(levels, &what)?));
+                    }
+                }
+                _ => return Err(format!("unknown key `{}`", key)),
+            }
+        }
+        // More specific paths are applied last, so they take precedence.
+        config.paths.sort_by_key(|&(ref path, _)| path.components().count());
+        Ok(config)
+    }
+
+    /// The levels for the source file at the absolute path `file`, in the
+    /// order they are to be applied.
+    pub fn levels_for_file(&self, file: &Path) -> Vec<(String, lint::Level)> {
+        self.paths.iter()
+                  .filter(|&&(ref path, _)| file.starts_with(path))
+                  .flat_map(|&(_, ref levels)| levels.iter().cloned())
+                  .collect()
+    }
+}
+
+fn expect_table(json: Json, what: &str) -> Result<BTreeMap<String, Json>, String> {
+    match json {
+        Json::Object(table) => Ok(table),
+        _ => Err(format!("expected a table for {}", what)),
+    }
+}
+
+fn parse_levels(json: Json, what: &str) -> Result<Vec<(String, lint::Level)>, String> {
+    expect_table(json, what)?.into_iter().map(|(lint_name, level)| {
+        let level = match level {
+            Json::String(ref level) => lint::Level::from_str(level),
+            _ => None,
+        };
+        match level {
+            Some(level) => Ok((lint_name.replace("-", "_"), level)),
+            None => Err(format!("expected one of \"allow\", \"warn\", \"deny\" or \"forbid\" \
+                                 for `{}` in {}", lint_name, what)),
+        }
+    }).collect()
+}
+
+/// Parses the subset of TOML described in the module documentation into
+/// the equivalent JSON.
+fn parse_toml(src: &str) -> Result<Json, String> {
+    let mut root = BTreeMap::new();
+    let mut current = vec![];
+
+    for (i, line) in src.lines().enumerate() {
+        let error = |msg: &str| format!("line {}: {}", i + 1, msg);
+        let mut line = TomlLine { chars: line.chars().peekable() };
+
+        line.skip_whitespace();
+        if line.at_end() {
+            continue;
+        }
+
+        if line.eat('[') {
+            let mut header = vec![];
+            loop {
+                line.skip_whitespace();
+                header.push(line.key().map_err(|e| error(&e))?);
+                line.skip_whitespace();
+                if line.eat(']') {
+                    break;
+                } else if !line.eat('.') {
+                    return Err(error("expected `.` or `]` in table header"));
+                }
+            }
+            get_table(&mut root, &header).map_err(|e| error(&e))?;
+            current = header;
+        } else {
+            let key = line.key().map_err(|e| error(&e))?;
+            line.skip_whitespace();
+            if !line.eat('=') {
+                return Err(error("expected `=` after key"));
+            }
+            line.skip_whitespace();
+            let value = line.string().map_err(|e| error(&e))?;
+            let table = get_table(&mut root, &current).map_err(|e| error(&e))?;
+            if table.insert(key.clone(), Json::String(value)).is_some() {
+                return Err(error(&format!("duplicate key `{}`", key)));
+            }
+        }
+
+        line.skip_whitespace();
+        if !line.at_end() {
+            return Err(error("unexpected characters at the end of the line"));
+        }
+    }
+
+    Ok(Json::Object(root))
+}
+
+/// Finds the table called `path` in `root`, creating it if needed.
+fn get_table<'a>(root: &'a mut BTreeMap<String, Json>, path: &[String])
+                 -> Result<&'a mut BTreeMap<String, Json>, String> {
+    let mut table = root;
+    for key in path {
+        let entry = {table}.entry(key.clone()).or_insert(Json::Object(BTreeMap::new()));
+        table = match *entry {
+            Json::Object(ref mut table) => table,
+            _ => return Err(format!("`{}` is not a table", key)),
+        };
+    }
+    Ok(table)
+}
+
+struct TomlLine<'a> {
+    chars: Peekable<Chars<'a>>,
+}
+
+impl<'a> TomlLine<'a> {
+    fn eat(&mut self, c: char) -> bool {
+        if self.chars.peek() == Some(&c) {
+            self.chars.next();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.eat(' ') || self.eat('\t') {}
+    }
+
+    /// Are we at the end of the line, not counting a comment?
+    fn at_end(&mut self) -> bool {
+        match self.chars.peek() {
+            None | Some(&'#') => true,
+            _ => false,
+        }
+    }
+
+    fn key(&mut self) -> Result<String, String> {
+        match self.chars.peek() {
+            Some(&'"') | Some(&'\'') => return self.string(),
+            _ => {}
+        }
+        let mut key = String::new();
+        while let Some(&c) = self.chars.peek() {
+            match c {
+                'a'...'z' | 'A'...'Z' | '0'...'9' | '_' | '-' => key.push(c),
+                _ => break,
+            }
+            self.chars.next();
+        }
+        if key.is_empty() {
+            Err("expected a key".to_string())
+        } else {
+            Ok(key)
+        }
+    }
+
+    fn string(&mut self) -> Result<String, String> {
+        let literal = if self.eat('\'') {
+            true
+        } else if self.eat('"') {
+            false
+        } else {
+            return Err("expected a string".to_string());
+        };
+
+        let mut s = String::new();
+        loop {
+            match self.chars.next() {
+                None => return Err("unterminated string".to_string()),
+                Some('\'') if literal => return Ok(s),
+                Some('"') if !literal => return Ok(s),
+                Some('\\') if !literal => {
+                    s.push(match self.chars.next() {
+                        Some('"') => '"',
+                        Some('\\') => '\\',
+                        Some('n') => '\n',
+                        Some('t') => '\t',
+                        Some('r') => '\r',
+                        _ => return Err("unknown escape sequence in string".to_string()),
+                    });
+                }
+                Some(c) => s.push(c),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use lint;
+    use serialize::json::Json;
+    use std::path::{Path, PathBuf};
+    use super::{LintConfig, parse_toml};
+
+    #[test]
+    fn toml_and_json_agree() {
+        let toml = parse_toml("
+            # Whole crate.
+            [lints]
+            unused = \"deny\"
+            missing-docs = 'warn' # trailing comment
+
+            [paths.\"src/gen\"]
+            dead_code = \"allow\"
+        ").unwrap();
+        let json = Json::from_str(r#"{
+            "lints": { "unused": "deny", "missing-docs": "warn" },
+            "paths": { "src/gen": { "dead_code": "allow" } }
+        }"#).unwrap();
+        assert_eq!(toml, json);
+
+        let config = LintConfig::from_json(toml, Path::new("/work")).unwrap();
+        assert_eq!(config.levels, vec![(String::from("missing_docs"), lint::Warn),
+                                       (String::from("unused"), lint::Deny)]);
+        assert_eq!(config.paths, vec![(PathBuf::from("/work/src/gen"),
+                                       vec![(String::from("dead_code"), lint::Allow)])]);
+    }
+
+    #[test]
+    fn levels_for_file() {
+        let json = Json::from_str(r#"{
+            "paths": {
+                "src/gen/inner": { "dead_code": "warn" },
+                "src/gen": { "dead_code": "allow" }
+            }
+        }"#).unwrap();
+        let config = LintConfig::from_json(json, Path::new("/work")).unwrap();
+        assert_eq!(config.levels_for_file(Path::new("/work/src/lib.rs")), vec![]);
+        assert_eq!(config.levels_for_file(Path::new("/work/src/gen/inner/mod.rs")),
+                   vec![(String::from("dead_code"), lint::Allow),
+                        (String::from("dead_code"), lint::Warn)]);
+    }
+
+    #[test]
+    fn errors() {
+        assert!(parse_toml("[lints\nunused = \"deny\"").is_err());
+        assert!(parse_toml("unused = deny").is_err());
+        assert!(parse_toml("a = \"x\"\na = \"y\"").is_err());
+        assert!(parse_toml("a = \"x\"\n[a]").is_err());
+
+        let json = Json::from_str(r#"{ "lints": { "unused": "loud" } }"#).unwrap();
+        assert!(LintConfig::from_json(json, Path::new("/work")).is_err());
+        let json = Json::from_str(r#"{ "lint": {} }"#).unwrap();
+        assert!(LintConfig::from_json(json, Path::new("/work")).is_err());
+    }
+}
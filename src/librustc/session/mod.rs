@@ -16,6 +16,7 @@ use middle::cstore::CrateStore;
 use middle::dependency_format;
 use session::search_paths::PathKind;
 use session::config::{DebugInfoLevel, PanicStrategy};
+use session::code_stats::CodeStats;
 use ty::tls;
 use util::nodemap::{NodeMap, FnvHashMap};
 use mir::transform as mir_pass;
@@ -23,6 +24,7 @@ use mir::transform as mir_pass;
 use syntax::ast::{NodeId, Name};
 use errors::{self, DiagnosticBuilder};
 use errors::emitter::{Emitter, EmitterWriter};
+use syntax::fixit::{self, SuggestionCollector};
 use syntax::json::JsonEmitter;
 use syntax::feature_gate;
 use syntax::parse;
@@ -44,8 +46,10 @@ use std::rc::Rc;
 use std::fmt;
 use libc::c_int;
 
+pub mod code_stats;
 pub mod config;
 pub mod filesearch;
+pub mod lint_config;
 pub mod search_paths;
 
 // Represents the data associated with a compilation
@@ -101,6 +105,17 @@ pub struct Session {
     /// macro name and defintion span in the source crate.
     pub imported_macro_spans: RefCell<HashMap<Span, (String, Span)>>,
 
+    /// The edits of the machine-applicable suggestions emitted so far. Only
+    /// recorded with `-Z apply-suggestions`.
+    pub suggestion_replacements: Rc<RefCell<Vec<fixit::Replacement>>>,
+
+    /// Names of the crates passed with `--extern` which name resolution
+    /// found no use of, for the `unused_crate_dependencies` lint.
+    pub unused_extern_names: RefCell<Vec<String>>,
+
+    /// Statistics about the generated code, for the `-Z print-*` options.
+    pub code_stats: RefCell<CodeStats>,
+
     next_node_id: Cell<ast::NodeId>,
 }
 
@@ -374,18 +389,28 @@ pub fn build_session_with_codemap(sopts: config::Options,
             Box::new(JsonEmitter::stderr(Some(registry), codemap.clone()))
         }
     };
+    let suggestion_replacements = Rc::new(RefCell::new(Vec::new()));
+    let emitter: Box<Emitter> = if sopts.debugging_opts.apply_suggestions {
+        Box::new(SuggestionCollector::new(emitter,
+                                          codemap.clone(),
+                                          suggestion_replacements.clone()))
+    } else {
+        emitter
+    };
 
     let diagnostic_handler =
         errors::Handler::with_emitter(can_print_warnings,
                                       treat_err_as_bug,
                                       emitter);
 
-    build_session_(sopts,
-                   dep_graph,
-                   local_crate_source_file,
-                   diagnostic_handler,
-                   codemap,
-                   cstore)
+    let mut sess = build_session_(sopts,
+                                  dep_graph,
+                                  local_crate_source_file,
+                                  diagnostic_handler,
+                                  codemap,
+                                  cstore);
+    sess.suggestion_replacements = suggestion_replacements;
+    sess
 }
 
 pub fn build_session_(sopts: config::Options,
@@ -446,6 +471,9 @@ pub fn build_session_(sopts: config::Options,
         injected_panic_runtime: Cell::new(None),
         available_macros: RefCell::new(HashSet::new()),
         imported_macro_spans: RefCell::new(HashMap::new()),
+        suggestion_replacements: Rc::new(RefCell::new(Vec::new())),
+        unused_extern_names: RefCell::new(Vec::new()),
+        code_stats: RefCell::new(CodeStats::new()),
     };
 
     init_llvm(&sess);
@@ -488,6 +516,7 @@ unsafe fn configure_llvm(sess: &Session) {
         add("rustc"); // fake program name
         if sess.time_llvm_passes() { add("-time-passes"); }
         if sess.print_llvm_passes() { add("-debug-pass=Structure"); }
+        if sess.opts.split_dwarf() { add("-split-dwarf=Enable"); }
 
         for arg in &sess.opts.cg.llvm_args {
             add(&(*arg));
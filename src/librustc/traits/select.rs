@@ -2244,6 +2244,10 @@ impl<'cx, 'gcx, 'tcx> SelectionContext<'cx, 'gcx, 'tcx> {
                obligation,
                impl_def_id);
 
+        if !impl_def_id.is_local() {
+            self.tcx().selected_impl_crates.borrow_mut().insert(impl_def_id.krate);
+        }
+
         // First, create the substitutions by matching the impl again,
         // this time not in a probe.
         self.in_snapshot(|this, snapshot| {
@@ -23,6 +23,7 @@ use middle::free_region::FreeRegionMap;
 use middle::region::RegionMaps;
 use middle::resolve_lifetime;
 use middle::stability;
+use mir::repr::Mir;
 use ty::subst::Substs;
 use traits;
 use ty::{self, TraitRef, Ty, TypeAndMut};
@@ -428,6 +429,19 @@ pub struct GlobalCtxt<'tcx> {
     /// Cache used by const_eval when decoding extern const fns
     pub extern_const_fns: RefCell<DefIdMap<NodeId>>,
 
+    /// Builds the MIR of a `const fn` of the current crate once its body has
+    /// been type-checked, so that calls to it can be evaluated before the MIR
+    /// of the whole crate is built. Set by the driver, as MIR construction
+    /// lives in `rustc_mir`.
+    pub const_fn_mir_builder: Cell<Option<for<'a> fn(TyCtxt<'a, 'tcx, 'tcx>, DefId)
+                                                     -> Option<Mir<'tcx>>>>,
+    /// Cache of the MIR built by `const_fn_mir_builder`.
+    pub local_const_fn_mir: RefCell<DefIdMap<Rc<Mir<'tcx>>>>,
+
+    /// Other crates which trait selection has picked impls from, for the
+    /// `unused_crate_dependencies` lint.
+    pub selected_impl_crates: RefCell<FnvHashSet<ast::CrateNum>>,
+
     /// Maps any item's def-id to its stability index.
     pub stability: RefCell<stability::Index<'tcx>>,
 
@@ -745,6 +759,9 @@ impl<'a, 'gcx, 'tcx> TyCtxt<'a, 'gcx, 'tcx> {
             populated_external_primitive_impls: RefCell::new(DefIdSet()),
             extern_const_statics: RefCell::new(DefIdMap()),
             extern_const_fns: RefCell::new(DefIdMap()),
+            const_fn_mir_builder: Cell::new(None),
+            local_const_fn_mir: RefCell::new(DefIdMap()),
+            selected_impl_crates: RefCell::new(FnvHashSet()),
             stability: RefCell::new(stability),
             selection_cache: traits::SelectionCache::new(),
             evaluation_cache: traits::EvaluationCache::new(),
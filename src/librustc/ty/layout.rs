@@ -450,8 +450,24 @@ impl Primitive {
 // FIXME(eddyb) use small vector optimization for the common case.
 pub type FieldPath = Vec<u32>;
 
+/// How `Struct::extend_reordered` may place the fields of a structure.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum StructKind {
+    /// The fields must stay in source order, e.g. due to `#[repr(C)]`.
+    SourceOrder,
+    /// A struct, tuple or closure whose fields may all be reordered.
+    AlwaysSized,
+    /// A struct or tuple whose last field may be unsized for some type
+    /// parameters, so it must stay last to be reachable through an
+    /// unsized pointer.
+    MaybeUnsized,
+    /// The fields of an enum variant, which follow its discriminant:
+    /// smaller fields go first, to fill the discriminant's padding.
+    EnumVariant
+}
+
 /// A structure, a product type in ADT terms.
-#[derive(PartialEq, Eq, Hash, Debug)]
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
 pub struct Struct {
     pub align: Align,
 
@@ -461,11 +477,17 @@ pub struct Struct {
     /// If true, the size is exact, otherwise it's only a lower bound.
     pub sized: bool,
 
-    /// Offsets for the first byte after each field.
-    /// That is, field_offset(i) = offset_after_field[i - 1] and the
-    /// whole structure's size is the last offset, excluding padding.
+    /// Offsets for the first byte after each field, in memory order.
+    /// That is, the offset of the field at memory_index[i] is
+    /// offset_after_field[memory_index[i] - 1] and the whole
+    /// structure's size is the last offset, excluding padding.
     // FIXME(eddyb) use small vector optimization for the common case.
-    pub offset_after_field: Vec<Size>
+    pub offset_after_field: Vec<Size>,
+
+    /// The position in memory of each field, by source order index.
+    /// Fields are reordered to reduce padding, unless `StructKind`
+    /// says otherwise, so LLVM GEPs should use these indices.
+    pub memory_index: Vec<u32>
 }
 
 impl<'a, 'gcx, 'tcx> Struct {
@@ -474,7 +496,8 @@ impl<'a, 'gcx, 'tcx> Struct {
             align: if packed { dl.i8_align } else { dl.aggregate_align },
             packed: packed,
             sized: true,
-            offset_after_field: vec![]
+            offset_after_field: vec![],
+            memory_index: vec![]
         }
     }
 
@@ -485,6 +508,7 @@ impl<'a, 'gcx, 'tcx> Struct {
                      -> Result<(), LayoutError<'gcx>>
     where I: Iterator<Item=Result<&'a Layout, LayoutError<'gcx>>> {
         self.offset_after_field.reserve(fields.size_hint().0);
+        self.memory_index.reserve(fields.size_hint().0);
 
         for field in fields {
             if !self.sized {
@@ -512,12 +536,81 @@ impl<'a, 'gcx, 'tcx> Struct {
             offset = offset.checked_add(field.size(dl), dl)
                            .map_or(Err(LayoutError::SizeOverflow(scapegoat)), Ok)?;
 
+            self.memory_index.push(self.offset_after_field.len() as u32);
             self.offset_after_field.push(offset);
         }
 
         Ok(())
     }
 
+    /// Extend the Struct with more fields, placing them in whichever order
+    /// `kind` allows that makes the structure smallest. Fields are only
+    /// moved if that saves space, so ties keep the source order.
+    pub fn extend_reordered(&mut self, dl: &TargetDataLayout,
+                            fields: Vec<&'a Layout>,
+                            kind: StructKind,
+                            scapegoat: Ty<'gcx>)
+                            -> Result<(), LayoutError<'gcx>> {
+        if kind == StructKind::SourceOrder || self.packed {
+            return self.extend(dl, fields.into_iter().map(Ok), scapegoat);
+        }
+
+        // A field which must stay last takes no part in the decision,
+        // so that it's the same for all the sizes it may be unsized to.
+        let keep_last = kind == StructKind::MaybeUnsized ||
+                        fields.last().map_or(false, |field| field.is_unsized());
+        let end = if keep_last && !fields.is_empty() {
+            fields.len() - 1
+        } else {
+            fields.len()
+        };
+
+        let start = self.offset_after_field.len();
+        let mut order: Vec<usize> = (0..end).collect();
+        let align = |i: usize| fields[i].align(dl).abi();
+        if kind == StructKind::EnumVariant {
+            order.sort_by_key(|&i| align(i));
+        } else {
+            order.sort_by(|&a, &b| align(b).cmp(&align(a)));
+        }
+
+        let mut reordered = self.clone();
+        reordered.extend(dl, order.iter().map(|&i| Ok(fields[i])), scapegoat)?;
+        self.extend(dl, fields[..end].iter().map(|&field| Ok(field)), scapegoat)?;
+        if reordered.stride() < self.stride() {
+            for (memory, &i) in order.iter().enumerate() {
+                reordered.memory_index[start + i] = (start + memory) as u32;
+            }
+            *self = reordered;
+        }
+        self.extend(dl, fields[end..].iter().map(|&field| Ok(field)), scapegoat)
+    }
+
+    /// Get the offset of a field, by source order index.
+    pub fn field_offset(&self, i: usize) -> Size {
+        match self.memory_index[i] {
+            0 => Size::from_bytes(0),
+            memory => self.offset_after_field[memory as usize - 1]
+        }
+    }
+
+    /// How the fields of the given struct or enum may be reordered.
+    pub fn kind_of(tcx: TyCtxt<'a, 'gcx, 'tcx>, def: ty::AdtDef<'gcx>) -> StructKind {
+        let hints = tcx.lookup_repr_hints(def.did);
+        if hints.contains(&attr::ReprExtern) || hints.contains(&attr::ReprPacked) ||
+           tcx.has_attr(def.did, "no_field_reorder") {
+            return StructKind::SourceOrder;
+        }
+        if def.adt_kind() == ty::AdtKind::Enum {
+            return StructKind::EnumVariant;
+        }
+        // Conservatively, a field of generic type may be unsized.
+        match def.struct_variant().fields.last() {
+            Some(field) if field.unsubst_ty().has_param_types() => StructKind::MaybeUnsized,
+            _ => StructKind::AlwaysSized
+        }
+    }
+
     /// Get the size without trailing alignment padding.
     pub fn min_size(&self) -> Size {
         self.offset_after_field.last().map_or(Size::from_bytes(0), |&last| last)
@@ -573,25 +666,27 @@ impl<'a, 'gcx, 'tcx> Struct {
 
             // Perhaps one of the fields of this struct is non-zero
             // let's recurse and find out
-            (_, &ty::TyStruct(def, substs)) => {
+            (&Univariant { ref variant, .. }, &ty::TyStruct(def, substs)) => {
                 Struct::non_zero_field_path(infcx, def.struct_variant().fields
                                                       .iter().map(|field| {
                     field.ty(tcx, substs)
-                }))
+                }), Some(&variant.memory_index[..]))
             }
 
             // Perhaps one of the upvars of this closure is non-zero
             // Let's recurse and find out!
-            (_, &ty::TyClosure(_, ty::ClosureSubsts { upvar_tys: tys, .. })) |
+            (&Univariant { ref variant, .. },
+             &ty::TyClosure(_, ty::ClosureSubsts { upvar_tys: tys, .. })) |
             // Can we use one of the fields in this tuple?
-            (_, &ty::TyTuple(tys)) => {
-                Struct::non_zero_field_path(infcx, tys.iter().cloned())
+            (&Univariant { ref variant, .. }, &ty::TyTuple(tys)) => {
+                Struct::non_zero_field_path(infcx, tys.iter().cloned(),
+                                            Some(&variant.memory_index[..]))
             }
 
             // Is this a fixed-size array of something non-zero
             // with at least one element?
             (_, &ty::TyArray(ety, d)) if d > 0 => {
-                Struct::non_zero_field_path(infcx, Some(ety).into_iter())
+                Struct::non_zero_field_path(infcx, Some(ety).into_iter(), None)
             }
 
             (_, &ty::TyProjection(_)) | (_, &ty::TyAnon(..)) => {
@@ -609,18 +704,146 @@ impl<'a, 'gcx, 'tcx> Struct {
 
     /// Find the path leading to a non-zero leaf field, starting from
     /// the given set of fields and recursing through aggregates.
+    /// The path uses `memory_index` to map field indices, if given.
     pub fn non_zero_field_path<I>(infcx: &InferCtxt<'a, 'gcx, 'tcx>,
-                                  fields: I)
+                                  fields: I,
+                                  memory_index: Option<&[u32]>)
                                   -> Result<Option<FieldPath>, LayoutError<'gcx>>
     where I: Iterator<Item=Ty<'gcx>> {
         for (i, ty) in fields.enumerate() {
             if let Some(mut path) = Struct::non_zero_field_in_type(infcx, ty)? {
-                path.push(i as u32);
+                path.push(memory_index.map_or(i as u32, |memory_index| memory_index[i]));
                 return Ok(Some(path));
             }
         }
         Ok(None)
     }
+
+    /// Find a leaf field with at least `count` values it never holds,
+    /// starting from the given type and recursing through aggregates.
+    pub fn niche_in_type(infcx: &InferCtxt<'a, 'gcx, 'tcx>,
+                         ty: Ty<'gcx>,
+                         count: u64)
+                         -> Result<Option<Niche>, LayoutError<'gcx>> {
+        let tcx = infcx.tcx.global_tcx();
+        let leaf = |value, valid| Niche { path: vec![], value: value, valid: valid };
+        let niche = match (ty.layout(infcx)?, &ty.sty) {
+            // A bool is a byte in memory, of which only 0 and 1 are valid.
+            (_, &ty::TyBool) => leaf(Int(I8), (0, 1)),
+            (_, &ty::TyChar) => leaf(Int(I32), (0, 0x10FFFF)),
+
+            (&CEnum { discr, min, max, .. }, _) => leaf(Int(discr), (min, max)),
+            (&General { discr, ref variants, .. }, _) => {
+                Niche {
+                    path: vec![0],
+                    value: Int(discr),
+                    valid: (0, variants.len() as u64 - 1)
+                }
+            }
+            (&NicheFilling { ref niche, niche_value, niche_valid, .. }, _) => {
+                Niche {
+                    path: niche[1..].iter().rev().cloned().collect(),
+                    value: niche_value,
+                    valid: niche_valid
+                }
+            }
+
+            // Perhaps the only variant of this enum has a niche.
+            (&Univariant { ref variant, .. }, &ty::TyEnum(def, substs))
+                    if def.variants.len() == 1 => {
+                return Struct::niche_field_path(infcx, def.variants[0].fields
+                                                          .iter().map(|field| {
+                    field.ty(tcx, substs)
+                }), Some(&variant.memory_index[..]), count);
+            }
+
+            // Fields of packed structs may be misaligned, so leave them be.
+            (&Univariant { ref variant, .. }, &ty::TyStruct(def, substs))
+                    if !tcx.lookup_packed(def.did) => {
+                return Struct::niche_field_path(infcx, def.struct_variant().fields
+                                                          .iter().map(|field| {
+                    field.ty(tcx, substs)
+                }), Some(&variant.memory_index[..]), count);
+            }
+
+            (&Univariant { ref variant, .. },
+             &ty::TyClosure(_, ty::ClosureSubsts { upvar_tys: tys, .. })) |
+            (&Univariant { ref variant, .. }, &ty::TyTuple(tys)) => {
+                return Struct::niche_field_path(infcx, tys.iter().cloned(),
+                                                Some(&variant.memory_index[..]), count);
+            }
+
+            (_, &ty::TyArray(ety, d)) if d > 0 => {
+                return Struct::niche_field_path(infcx, Some(ety).into_iter(), None, count);
+            }
+
+            (_, &ty::TyProjection(_)) | (_, &ty::TyAnon(..)) => {
+                let normalized = normalize_associated_type(infcx, ty);
+                if ty == normalized {
+                    return Ok(None);
+                }
+                return Struct::niche_in_type(infcx, normalized, count);
+            }
+
+            _ => return Ok(None)
+        };
+
+        if niche.available(&tcx.data_layout) >= count {
+            Ok(Some(niche))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Find a leaf field with at least `count` values it never holds,
+    /// starting from the given set of fields and recursing through aggregates.
+    /// The path uses `memory_index` to map field indices, if given.
+    pub fn niche_field_path<I>(infcx: &InferCtxt<'a, 'gcx, 'tcx>,
+                               fields: I,
+                               memory_index: Option<&[u32]>,
+                               count: u64)
+                               -> Result<Option<Niche>, LayoutError<'gcx>>
+    where I: Iterator<Item=Ty<'gcx>> {
+        for (i, ty) in fields.enumerate() {
+            if let Some(mut niche) = Struct::niche_in_type(infcx, ty, count)? {
+                niche.path.push(memory_index.map_or(i as u32, |memory_index| memory_index[i]));
+                return Ok(Some(niche));
+            }
+        }
+        Ok(None)
+    }
+}
+
+/// An integer leaf field which never holds some of its values, found by
+/// `Struct::niche_in_type`, which an enum can store its discriminant in.
+#[derive(Debug)]
+pub struct Niche {
+    /// Path to the field, innermost first, like `Struct::non_zero_field_path`.
+    pub path: FieldPath,
+    pub value: Primitive,
+    /// Inclusive range of the values the field holds, which may wrap around.
+    pub valid: (u64, u64)
+}
+
+impl Niche {
+    fn mask(&self, dl: &TargetDataLayout) -> u64 {
+        !0 >> (64 - self.value.size(dl).bits())
+    }
+
+    /// The number of values the field never holds.
+    pub fn available(&self, dl: &TargetDataLayout) -> u64 {
+        self.valid.0.wrapping_sub(self.valid.1).wrapping_sub(1) & self.mask(dl)
+    }
+
+    /// Take `count` of the values the field never holds, returning the first
+    /// of them and the new range of values the field holds.
+    pub fn reserve(&self, dl: &TargetDataLayout, count: u64) -> (u64, (u64, u64)) {
+        assert!(count > 0 && count <= self.available(dl));
+        let mask = self.mask(dl);
+        let start = self.valid.1.wrapping_add(1) & mask;
+        let end = self.valid.1.wrapping_add(count) & mask;
+        (start, (self.valid.0 & mask, end))
+    }
 }
 
 /// The first half of a fat pointer.
@@ -722,6 +945,32 @@ pub enum Layout {
         nonnull: Struct,
         // N.B. There is a 0 at the start, for LLVM GEP through a pointer.
         discrfield: FieldPath
+    },
+
+    /// Several cases, all but one of them zero sized, distinguished by a niche:
+    /// the case with discriminant `dataful` is represented by the struct
+    /// `variant`, where the `niche`th field is an integer which never holds
+    /// some of its values due to its type (e.g. a `bool`, a `char`, or the
+    /// discriminant of another enum). The other cases are stored as those
+    /// values, in order: discriminant `d` in `niche_variants` is represented
+    /// by `niche_start + (d - niche_variants.0)`, wrapping around.
+    ///
+    /// For example, `Option<Option<bool>>` is a single byte, where 0 and 1
+    /// are `Some(Some(_))`, 2 is `Some(None)` and 3 is `None`.
+    NicheFilling {
+        dataful: u64,
+        variant: Struct,
+        // N.B. There is a 0 at the start, for LLVM GEP through a pointer.
+        niche: FieldPath,
+        niche_value: Primitive,
+        niche_start: u64,
+        // Inclusive range of the discriminants stored in the niche,
+        // which may include `dataful` itself.
+        niche_variants: (u64, u64),
+        // Inclusive range of the values the niche field holds, counting
+        // those used for `niche_variants`, which may wrap around like
+        // CEnum's; lets an enum containing this one use the rest.
+        niche_valid: (u64, u64)
     }
 }
 
@@ -860,8 +1109,16 @@ impl<'a, 'gcx, 'tcx> Layout {
             // Tuples.
             ty::TyClosure(_, ty::ClosureSubsts { upvar_tys: tys, .. }) |
             ty::TyTuple(tys) => {
+                // Only the last element of a tuple may be unsized.
+                let kind = if let ty::TyTuple(_) = ty.sty {
+                    StructKind::MaybeUnsized
+                } else {
+                    StructKind::AlwaysSized
+                };
+                let fields = tys.iter().map(|ty| ty.layout(infcx))
+                                .collect::<Result<Vec<_>, _>>()?;
                 let mut st = Struct::new(dl, false);
-                st.extend(dl, tys.iter().map(|ty| ty.layout(infcx)), ty)?;
+                st.extend_reordered(dl, fields, kind, ty)?;
                 Univariant { variant: st, non_zero: false }
             }
 
@@ -886,10 +1143,10 @@ impl<'a, 'gcx, 'tcx> Layout {
                 }
                 let fields = def.struct_variant().fields.iter().map(|field| {
                     field.ty(tcx, substs).layout(infcx)
-                });
+                }).collect::<Result<Vec<_>, _>>()?;
                 let packed = tcx.lookup_packed(def.did);
                 let mut st = Struct::new(dl, packed);
-                st.extend(dl, fields, ty)?;
+                st.extend_reordered(dl, fields, Struct::kind_of(tcx, def), ty)?;
 
                 Univariant {
                     variant: st,
@@ -939,15 +1196,24 @@ impl<'a, 'gcx, 'tcx> Layout {
                     }
                 }
 
+                // Variants laid out without a discriminant in front of
+                // their fields have them reordered like those of structs.
+                let kind = Struct::kind_of(tcx, def);
+                let struct_kind = if kind == StructKind::EnumVariant {
+                    StructKind::AlwaysSized
+                } else {
+                    kind
+                };
+
                 if def.variants.len() == 1 {
                     // Equivalent to a struct/tuple/newtype.
                     // (Typechecking will reject discriminant-sizing attrs.)
                     assert_eq!(hint, attr::ReprAny);
                     let fields = def.variants[0].fields.iter().map(|field| {
                         field.ty(tcx, substs).layout(infcx)
-                    });
+                    }).collect::<Result<Vec<_>, _>>()?;
                     let mut st = Struct::new(dl, false);
-                    st.extend(dl, fields, ty)?;
+                    st.extend_reordered(dl, fields, struct_kind, ty)?;
                     return success(Univariant { variant: st, non_zero: false });
                 }
 
@@ -965,8 +1231,12 @@ impl<'a, 'gcx, 'tcx> Layout {
                         if !Struct::would_be_zero_sized(dl, other_fields)? {
                             continue;
                         }
+                        let fields = variants[discr].iter().map(|ty| ty.layout(infcx))
+                                                    .collect::<Result<Vec<_>, _>>()?;
+                        let mut st = Struct::new(dl, false);
+                        st.extend_reordered(dl, fields, struct_kind, ty)?;
                         let path = Struct::non_zero_field_path(infcx,
-                            variants[discr].iter().cloned())?;
+                            variants[discr].iter().cloned(), Some(&st.memory_index[..]))?;
                         let mut path = if let Some(p) = path { p } else { continue };
 
                         // FIXME(eddyb) should take advantage of a newtype.
@@ -988,8 +1258,6 @@ impl<'a, 'gcx, 'tcx> Layout {
 
                         path.push(0); // For GEP through a pointer.
                         path.reverse();
-                        let mut st = Struct::new(dl, false);
-                        st.extend(dl, variants[discr].iter().map(|ty| ty.layout(infcx)), ty)?;
                         return success(StructWrappedNullablePointer {
                             nndiscr: discr as u64,
                             nonnull: st,
@@ -998,6 +1266,61 @@ impl<'a, 'gcx, 'tcx> Layout {
                     }
                 }
 
+                if hint == attr::ReprAny {
+                    // Niche-filling optimization: if only one case has data,
+                    // look for a field in it which never holds enough of its
+                    // values to store the discriminants of all the others.
+                    let mut dataful = vec![];
+                    let mut zst_align = dl.i8_align;
+                    for (i, fields) in variants.iter().enumerate() {
+                        let fields = fields.iter().map(|ty| ty.layout(infcx));
+                        if !Struct::would_be_zero_sized(dl, fields)? {
+                            dataful.push(i);
+                            continue;
+                        }
+                        for ty in &variants[i] {
+                            zst_align = zst_align.max(ty.layout(infcx)?.align(dl));
+                        }
+                    }
+
+                    if dataful.len() == 1 {
+                        let dataful = dataful[0];
+                        let first = if dataful == 0 { 1 } else { 0 };
+                        let last = if dataful == variants.len() - 1 {
+                            dataful - 1
+                        } else {
+                            variants.len() - 1
+                        };
+                        let count = (last - first + 1) as u64;
+
+                        let fields = variants[dataful].iter().map(|ty| ty.layout(infcx))
+                                                      .collect::<Result<Vec<_>, _>>()?;
+                        let mut st = Struct::new(dl, false);
+                        st.extend_reordered(dl, fields, struct_kind, ty)?;
+                        let niche = Struct::niche_field_path(infcx,
+                            variants[dataful].iter().cloned(), Some(&st.memory_index[..]),
+                            count)?;
+
+                        // The other cases must not need more alignment than the
+                        // dataful one, as they share its representation.
+                        if let (Some(niche), true) = (niche, zst_align.abi() <= st.align.abi()) {
+                            let (niche_start, niche_valid) = niche.reserve(dl, count);
+                            let mut path = niche.path;
+                            path.push(0); // For GEP through a pointer.
+                            path.reverse();
+                            return success(NicheFilling {
+                                dataful: dataful as u64,
+                                variant: st,
+                                niche: path,
+                                niche_value: niche.value,
+                                niche_start: niche_start,
+                                niche_variants: (first as u64, last as u64),
+                                niche_valid: niche_valid
+                            });
+                        }
+                    }
+                }
+
                 // The general case.
                 let discr_max = (variants.len() - 1) as i64;
                 assert!(discr_max >= 0);
@@ -1013,22 +1336,26 @@ impl<'a, 'gcx, 'tcx> Layout {
                 // Use the minimum integer type we figured out above
                 let discr = Some(Scalar { value: Int(min_ity), non_zero: false });
                 let mut variants = variants.into_iter().map(|fields| {
-                    let mut found_start = false;
-                    let fields = fields.into_iter().map(|field| {
-                        let field = field.layout(infcx)?;
-                        if !found_start {
-                            // Find the first field we can't move later
-                            // to make room for a larger discriminant.
-                            let field_align = field.align(dl);
-                            if field.size(dl).bytes() != 0 || field_align.abi() != 1 {
-                                start_align = start_align.min(field_align);
-                                found_start = true;
-                            }
-                        }
-                        Ok(field)
-                    });
+                    let fields = fields.into_iter().map(|field| field.layout(infcx))
+                                       .collect::<Result<Vec<_>, _>>()?;
                     let mut st = Struct::new(dl, false);
-                    st.extend(dl, discr.iter().map(Ok).chain(fields), ty)?;
+                    st.extend(dl, discr.iter().map(Ok), ty)?;
+                    st.extend_reordered(dl, fields.clone(), kind, ty)?;
+
+                    // Find the first field in memory we can't move later
+                    // to make room for a larger discriminant.
+                    let mut in_memory = fields.iter().enumerate().map(|(i, &field)| {
+                        (st.memory_index[i + 1], field)
+                    }).collect::<Vec<_>>();
+                    in_memory.sort_by_key(|&(memory, _)| memory);
+                    for &(_, field) in &in_memory {
+                        let field_align = field.align(dl);
+                        if field.size(dl).bytes() != 0 || field_align.abi() != 1 {
+                            start_align = start_align.min(field_align);
+                            break;
+                        }
+                    }
+
                     size = cmp::max(size, st.min_size());
                     align = align.max(st.align);
                     Ok(st)
@@ -1117,7 +1444,8 @@ impl<'a, 'gcx, 'tcx> Layout {
             Scalar {..} | Vector {..} | FatPointer {..} |
             CEnum {..} | General {..} |
             RawNullablePointer {..} |
-            StructWrappedNullablePointer {..} => false,
+            StructWrappedNullablePointer {..} |
+            NicheFilling {..} => false,
 
             Array { sized, .. } |
             Univariant { variant: Struct { sized, .. }, .. } => !sized
@@ -1151,7 +1479,8 @@ impl<'a, 'gcx, 'tcx> Layout {
             Array { size, .. } | General { size, .. } => size,
 
             Univariant { ref variant, .. } |
-            StructWrappedNullablePointer { nonnull: ref variant, .. } => {
+            StructWrappedNullablePointer { nonnull: ref variant, .. } |
+            NicheFilling { ref variant, .. } => {
                 variant.stride()
             }
         }
@@ -1190,7 +1519,8 @@ impl<'a, 'gcx, 'tcx> Layout {
             Array { align, .. } | General { align, .. } => align,
 
             Univariant { ref variant, .. } |
-            StructWrappedNullablePointer { nonnull: ref variant, .. } => {
+            StructWrappedNullablePointer { nonnull: ref variant, .. } |
+            NicheFilling { ref variant, .. } => {
                 variant.align
             }
         }
@@ -22,6 +22,7 @@ use std::time::Instant;
 use hir;
 use hir::intravisit;
 use hir::intravisit::Visitor;
+use util::profiling::{self, Category};
 
 // The name of the associated type for `Fn` return types
 pub const FN_OUTPUT_NAME: &'static str = "Output";
@@ -35,7 +36,7 @@ pub fn time<T, F>(do_it: bool, what: &str, f: F) -> T where
     F: FnOnce() -> T,
 {
     thread_local!(static DEPTH: Cell<usize> = Cell::new(0));
-    if !do_it { return f(); }
+    if !do_it { return profiling::record(Category::Pass, what, f); }
 
     let old = DEPTH.with(|slot| {
         let r = slot.get();
@@ -44,7 +45,7 @@ pub fn time<T, F>(do_it: bool, what: &str, f: F) -> T where
     });
 
     let start = Instant::now();
-    let rv = f();
+    let rv = profiling::record(Category::Pass, what, f);
     let dur = start.elapsed();
 
     // Hack up our own formatting for the duration to make it easier for scripts
@@ -0,0 +1,190 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Structured self-profiling of the compiler, enabled with
+//! `-Z self-profile=<file>`.
+//!
+//! While a profile is being recorded, every pass timed through
+//! `util::common::time` is recorded as an event, along with some finer
+//! grained work such as type checking and borrow checking of individual
+//! items, or the optimization and code generation of each LLVM module. At the
+//! end of compilation the events are written out in the Chrome `trace_event`
+//! format, which can be loaded into `chrome://tracing` or any other viewer
+//! understanding that format.
+//!
+//! The profiler is installed per thread. Threads spawned by the compiler
+//! which want their work to show up in the profile (like the LLVM workers in
+//! `back::write`) need to grab the `current()` profiler before they're
+//! spawned and `enter_thread` it once they're running.
+
+use std::cell::RefCell;
+use std::fs::File;
+use std::io::{self, Write};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use serialize::json;
+
+/// The kind of work an event describes. This becomes the event category in
+/// the trace, which viewers let you filter on.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Category {
+    /// A compiler pass, as timed by `-Z time-passes`.
+    Pass,
+    /// Analysis of a single item, e.g. type checking of one function body.
+    Item,
+    /// LLVM work on a single module.
+    Llvm,
+}
+
+impl Category {
+    fn name(&self) -> &'static str {
+        match *self {
+            Category::Pass => "pass",
+            Category::Item => "item",
+            Category::Llvm => "llvm",
+        }
+    }
+}
+
+struct Event {
+    name: String,
+    category: Category,
+    thread: usize,
+    start: Duration,
+    duration: Duration,
+}
+
+/// A profile being recorded. Shared between all threads taking part in the
+/// compilation.
+pub struct Profiler {
+    epoch: Instant,
+    events: Mutex<Vec<Event>>,
+}
+
+thread_local!(static CURRENT: RefCell<Option<(Arc<Profiler>, usize)>> = RefCell::new(None));
+
+/// Starts recording a new profile on the current thread, which is
+/// considered to be the main thread of the compilation.
+pub fn start() {
+    let profiler = Arc::new(Profiler {
+        epoch: Instant::now(),
+        events: Mutex::new(Vec::new()),
+    });
+    enter_thread(Some(profiler), 0);
+}
+
+/// The profile being recorded on the current thread, if any.
+pub fn current() -> Option<Arc<Profiler>> {
+    CURRENT.with(|c| c.borrow().as_ref().map(|&(ref p, _)| p.clone()))
+}
+
+/// Makes the current thread record its events into `profiler`, under the
+/// given thread number. Passing `None` does nothing.
+pub fn enter_thread(profiler: Option<Arc<Profiler>>, thread: usize) {
+    if let Some(profiler) = profiler {
+        CURRENT.with(|c| *c.borrow_mut() = Some((profiler, thread)));
+    }
+}
+
+pub fn is_enabled() -> bool {
+    CURRENT.with(|c| c.borrow().is_some())
+}
+
+/// Runs `f`, recording how long it took under `name` if a profile is being
+/// recorded.
+pub fn record<T, F>(category: Category, name: &str, f: F) -> T where
+    F: FnOnce() -> T,
+{
+    record_with(category, || name.to_string(), f)
+}
+
+/// Like `record`, but only computes the name of the event if it's actually
+/// going to be recorded, for when building the name isn't free.
+pub fn record_with<T, N, F>(category: Category, name: N, f: F) -> T where
+    N: FnOnce() -> String,
+    F: FnOnce() -> T,
+{
+    let (profiler, thread) = match CURRENT.with(|c| c.borrow().clone()) {
+        Some(current) => current,
+        None => return f(),
+    };
+
+    let start = Instant::now();
+    let rv = f();
+    let duration = start.elapsed();
+
+    let event = Event {
+        name: name(),
+        category: category,
+        thread: thread,
+        start: start.duration_since(profiler.epoch),
+        duration: duration,
+    };
+    profiler.events.lock().unwrap().push(event);
+
+    rv
+}
+
+#[derive(RustcEncodable)]
+struct TraceEvent<'a> {
+    name: &'a str,
+    cat: &'static str,
+    ph: &'static str,
+    ts: u64,
+    dur: u64,
+    pid: u32,
+    tid: usize,
+}
+
+#[derive(RustcEncodable)]
+#[allow(non_snake_case)]
+struct Trace<'a> {
+    traceEvents: Vec<TraceEvent<'a>>,
+    displayTimeUnit: &'static str,
+}
+
+fn micros(d: Duration) -> u64 {
+    d.as_secs() * 1_000_000 + (d.subsec_nanos() / 1_000) as u64
+}
+
+/// Stops recording the profile of the current thread and writes it to `path`
+/// as a Chrome trace. Does nothing if no profile was being recorded.
+pub fn finish(path: &Path) -> io::Result<()> {
+    let profiler = match CURRENT.with(|c| c.borrow_mut().take()) {
+        Some((profiler, _)) => profiler,
+        None => return Ok(()),
+    };
+
+    let mut events = profiler.events.lock().unwrap();
+    // Events are recorded when they end, sort them so that they appear in the
+    // order they began.
+    events.sort_by_key(|e| (e.start, e.thread));
+
+    let trace = Trace {
+        traceEvents: events.iter().map(|e| {
+            TraceEvent {
+                name: &e.name,
+                cat: e.category.name(),
+                // A "complete" event, which has both a start and a duration.
+                ph: "X",
+                ts: micros(e.start),
+                dur: micros(e.duration),
+                pid: 1,
+                tid: e.thread,
+            }
+        }).collect(),
+        displayTimeUnit: "ms",
+    };
+
+    let mut file = File::create(path)?;
+    write!(file, "{}", json::as_json(&trace))
+}
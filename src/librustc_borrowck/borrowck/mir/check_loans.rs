@@ -0,0 +1,366 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// ----------------------------------------------------------------------
+// Checking loans on the MIR
+//
+// This is the MIR counterpart of `borrowck::check_loans`, enabled by
+// `-Z borrowck-mir`. The `Borrows` dataflow tells which loans may be in
+// effect on entry to each basic block; we follow it through the block one
+// statement at a time and check that:
+// 1. assignments do not affect things which are borrowed;
+// 2. moves do not affect things which are borrowed;
+// 3. uses do not read things which are mutably borrowed;
+// 4. new loans do not conflict with the loans in effect.
+//
+// Errors reuse the codes of the AST checker, with a "(Mir)" suffix to
+// tell them apart when both checkers run.
+
+use rustc::hir;
+use rustc::mir::repr::{self, BorrowKind, Location, Lvalue, ProjectionElem};
+use rustc::mir::tcx::LvalueTy;
+use rustc::mir::visit::{LvalueContext, Visitor};
+use rustc::ty;
+use rustc_data_structures::indexed_vec::Idx;
+use syntax::parse::token::keywords;
+use syntax_pos::Span;
+
+use super::MirBorrowckCtxt;
+use super::dataflow::BorrowData;
+
+impl<'b, 'a: 'b, 'tcx: 'a> MirBorrowckCtxt<'b, 'a, 'tcx> {
+    fn span_of(&self, location: Location) -> Span {
+        let data = &self.mir[location.block];
+        match data.statements.get(location.statement_index) {
+            Some(stmt) => stmt.source_info.span,
+            None => data.terminator().source_info.span,
+        }
+    }
+
+    /// Returns the first loan in effect at `span` satisfying `pred`.
+    fn find_borrow_in_effect<P>(&self, span: Span, mut pred: P) -> Option<&BorrowData<'tcx>>
+        where P: FnMut(&BorrowData<'tcx>) -> bool
+    {
+        self.flow_borrows.operator().borrows().iter_enumerated().find(|&(index, borrow)| {
+            self.borrows_in_effect.contains(&index) &&
+                borrow.is_in_scope_at(span) &&
+                self.is_tracked(&borrow.lvalue) &&
+                pred(borrow)
+        }).map(|(_, borrow)| borrow)
+    }
+
+    /// Whether loans of `lvalue` restrict anything. Like in the AST
+    /// checker, statics and data reached through `&` or `*` pointers are
+    /// left alone, as they are aliased anyway.
+    fn is_tracked(&self, lvalue: &Lvalue<'tcx>) -> bool {
+        match *lvalue {
+            Lvalue::Static(_) => false,
+            Lvalue::Var(_) | Lvalue::Temp(_) | Lvalue::Arg(_) | Lvalue::ReturnPointer => true,
+            Lvalue::Projection(ref proj) => {
+                if let ProjectionElem::Deref = proj.elem {
+                    let base_ty = proj.base.ty(self.mir, self.bcx.tcx).to_ty(self.bcx.tcx);
+                    match base_ty.sty {
+                        ty::TyRef(_, ty::TypeAndMut { mutbl: hir::MutImmutable, .. }) |
+                        ty::TyRawPtr(_) => return false,
+                        _ => {}
+                    }
+                }
+                self.is_tracked(&proj.base)
+            }
+        }
+    }
+
+    fn check_assignment(&self, lvalue: &Lvalue<'tcx>, span: Span, is_call_dest: bool) {
+        let conflict = self.find_borrow_in_effect(span, |borrow| {
+            // The loans of the arguments of a call end with the call, so
+            // they are over by the time its destination is written.
+            if is_call_dest {
+                if let Some(region_span) = borrow.region_span {
+                    if region_span.hi <= span.hi {
+                        return false;
+                    }
+                }
+            }
+            lvalues_overlap(&borrow.lvalue, lvalue)
+        });
+        if let Some(borrow) = conflict {
+            let desc = self.describe_lvalue(&borrow.lvalue);
+            struct_span_err!(self.bcx, span, E0506,
+                             "cannot assign to `{}` because it is borrowed (Mir)",
+                             self.describe_lvalue(lvalue))
+                .span_label(borrow.span, &format!("borrow of `{}` occurs here", desc))
+                .span_label(span, &format!("assignment to borrowed `{}` occurs here", desc))
+                .emit();
+        }
+    }
+
+    fn check_move(&self, lvalue: &Lvalue<'tcx>, span: Span) {
+        let conflict = self.find_borrow_in_effect(span, |borrow| {
+            lvalues_overlap(&borrow.lvalue, lvalue)
+        });
+        if let Some(borrow) = conflict {
+            let desc = self.describe_lvalue(lvalue);
+            struct_span_err!(self.bcx, span, E0505,
+                             "cannot move out of `{}` because it is borrowed (Mir)",
+                             desc)
+                .span_label(borrow.span,
+                            &format!("borrow of `{}` occurs here",
+                                     self.describe_lvalue(&borrow.lvalue)))
+                .span_label(span, &format!("move out of `{}` occurs here", desc))
+                .emit();
+        }
+    }
+
+    fn check_use(&self, lvalue: &Lvalue<'tcx>, span: Span) {
+        let conflict = self.find_borrow_in_effect(span, |borrow| {
+            borrow.kind != BorrowKind::Shared && lvalues_overlap(&borrow.lvalue, lvalue)
+        });
+        if let Some(borrow) = conflict {
+            let desc = self.describe_lvalue(&borrow.lvalue);
+            struct_span_err!(self.bcx, span, E0503,
+                             "cannot use `{}` because it was mutably borrowed (Mir)",
+                             self.describe_lvalue(lvalue))
+                .span_label(borrow.span, &format!("borrow of `{}` occurs here", desc))
+                .span_label(span, &format!("use of borrowed `{}`", desc))
+                .emit();
+        }
+    }
+
+    fn check_borrow(&self, lvalue: &Lvalue<'tcx>, kind: BorrowKind, span: Span) {
+        if !self.is_tracked(lvalue) {
+            return;
+        }
+        let conflict = self.find_borrow_in_effect(span, |borrow| {
+            (kind != BorrowKind::Shared || borrow.kind != BorrowKind::Shared) &&
+                lvalues_overlap(&borrow.lvalue, lvalue)
+        });
+        let borrow = match conflict {
+            Some(borrow) => borrow,
+            None => return,
+        };
+        let desc = self.describe_lvalue(lvalue);
+        if kind == BorrowKind::Mut && borrow.kind == BorrowKind::Mut {
+            struct_span_err!(self.bcx, span, E0499,
+                             "cannot borrow `{}` as mutable more than once at a time (Mir)",
+                             desc)
+                .span_label(borrow.span, &format!("first mutable borrow occurs here"))
+                .span_label(span, &format!("second mutable borrow occurs here"))
+                .emit();
+        } else {
+            let old_desc = self.describe_lvalue(&borrow.lvalue);
+            let old_pronoun = if old_desc == desc {
+                "it".to_string()
+            } else {
+                format!("`{}`", old_desc)
+            };
+            struct_span_err!(self.bcx, span, E0502,
+                             "cannot borrow `{}` as {} because {} is also borrowed as {} (Mir)",
+                             desc,
+                             borrow_kind_to_user_str(kind),
+                             old_pronoun,
+                             borrow_kind_to_user_str(borrow.kind))
+                .span_label(span,
+                            &format!("{} borrow occurs here", borrow_kind_to_user_str(kind)))
+                .span_label(borrow.span,
+                            &format!("{} borrow occurs here",
+                                     borrow_kind_to_user_str(borrow.kind)))
+                .emit();
+        }
+    }
+
+    fn is_drop_and_replace(&self, location: Location) -> bool {
+        let data = &self.mir[location.block];
+        if location.statement_index < data.statements.len() {
+            return false;
+        }
+        match data.terminator().kind {
+            repr::TerminatorKind::DropAndReplace { .. } => true,
+            _ => false,
+        }
+    }
+
+    fn describe_lvalue(&self, lvalue: &Lvalue<'tcx>) -> String {
+        let mut buf = String::new();
+        self.append_lvalue_to_string(lvalue, &mut buf, false);
+        buf
+    }
+
+    /// Appends the user-facing description of `lvalue`, in the style of
+    /// `BorrowckCtxt::loan_path_to_string`. Dereferences are left out of
+    /// the bases of field and element accesses when `autoderef` is set.
+    fn append_lvalue_to_string(&self, lvalue: &Lvalue<'tcx>, buf: &mut String, autoderef: bool) {
+        match *lvalue {
+            Lvalue::Var(var) => {
+                buf.push_str(&self.mir.var_decls[var].name.as_str());
+            }
+            Lvalue::Arg(arg) => {
+                let name = self.mir.arg_decls[arg].debug_name;
+                if name != keywords::Invalid.name() {
+                    buf.push_str(&name.as_str());
+                } else {
+                    buf.push_str(&format!("{:?}", lvalue));
+                }
+            }
+            Lvalue::Temp(_) | Lvalue::ReturnPointer => {
+                buf.push_str(&format!("{:?}", lvalue));
+            }
+            Lvalue::Static(def_id) => {
+                buf.push_str(&self.bcx.tcx.item_name(def_id).as_str());
+            }
+            Lvalue::Projection(ref proj) => {
+                match proj.elem {
+                    ProjectionElem::Deref => {
+                        if let Some(name) = self.upvar_name(&proj.base, true) {
+                            buf.push_str(&name);
+                        } else {
+                            if !autoderef {
+                                buf.push('*');
+                            }
+                            self.append_lvalue_to_string(&proj.base, buf, autoderef);
+                        }
+                    }
+                    ProjectionElem::Downcast(..) => {
+                        self.append_lvalue_to_string(&proj.base, buf, autoderef);
+                    }
+                    ProjectionElem::Field(field, _) => {
+                        if let Some(name) = self.upvar_name(lvalue, false) {
+                            buf.push_str(&name);
+                        } else {
+                            self.append_lvalue_to_string(&proj.base, buf, true);
+                            buf.push('.');
+                            buf.push_str(&self.field_name(&proj.base, field.index()));
+                        }
+                    }
+                    ProjectionElem::Index(_) |
+                    ProjectionElem::ConstantIndex { .. } |
+                    ProjectionElem::Subslice { .. } => {
+                        self.append_lvalue_to_string(&proj.base, buf, true);
+                        buf.push_str("[..]");
+                    }
+                }
+            }
+        }
+    }
+
+    /// Returns the name of the variable captured by a closure, if `lvalue`
+    /// is the field of the closure environment holding it, and the capture
+    /// is by reference exactly when `by_ref` is set.
+    fn upvar_name(&self, lvalue: &Lvalue<'tcx>, by_ref: bool) -> Option<String> {
+        if let Lvalue::Projection(ref proj) = *lvalue {
+            if let ProjectionElem::Field(field, _) = proj.elem {
+                let base_ty = proj.base.ty(self.mir, self.bcx.tcx).to_ty(self.bcx.tcx);
+                if let ty::TyClosure(..) = base_ty.sty {
+                    if let Some(upvar) = self.mir.upvar_decls.get(field.index()) {
+                        if upvar.by_ref == by_ref {
+                            return Some(upvar.debug_name.to_string());
+                        }
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    fn field_name(&self, base: &Lvalue<'tcx>, field: usize) -> String {
+        let variant = match base.ty(self.mir, self.bcx.tcx) {
+            LvalueTy::Ty { ty } => match ty.sty {
+                ty::TyStruct(def, _) => Some(def.struct_variant()),
+                _ => None,
+            },
+            LvalueTy::Downcast { adt_def, variant_index, .. } => {
+                Some(&adt_def.variants[variant_index])
+            }
+        };
+        match variant {
+            Some(variant) => variant.fields[field].name.to_string(),
+            None => field.to_string(),
+        }
+    }
+}
+
+impl<'b, 'a: 'b, 'tcx: 'a> Visitor<'tcx> for MirBorrowckCtxt<'b, 'a, 'tcx> {
+    fn visit_lvalue(&mut self,
+                    lvalue: &Lvalue<'tcx>,
+                    context: LvalueContext,
+                    location: Location) {
+        let span = self.span_of(location);
+        match context {
+            LvalueContext::Store => self.check_assignment(lvalue, span, false),
+            LvalueContext::Call => self.check_assignment(lvalue, span, true),
+            LvalueContext::Drop => {
+                // Plain drops run at the end of the scopes, once the loans
+                // of the values dropped are over; only assignments of
+                // values with destructors need checking.
+                if self.is_drop_and_replace(location) {
+                    self.check_assignment(lvalue, span, false);
+                }
+            }
+            LvalueContext::Borrow { kind, .. } => self.check_borrow(lvalue, kind, span),
+            LvalueContext::Consume => {
+                let tcx = self.bcx.tcx;
+                let ty = lvalue.ty(self.mir, tcx).to_ty(tcx);
+                if ty.moves_by_default(tcx, &self.mdpe.param_env, span) {
+                    self.check_move(lvalue, span);
+                } else {
+                    self.check_use(lvalue, span);
+                }
+            }
+            LvalueContext::Inspect |
+            LvalueContext::Slice { .. } => self.check_use(lvalue, span),
+            LvalueContext::Projection |
+            LvalueContext::StorageLive |
+            LvalueContext::StorageDead => {}
+        }
+        self.super_lvalue(lvalue, context, location);
+    }
+}
+
+/// Whether `a` and `b` may refer to overlapping memory, that is, whether
+/// one of them is a prefix of the other. Elements of the same array are
+/// assumed to overlap, whatever their indices.
+fn lvalues_overlap<'tcx>(a: &Lvalue<'tcx>, b: &Lvalue<'tcx>) -> bool {
+    let (a_root, a_elems) = decompose(a);
+    let (b_root, b_elems) = decompose(b);
+    if a_root != b_root {
+        return false;
+    }
+    a_elems.iter().zip(&b_elems).all(|(a_elem, b_elem)| {
+        match (*a_elem, *b_elem) {
+            (&ProjectionElem::Field(a_field, _), &ProjectionElem::Field(b_field, _)) => {
+                a_field == b_field
+            }
+            (&ProjectionElem::Downcast(_, a_variant), &ProjectionElem::Downcast(_, b_variant)) => {
+                a_variant == b_variant
+            }
+            _ => true,
+        }
+    })
+}
+
+/// Splits `lvalue` into its root and the projections applied to it, from
+/// the innermost one outward.
+fn decompose<'l, 'tcx>(mut lvalue: &'l Lvalue<'tcx>)
+                       -> (&'l Lvalue<'tcx>, Vec<&'l repr::LvalueElem<'tcx>>) {
+    let mut elems = vec![];
+    while let Lvalue::Projection(ref proj) = *lvalue {
+        elems.push(&proj.elem);
+        lvalue = &proj.base;
+    }
+    elems.reverse();
+    (lvalue, elems)
+}
+
+fn borrow_kind_to_user_str(kind: BorrowKind) -> &'static str {
+    match kind {
+        BorrowKind::Shared => "immutable",
+        BorrowKind::Unique => "uniquely immutable",
+        BorrowKind::Mut => "mutable",
+    }
+}
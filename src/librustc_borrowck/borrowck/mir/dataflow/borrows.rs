@@ -0,0 +1,223 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use rustc::ty::{self, TyCtxt};
+use rustc::mir::repr::{self, Mir, Location};
+use rustc::util::nodemap::FnvHashMap;
+use rustc_data_structures::indexed_vec::{Idx, IndexVec};
+use syntax_pos::Span;
+
+use super::super::MoveDataParamEnv;
+
+use super::{BitDenotation, BlockSets, DataflowOperator};
+
+use bitslice::{BitwiseOperator};
+use indexed_set::{IdxSet};
+
+use std::cmp;
+use std::fmt;
+
+/// Index into `Borrows::borrows`.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Hash)]
+pub struct BorrowIndex(usize);
+
+impl Idx for BorrowIndex {
+    fn new(idx: usize) -> Self { BorrowIndex(idx) }
+    fn index(self) -> usize { self.0 }
+}
+
+/// A `&` or `&mut` rvalue of the MIR, and the span of code it stays
+/// valid for.
+pub struct BorrowData<'tcx> {
+    pub location: Location,
+    pub kind: repr::BorrowKind,
+    pub lvalue: repr::Lvalue<'tcx>,
+    /// The lvalue the reference is assigned to.
+    pub assigned_lvalue: repr::Lvalue<'tcx>,
+    /// The span of the `Ref` rvalue creating the borrow.
+    pub span: Span,
+    /// The span of code from the borrow to the end of the scope its region
+    /// ends with, if that is a scope of the function body. Other regions,
+    /// like those of the function's signature, outlive the whole body.
+    pub region_span: Option<Span>,
+}
+
+impl<'tcx> fmt::Debug for BorrowData<'tcx> {
+    fn fmt(&self, w: &mut fmt::Formatter) -> fmt::Result {
+        let kind = match self.kind {
+            repr::BorrowKind::Shared => "",
+            repr::BorrowKind::Unique => "uniq ",
+            repr::BorrowKind::Mut => "mut ",
+        };
+        write!(w, "&{}{:?}@{:?}", kind, self.lvalue, self.location)
+    }
+}
+
+impl<'tcx> BorrowData<'tcx> {
+    /// Whether the borrow may still be used by code at `span`.
+    pub fn is_in_scope_at(&self, span: Span) -> bool {
+        match self.region_span {
+            Some(region_span) => region_span.lo <= span.lo && span.hi <= region_span.hi,
+            None => true,
+        }
+    }
+}
+
+/// `Borrows` tracks the borrows which may be in effect upon reaching a
+/// particular point in the control flow of a function.
+///
+/// A borrow comes into effect at the statement creating it, and goes out
+/// of effect at the first statement outside of the scope its region ends
+/// with. Code desugared from `for` loops shares a single span, which the
+/// scopes inside of it cannot be told apart with; a borrow whose region
+/// does not extend past the span of the temporary the reference is assigned
+/// to also goes out of effect when the storage of that temporary goes dead.
+/// Reborrows of other borrows are tracked as borrows in their own right.
+pub struct Borrows<'a, 'tcx: 'a> {
+    mir: &'a Mir<'tcx>,
+    borrows: IndexVec<BorrowIndex, BorrowData<'tcx>>,
+    location_map: FnvHashMap<Location, BorrowIndex>,
+}
+
+impl<'a, 'tcx: 'a> Borrows<'a, 'tcx> {
+    pub fn new(tcx: TyCtxt<'a, 'tcx, 'tcx>, mir: &'a Mir<'tcx>) -> Self {
+        let mut borrows = IndexVec::new();
+        let mut location_map = FnvHashMap();
+        for (bb, data) in mir.basic_blocks().iter_enumerated() {
+            for (i, stmt) in data.statements.iter().enumerate() {
+                let (assigned_lvalue, region, kind, lvalue) = match stmt.kind {
+                    repr::StatementKind::Assign(ref assigned_lvalue,
+                                                repr::Rvalue::Ref(region, kind, ref lvalue)) => {
+                        (assigned_lvalue, region, kind, lvalue)
+                    }
+                    _ => continue,
+                };
+                let span = stmt.source_info.span;
+                // The scope of a `let` binding only starts after the `let`
+                // statement, so extend the region back to the borrow itself.
+                let region_span = match *region {
+                    ty::ReScope(extent) => extent.span(&tcx.region_maps, &tcx.map),
+                    _ => None,
+                }.map(|region_span| {
+                    Span { lo: cmp::min(span.lo, region_span.lo), ..region_span }
+                });
+                let location = Location { block: bb, statement_index: i };
+                let index = borrows.push(BorrowData {
+                    location: location,
+                    kind: kind,
+                    lvalue: lvalue.clone(),
+                    assigned_lvalue: assigned_lvalue.clone(),
+                    span: span,
+                    region_span: region_span,
+                });
+                location_map.insert(location, index);
+            }
+        }
+        Borrows { mir: mir, borrows: borrows, location_map: location_map }
+    }
+
+    pub fn borrows(&self) -> &IndexVec<BorrowIndex, BorrowData<'tcx>> { &self.borrows }
+
+    fn entry_span(&self, bb: repr::BasicBlock) -> Span {
+        let data = &self.mir[bb];
+        match data.statements.first() {
+            Some(stmt) => stmt.source_info.span,
+            None => data.terminator().source_info.span,
+        }
+    }
+
+    fn kill_out_of_scope(&self, sets: &mut BlockSets<BorrowIndex>, span: Span) {
+        for (index, borrow) in self.borrows.iter_enumerated() {
+            if !borrow.is_in_scope_at(span) {
+                sets.kill(&index);
+            }
+        }
+    }
+}
+
+impl<'a, 'tcx> BitDenotation for Borrows<'a, 'tcx> {
+    type Idx = BorrowIndex;
+    type Ctxt = MoveDataParamEnv<'tcx>;
+    fn name() -> &'static str { "borrows" }
+    fn bits_per_block(&self, _ctxt: &Self::Ctxt) -> usize {
+        self.borrows.len()
+    }
+
+    fn start_block_effect(&self, _ctxt: &Self::Ctxt, _sets: &mut BlockSets<BorrowIndex>) {
+        // no borrows have been created prior to function execution,
+        // so this method has no effect on `_sets`.
+    }
+
+    fn statement_effect(&self,
+                        _ctxt: &Self::Ctxt,
+                        sets: &mut BlockSets<BorrowIndex>,
+                        bb: repr::BasicBlock,
+                        idx: usize) {
+        let stmt = &self.mir[bb].statements[idx];
+        self.kill_out_of_scope(sets, stmt.source_info.span);
+
+        if let repr::StatementKind::StorageDead(ref lvalue) = stmt.kind {
+            if let repr::Lvalue::Temp(_) = *lvalue {
+                let span = stmt.source_info.span;
+                for (index, borrow) in self.borrows.iter_enumerated() {
+                    let region_within_temp = borrow.region_span.map_or(false, |region_span| {
+                        span.lo <= region_span.lo && region_span.hi <= span.hi
+                    });
+                    if borrow.assigned_lvalue == *lvalue && region_within_temp {
+                        sets.kill(&index);
+                    }
+                }
+            }
+        }
+
+        let loc = Location { block: bb, statement_index: idx };
+        if let Some(index) = self.location_map.get(&loc) {
+            sets.gen(index);
+        }
+    }
+
+    fn terminator_effect(&self,
+                         _ctxt: &Self::Ctxt,
+                         sets: &mut BlockSets<BorrowIndex>,
+                         bb: repr::BasicBlock,
+                         _statements_len: usize) {
+        let term = self.mir[bb].terminator();
+        self.kill_out_of_scope(sets, term.source_info.span);
+        // A jump back to the start of a loop leaves the scopes entered in
+        // the loop, even if the jump itself, like a `continue`, is inside
+        // of them.
+        if let repr::TerminatorKind::Goto { target } = term.kind {
+            self.kill_out_of_scope(sets, self.entry_span(target));
+        }
+    }
+
+    fn propagate_call_return(&self,
+                             _ctxt: &Self::Ctxt,
+                             _in_out: &mut IdxSet<BorrowIndex>,
+                             _call_bb: repr::BasicBlock,
+                             _dest_bb: repr::BasicBlock,
+                             _dest_lval: &repr::Lvalue) {
+        // returning from a call neither creates nor ends borrows.
+    }
+}
+
+impl<'a, 'tcx> BitwiseOperator for Borrows<'a, 'tcx> {
+    #[inline]
+    fn join(&self, pred1: usize, pred2: usize) -> usize {
+        pred1 | pred2 // borrows from either pred may be in effect
+    }
+}
+
+impl<'a, 'tcx> DataflowOperator for Borrows<'a, 'tcx> {
+    #[inline]
+    fn bottom_value() -> bool {
+        false // bottom = no borrows in effect by default
+    }
+}
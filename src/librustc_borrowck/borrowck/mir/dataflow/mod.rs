@@ -28,10 +28,12 @@ use indexed_set::{IdxSet, IdxSetBuf};
 pub use self::sanity_check::sanity_check_via_rustc_peek;
 pub use self::impls::{MaybeInitializedLvals, MaybeUninitializedLvals};
 pub use self::impls::{DefinitelyInitializedLvals, MovingOutStatements};
+pub use self::borrows::{Borrows, BorrowData, BorrowIndex};
 
 mod graphviz;
 mod sanity_check;
 mod impls;
+mod borrows;
 
 pub trait Dataflow<BD: BitDenotation> {
     fn dataflow<P>(&mut self, p: P) where P: Fn(&BD::Ctxt, BD::Idx) -> &Debug;
@@ -58,6 +60,14 @@ struct PropagationContext<'b, 'a: 'b, 'tcx: 'a, O>
 impl<'a, 'tcx: 'a, BD> DataflowAnalysis<'a, 'tcx, BD>
     where BD: BitDenotation + DataflowOperator
 {
+    /// Computes the dataflow to its fixed point, without any of the
+    /// graphviz instrumentation of `Dataflow::dataflow`.
+    pub fn run(mut self) -> DataflowResults<BD> {
+        self.build_sets();
+        self.propagate();
+        self.results()
+    }
+
     fn propagate(&mut self) {
         let mut temp = IdxSetBuf::new_empty(self.flow_state.sets.bits_per_block);
         let mut propcx = PropagationContext {
@@ -205,6 +215,34 @@ impl<O: BitDenotation> DataflowResults<O> {
     pub fn sets(&self) -> &AllSets<O::Idx> {
         &self.0.sets
     }
+
+    pub fn operator(&self) -> &O {
+        &self.0.operator
+    }
+
+    /// Applies the effect of the statement `idx_stmt` of `bb` to `in_out`,
+    /// so the state of the analysis can be followed through a block one
+    /// statement at a time, starting from its entry set.
+    pub fn apply_statement_effect(&self,
+                                  ctxt: &O::Ctxt,
+                                  in_out: &mut IdxSet<O::Idx>,
+                                  bb: repr::BasicBlock,
+                                  idx_stmt: usize) {
+        let bits_per_block = self.0.sets.bits_per_block;
+        let mut on_entry = IdxSetBuf::new_empty(bits_per_block);
+        let mut gen_set = IdxSetBuf::new_empty(bits_per_block);
+        let mut kill_set = IdxSetBuf::new_empty(bits_per_block);
+        {
+            let mut sets = BlockSets {
+                on_entry: &mut on_entry,
+                gen_set: &mut gen_set,
+                kill_set: &mut kill_set,
+            };
+            self.0.operator.statement_effect(ctxt, &mut sets, bb, idx_stmt);
+        }
+        in_out.union(&gen_set);
+        in_out.subtract(&kill_set);
+    }
 }
 
 // FIXME: This type shouldn't be public, but the graphviz::MirWithFlowState trait
@@ -8,7 +8,7 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
-use indexed_set::IdxSetBuf;
+use indexed_set::{IdxSet, IdxSetBuf};
 use super::gather_moves::{MoveData, MovePathIndex, MovePathContent};
 use super::dataflow::{MaybeInitializedLvals, MaybeUninitializedLvals};
 use super::dataflow::{DataflowResults};
@@ -23,6 +23,7 @@ use rustc::middle::const_val::ConstVal;
 use rustc::middle::lang_items;
 use rustc::util::nodemap::FnvHashMap;
 use rustc_data_structures::indexed_vec::Idx;
+use rustc_mir::pretty_html;
 use syntax_pos::Span;
 
 use std::fmt;
@@ -56,7 +57,7 @@ impl<'tcx> MirPass<'tcx> for ElaborateDrops {
                 super::do_dataflow(tcx, mir, id, &[], &env,
                                    MaybeUninitializedLvals::new(tcx, mir));
 
-            ElaborateDropsCtxt {
+            let ctxt = ElaborateDropsCtxt {
                 tcx: tcx,
                 mir: mir,
                 env: &env,
@@ -64,7 +65,9 @@ impl<'tcx> MirPass<'tcx> for ElaborateDrops {
                 flow_uninits: flow_uninits,
                 drop_flags: FnvHashMap(),
                 patch: MirPatch::new(mir),
-            }.elaborate()
+            };
+            ctxt.dump_dataflow(src);
+            ctxt.elaborate()
         };
         elaborate_patch.apply(mir);
     }
@@ -140,6 +143,34 @@ impl<'b, 'tcx> ElaborateDropsCtxt<'b, 'tcx> {
         &self.env.param_env
     }
 
+    /// Adds the paths which may be initialized and may be uninitialized on
+    /// entry to each block to the `-Z dump-mir-format=html` report, as they
+    /// decide which drops get a drop flag.
+    fn dump_dataflow(&self, src: MirSource) {
+        if !pretty_html::dataflow_enabled(self.tcx, src) {
+            return;
+        }
+        let move_paths = &self.move_data().move_paths;
+        let describe = |set: &IdxSet<MovePathIndex>| {
+            let paths: Vec<_> = (0..move_paths.len()).map(MovePathIndex::new).filter(|path| {
+                set.contains(path)
+            }).map(|path| {
+                match move_paths[path].content {
+                    MovePathContent::Lvalue(ref lvalue) => format!("{:?}", lvalue),
+                    MovePathContent::Static => "<static>".to_string(),
+                }
+            }).collect();
+            format!("{{{}}}", paths.join(", "))
+        };
+        let blocks = 0..self.mir.basic_blocks().len();
+        pretty_html::dump_dataflow(self.tcx, src, "maybe initialized", blocks.clone().map(|bb| {
+            describe(self.flow_inits.sets().on_entry_set_for(bb))
+        }).collect());
+        pretty_html::dump_dataflow(self.tcx, src, "maybe uninitialized", blocks.map(|bb| {
+            describe(self.flow_uninits.sets().on_entry_set_for(bb))
+        }).collect());
+    }
+
     fn initialization_data_at(&self, loc: Location) -> InitializationData {
         let mut data = InitializationData {
             live: self.flow_inits.sets().on_entry_set_for(loc.block.index())
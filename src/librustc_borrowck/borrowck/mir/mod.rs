@@ -20,10 +20,12 @@ use rustc::hir::intravisit::{FnKind};
 
 use rustc::mir::repr;
 use rustc::mir::repr::{BasicBlock, BasicBlockData, Mir, Statement, Terminator, Location};
+use rustc::mir::visit::Visitor;
 use rustc::session::Session;
 use rustc::ty::{self, TyCtxt};
 
 mod abs_domain;
+mod check_loans;
 pub mod elaborate_drops;
 mod dataflow;
 mod gather_moves;
@@ -35,9 +37,12 @@ use self::dataflow::{DataflowOperator};
 use self::dataflow::{Dataflow, DataflowAnalysis, DataflowResults};
 use self::dataflow::{MaybeInitializedLvals, MaybeUninitializedLvals};
 use self::dataflow::{DefinitelyInitializedLvals};
+use self::dataflow::{Borrows, BorrowIndex};
 use self::gather_moves::{MoveData, MovePathIndex};
 use self::gather_moves::{MovePathContent, MovePathData};
 
+use indexed_set::IdxSetBuf;
+
 fn has_rustc_mir_with(attrs: &[ast::Attribute], name: &str) -> Option<P<MetaItem>> {
     for attr in attrs {
         if attr.check_name("rustc_mir") {
@@ -69,10 +74,10 @@ pub fn borrowck_mir<'a, 'tcx: 'a>(
     match fk {
         FnKind::ItemFn(name, _, _, _, _, _, _) |
         FnKind::Method(name, _, _, _) => {
-            debug!("borrowck_mir({}) UNIMPLEMENTED", name);
+            debug!("borrowck_mir({})", name);
         }
         FnKind::Closure(_) => {
-            debug!("borrowck_mir closure (body.id={}) UNIMPLEMENTED", body.id);
+            debug!("borrowck_mir closure (body.id={})", body.id);
         }
     }
 
@@ -102,13 +107,18 @@ pub fn borrowck_mir<'a, 'tcx: 'a>(
         bcx.tcx.sess.fatal("stop_after_dataflow ended compilation");
     }
 
+    let flow_borrows = DataflowAnalysis::new(tcx, mir, &mdpe, Borrows::new(tcx, mir)).run();
+    let borrows_in_effect = IdxSetBuf::new_empty(flow_borrows.sets().bits_per_block());
+
     let mut mbcx = MirBorrowckCtxt {
         bcx: bcx,
         mir: mir,
         node_id: id,
-        move_data: mdpe.move_data,
+        mdpe: mdpe,
         flow_inits: flow_inits,
         flow_uninits: flow_uninits,
+        flow_borrows: flow_borrows,
+        borrows_in_effect: borrows_in_effect,
     };
 
     for bb in mir.basic_blocks().indices() {
@@ -173,28 +183,49 @@ pub struct MirBorrowckCtxt<'b, 'a: 'b, 'tcx: 'a> {
     bcx: &'b mut BorrowckCtxt<'a, 'tcx>,
     mir: &'b Mir<'tcx>,
     node_id: ast::NodeId,
-    move_data: MoveData<'tcx>,
+    mdpe: MoveDataParamEnv<'tcx>,
     flow_inits: DataflowResults<MaybeInitializedLvals<'a, 'tcx>>,
-    flow_uninits: DataflowResults<MaybeUninitializedLvals<'a, 'tcx>>
+    flow_uninits: DataflowResults<MaybeUninitializedLvals<'a, 'tcx>>,
+    flow_borrows: DataflowResults<Borrows<'a, 'tcx>>,
+    /// The loans which may be in effect at the statement being checked.
+    borrows_in_effect: IdxSetBuf<BorrowIndex>,
 }
 
 impl<'b, 'a: 'b, 'tcx: 'a> MirBorrowckCtxt<'b, 'a, 'tcx> {
     fn process_basic_block(&mut self, bb: BasicBlock) {
-        let BasicBlockData { ref statements, ref terminator, is_cleanup: _ } =
+        let BasicBlockData { ref statements, ref terminator, is_cleanup } =
             self.mir[bb];
-        for stmt in statements {
-            self.process_statement(bb, stmt);
+        // Cleanup blocks only run the drops of unwinding, which do not
+        // conflict with any loan.
+        if is_cleanup {
+            return;
         }
 
-        self.process_terminator(bb, terminator);
+        (*self.borrows_in_effect)
+            .clone_from(self.flow_borrows.sets().on_entry_set_for(bb.index()));
+        for (i, stmt) in statements.iter().enumerate() {
+            self.process_statement(bb, i, stmt);
+            self.flow_borrows.apply_statement_effect(&self.mdpe,
+                                                     &mut self.borrows_in_effect,
+                                                     bb, i);
+        }
+
+        self.process_terminator(bb, statements.len(), terminator);
     }
 
-    fn process_statement(&mut self, bb: BasicBlock, stmt: &Statement<'tcx>) {
+    fn process_statement(&mut self, bb: BasicBlock, idx: usize, stmt: &Statement<'tcx>) {
         debug!("MirBorrowckCtxt::process_statement({:?}, {:?}", bb, stmt);
+        self.visit_statement(bb, stmt, Location { block: bb, statement_index: idx });
     }
 
-    fn process_terminator(&mut self, bb: BasicBlock, term: &Option<Terminator<'tcx>>) {
+    fn process_terminator(&mut self,
+                          bb: BasicBlock,
+                          idx: usize,
+                          term: &Option<Terminator<'tcx>>) {
         debug!("MirBorrowckCtxt::process_terminator({:?}, {:?})", bb, term);
+        if let Some(ref term) = *term {
+            self.visit_terminator(bb, term, Location { block: bb, statement_index: idx });
+        }
     }
 }
 
@@ -37,6 +37,7 @@ use rustc::middle::mem_categorization as mc;
 use rustc::middle::mem_categorization::Categorization;
 use rustc::middle::region;
 use rustc::ty::{self, TyCtxt};
+use rustc::util::profiling::{self, Category};
 
 use std::fmt;
 use std::mem;
@@ -45,7 +46,7 @@ use std::hash::{Hash, Hasher};
 use syntax::ast;
 use syntax::attr::AttrMetaMethods;
 use syntax_pos::{MultiSpan, Span};
-use errors::DiagnosticBuilder;
+use errors::{Applicability, DiagnosticBuilder};
 
 use rustc::hir;
 use rustc::hir::{FnDecl, Block};
@@ -70,18 +71,23 @@ pub type LoanDataFlow<'a, 'tcx> = DataFlowContext<'a, 'tcx, LoanDataFlowOperator
 impl<'a, 'tcx, 'v> Visitor<'v> for BorrowckCtxt<'a, 'tcx> {
     fn visit_fn(&mut self, fk: FnKind<'v>, fd: &'v FnDecl,
                 b: &'v Block, s: Span, id: ast::NodeId) {
-        match fk {
-            FnKind::ItemFn(..) |
-            FnKind::Method(..) => {
-                self.with_temp_region_map(id, |this| {
-                    borrowck_fn(this, fk, fd, b, s, id, fk.attrs())
-                });
-            }
+        let tcx = self.tcx;
+        profiling::record_with(Category::Item,
+                               || format!("borrowck {}", tcx.node_path_str(id)),
+                               || {
+            match fk {
+                FnKind::ItemFn(..) |
+                FnKind::Method(..) => {
+                    self.with_temp_region_map(id, |this| {
+                        borrowck_fn(this, fk, fd, b, s, id, fk.attrs())
+                    });
+                }
 
-            FnKind::Closure(..) => {
-                borrowck_fn(self, fk, fd, b, s, id, fk.attrs());
+                FnKind::Closure(..) => {
+                    borrowck_fn(self, fk, fd, b, s, id, fk.attrs());
+                }
             }
-        }
+        })
     }
 
     fn visit_item(&mut self, item: &hir::Item) {
@@ -171,7 +177,8 @@ fn borrowck_fn(this: &mut BorrowckCtxt,
 
     let def_id = this.tcx.map.local_def_id(id);
 
-    if attributes.iter().any(|item| item.check_name("rustc_mir_borrowck")) {
+    if this.tcx.sess.opts.debugging_opts.borrowck_mir ||
+       attributes.iter().any(|item| item.check_name("rustc_mir_borrowck")) {
         let mir = this.mir_map.unwrap().map.get(&def_id).unwrap();
         this.with_temp_region_map(id, |this| {
             mir::borrowck_mir(this, fk, decl, mir, body, sp, id, attributes)
@@ -944,10 +951,10 @@ impl<'a, 'tcx> BorrowckCtxt<'a, 'tcx> {
     {
         let cmt_path_or_string = self.cmt_to_path_or_string(&err.cmt);
 
-        let suggestion =
+        let (suggestion, applicability) =
             match self.tcx.sess.codemap().span_to_snippet(err.span) {
-                Ok(string) => format!("move {}", string),
-                Err(_) => format!("move |<args>| <body>")
+                Ok(string) => (format!("move {}", string), Applicability::MachineApplicable),
+                Err(_) => (format!("move |<args>| <body>"), Applicability::Unspecified)
             };
 
         struct_span_err!(self.tcx.sess, err.span, E0373,
@@ -961,12 +968,14 @@ impl<'a, 'tcx> BorrowckCtxt<'a, 'tcx> {
             .span_label(err.span,
                        &format!("may outlive borrowed value {}",
                                 cmt_path_or_string))
-            .span_suggestion(err.span,
-                             &format!("to force the closure to take ownership of {} \
-                                       (and any other referenced variables), \
-                                       use the `move` keyword, as shown:",
-                                       cmt_path_or_string),
-                             suggestion)
+            .span_suggestion_with_applicability(
+                err.span,
+                &format!("to force the closure to take ownership of {} \
+                          (and any other referenced variables), \
+                          use the `move` keyword, as shown:",
+                          cmt_path_or_string),
+                suggestion,
+                applicability)
             .emit();
     }
 
@@ -34,7 +34,7 @@ use graphviz::IntoCow;
 use syntax::ast;
 use rustc::hir::{Expr, PatKind};
 use rustc::hir;
-use rustc::hir::intravisit::FnKind;
+use rustc::hir::intravisit::{self, FnKind, Visitor};
 use syntax::ptr::P;
 use syntax::codemap;
 use syntax::attr::IntType;
@@ -47,6 +47,8 @@ use std::collections::hash_map::Entry::Vacant;
 use rustc_const_math::*;
 use rustc_errors::DiagnosticBuilder;
 
+use interpret;
+
 macro_rules! math {
     ($e:expr, $op:expr) => {
         match $op {
@@ -455,6 +457,14 @@ pub enum ErrKind {
     BadType(ConstVal),
     ErroneousReferencedConstant(Box<ConstEvalErr>),
     CharCast(ConstInt),
+
+    NonConstFnCall(String),
+    Panic,
+    Unreachable,
+    UndefinedValue,
+    InvalidMemoryAccess,
+    StepLimitReached(u64),
+    RecursionLimitReached,
 }
 
 impl From<ConstMathErr> for ErrKind {
@@ -537,6 +547,17 @@ impl ConstEvalErr {
             CharCast(ref got) => {
                 simple!("only `u8` can be cast as `char`, not `{}`", got.description())
             },
+
+            NonConstFnCall(ref path) => simple!("call to non-constant function `{}`", path),
+            Panic => simple!("the constant function panicked"),
+            Unreachable => simple!("entered unreachable code"),
+            UndefinedValue => simple!("use of an uninitialized value"),
+            InvalidMemoryAccess => simple!("invalid memory access in constant function"),
+            StepLimitReached(limit) => {
+                simple!("constant evaluation took more than {} steps", limit)
+            }
+            RecursionLimitReached => simple!("reached the recursion limit during \
+                                              constant evaluation"),
         }
     }
 }
@@ -844,11 +865,14 @@ pub fn eval_const_expr_partial<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
               Struct(_) => signal!(e, UnimplementedConstVal("tuple struct constructors")),
               callee => signal!(e, CallOn(callee)),
           };
-          let (decl, result) = if let Some(fn_like) = lookup_const_fn_by_id(tcx, did) {
-              (fn_like.decl(), &fn_like.body().expr)
-          } else {
-              signal!(e, NonConstPath)
+          let fn_like = match lookup_const_fn_by_id(tcx, did) {
+              Some(fn_like) => fn_like,
+              None => signal!(e, NonConstPath),
           };
+          if has_control_flow(fn_like.body()) {
+              return eval_const_fn_call_with_mir(tcx, e, callee, did, args, ty_hint, fn_args);
+          }
+          let (decl, result) = (fn_like.decl(), &fn_like.body().expr);
           let result = result.as_ref().expect("const fn has no result expression");
           assert_eq!(decl.inputs.len(), args.len());
 
@@ -988,6 +1012,67 @@ pub fn eval_const_expr_partial<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
     }
 }
 
+/// Returns true if a `const fn` body has statements, branches or loops,
+/// which are only supported by the MIR interpreter.
+fn has_control_flow(body: &hir::Block) -> bool {
+    struct ControlFlowVisitor {
+        found: bool,
+    }
+
+    impl<'v> Visitor<'v> for ControlFlowVisitor {
+        fn visit_block(&mut self, block: &'v hir::Block) {
+            if !block.stmts.is_empty() {
+                self.found = true;
+            }
+            intravisit::walk_block(self, block);
+        }
+
+        fn visit_expr(&mut self, expr: &'v Expr) {
+            match expr.node {
+                hir::ExprIf(..) | hir::ExprWhile(..) | hir::ExprLoop(..) |
+                hir::ExprMatch(..) | hir::ExprAssign(..) | hir::ExprAssignOp(..) |
+                hir::ExprBreak(..) | hir::ExprAgain(..) | hir::ExprRet(..) => {
+                    self.found = true;
+                }
+                _ => intravisit::walk_expr(self, expr),
+            }
+        }
+    }
+
+    let mut visitor = ControlFlowVisitor { found: false };
+    visitor.visit_block(body);
+    visitor.found
+}
+
+/// Evaluates the call `e` to the `const fn` `did` with the MIR interpreter.
+fn eval_const_fn_call_with_mir<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
+                                         e: &Expr,
+                                         callee: &Expr,
+                                         did: DefId,
+                                         args: &[P<Expr>],
+                                         ty_hint: EvalHint<'tcx>,
+                                         fn_args: FnArgMap) -> EvalResult {
+    let substs = match ty_hint {
+        ExprTypeChecked => tcx.node_id_item_substs(callee.id).substs,
+        _ if tcx.lookup_generics(did).types.is_empty() => {
+            Substs::for_item(tcx, did,
+                             |_, _| tcx.mk_region(ty::ReErased),
+                             |_, _| bug!("const fn {:?} has type parameters", did))
+        }
+        _ => signal!(e, UnimplementedConstVal("calls to generic `const fn`s with \
+                                               control flow outside of functions")),
+    };
+
+    let mut arg_vals = Vec::with_capacity(args.len());
+    for arg in args {
+        arg_vals.push(eval_const_expr_partial(tcx, arg, ty_hint.erase_hint(), fn_args)?);
+    }
+    match interpret::eval_const_fn_call(tcx, e.span, did, substs, arg_vals) {
+        Ok(val) => Ok(val),
+        Err(err) => signal!(e, ErroneousReferencedConstant(box err)),
+    }
+}
+
 fn infer<'a, 'tcx>(i: ConstInt,
                    tcx: TyCtxt<'a, 'tcx, 'tcx>,
                    ty_hint: &ty::TypeVariants<'tcx>)
@@ -1170,7 +1255,8 @@ fn cast_const_float<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
     }
 }
 
-fn cast_const<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>, val: ConstVal, ty: ty::Ty) -> CastResult {
+pub fn cast_const<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>, val: ConstVal, ty: ty::Ty)
+                            -> CastResult {
     match val {
         Integral(i) => cast_const_int(tcx, i, ty),
         Bool(b) => cast_const_int(tcx, Infer(b as u64), ty),
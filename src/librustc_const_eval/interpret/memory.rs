@@ -0,0 +1,138 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! The values manipulated by the MIR interpreter, and the memory they live in.
+//!
+//! Memory is a list of allocations, each holding a single `Value`. Every
+//! local of every call frame gets its own allocation, and so do the
+//! targets of byte string literals. A pointer names an allocation and the
+//! path of field (or element) indices leading to its pointee inside of the
+//! allocation's value, so references to fields and array elements of locals
+//! stay valid as the locals are mutated.
+
+use rustc::hir::def_id::DefId;
+use rustc::ty::subst::Substs;
+use rustc_const_math::{ConstInt, ConstFloat};
+use syntax::parse::token::InternedString;
+
+use eval::ErrKind;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct AllocId(usize);
+
+/// A pointer to a value inside of an allocation.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Pointer {
+    pub alloc: AllocId,
+    /// The indices of the fields, or array elements, to go through to reach
+    /// the pointee from the value of the allocation.
+    pub path: Vec<usize>,
+}
+
+impl Pointer {
+    pub fn offset(&self, index: usize) -> Pointer {
+        let mut path = self.path.clone();
+        path.push(index);
+        Pointer {
+            alloc: self.alloc,
+            path: path,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value<'tcx> {
+    /// The value of a local, or field, which was not written to yet.
+    Undef,
+    Bool(bool),
+    Char(char),
+    Int(ConstInt),
+    Float(ConstFloat),
+    /// A `&'static str` literal.
+    Str(InternedString),
+    /// A function item, with the type parameters it was referred to with.
+    Fn(DefId, &'tcx Substs<'tcx>),
+    /// A thin reference.
+    Ptr(Pointer),
+    /// A reference to `len` elements of an array, starting at `start`.
+    Slice(Pointer, u64, u64),
+    /// A struct, tuple, array or enum value. The index is that of the
+    /// variant for enums, and zero for everything else.
+    Aggregate(usize, Vec<Value<'tcx>>),
+}
+
+pub struct Memory<'tcx> {
+    allocs: Vec<Value<'tcx>>,
+}
+
+impl<'tcx> Memory<'tcx> {
+    pub fn new() -> Memory<'tcx> {
+        Memory { allocs: vec![] }
+    }
+
+    pub fn allocate(&mut self, value: Value<'tcx>) -> Pointer {
+        self.allocs.push(value);
+        Pointer {
+            alloc: AllocId(self.allocs.len() - 1),
+            path: vec![],
+        }
+    }
+
+    pub fn read(&self, ptr: &Pointer) -> Result<&Value<'tcx>, ErrKind> {
+        let mut value = match self.allocs.get(ptr.alloc.0) {
+            Some(value) => value,
+            None => return Err(ErrKind::InvalidMemoryAccess),
+        };
+        for &index in &ptr.path {
+            value = match *value {
+                Value::Aggregate(_, ref fields) => {
+                    match fields.get(index) {
+                        Some(field) => field,
+                        None => return Err(ErrKind::InvalidMemoryAccess),
+                    }
+                }
+                Value::Undef => return Err(ErrKind::UndefinedValue),
+                _ => return Err(ErrKind::InvalidMemoryAccess),
+            };
+        }
+        Ok(value)
+    }
+
+    /// Returns the pointee of `ptr` for writing to it. Fields of values not
+    /// yet written to are created as needed, so aggregates can be built one
+    /// field at a time.
+    pub fn get_mut(&mut self, ptr: &Pointer) -> Result<&mut Value<'tcx>, ErrKind> {
+        let mut value = match self.allocs.get_mut(ptr.alloc.0) {
+            Some(value) => value,
+            None => return Err(ErrKind::InvalidMemoryAccess),
+        };
+        for &index in &ptr.path {
+            let current = value;
+            if let Value::Undef = *current {
+                *current = Value::Aggregate(0, vec![]);
+            }
+            value = match *current {
+                Value::Aggregate(_, ref mut fields) => {
+                    if fields.len() <= index {
+                        fields.resize(index + 1, Value::Undef);
+                    }
+                    &mut fields[index]
+                }
+                _ => return Err(ErrKind::InvalidMemoryAccess),
+            };
+        }
+        Ok(value)
+    }
+
+    pub fn write(&mut self, ptr: &Pointer, value: Value<'tcx>) -> Result<(), ErrKind> {
+        *self.get_mut(ptr)? = value;
+        Ok(())
+    }
+}
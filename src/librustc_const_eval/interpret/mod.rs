@@ -0,0 +1,888 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! An interpreter for MIR, used to evaluate calls to `const fn`s whose
+//! bodies contain branches, loops or mutable locals, which neither the
+//! HIR-based evaluator in `eval` nor the constant translation of trans can
+//! handle.
+//!
+//! Each call gets its own frame, whose locals live in `Memory`, so they can
+//! be borrowed and mutated through references. Calls to other `const fn`s
+//! and uses of constants are evaluated recursively, using the MIR of the
+//! current crate when it has been built, the MIR of its `const fn`s built
+//! on demand during type checking, and the MIR encoded in the metadata of
+//! other crates.
+
+use rustc::hir::def_id::DefId;
+use rustc::middle::const_val::ConstVal;
+use rustc::mir::mir_map::MirMap;
+use rustc::mir::repr as mir;
+use rustc::ty::{self, Ty, TyCtxt};
+use rustc::ty::subst::{Subst, Substs};
+use rustc::util::nodemap::DefIdMap;
+use rustc_const_math::{ConstInt, ConstUsize, ConstMathErr};
+use rustc_data_structures::indexed_vec::Idx;
+use syntax_pos::Span;
+
+use std::cmp::Ordering;
+use std::ops::Deref;
+use std::rc::Rc;
+
+use eval::{ConstEvalErr, ErrKind, EvalResult, EvalHint};
+use eval::{cast_const, eval_const_expr_partial, lookup_const_by_id, lookup_const_fn_by_id};
+
+mod memory;
+
+pub use self::memory::{AllocId, Memory, Pointer, Value};
+
+/// The number of statements and terminators a single evaluation may go
+/// through, to stop evaluations which never finish.
+pub const STEP_LIMIT: u64 = 1_000_000;
+
+/// Evaluates a call to the `const fn` `def_id`, for the HIR-based evaluator.
+/// Only scalar arguments and results are supported, as aggregates are
+/// represented in `ConstVal` by the HIR nodes they were evaluated from.
+pub fn eval_const_fn_call<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
+                                    span: Span,
+                                    def_id: DefId,
+                                    substs: &'tcx Substs<'tcx>,
+                                    args: Vec<ConstVal>)
+                                    -> EvalResult {
+    let mut ecx = EvalContext::new(tcx, None, span);
+    let mut arg_values = Vec::with_capacity(args.len());
+    for arg in args {
+        arg_values.push(ecx.from_const_val(arg)?);
+    }
+    let value = ecx.call_const_fn(def_id, substs, arg_values)?;
+    ecx.wrap(ecx.to_const_val(&value))
+}
+
+/// Returns true if the MIR only has a single path from its start to its
+/// return, and only assigns to locals as a whole, which is all the constant
+/// translation of trans supports.
+pub fn is_straight_line(mir: &mir::Mir) -> bool {
+    let mut seen = vec![false; mir.basic_blocks().len()];
+    let mut bb = mir::START_BLOCK;
+    loop {
+        if seen[bb.index()] {
+            return false;
+        }
+        seen[bb.index()] = true;
+
+        let data = &mir[bb];
+        for statement in &data.statements {
+            if let mir::StatementKind::Assign(mir::Lvalue::Projection(_), _) = statement.kind {
+                return false;
+            }
+        }
+        bb = match data.terminator().kind {
+            mir::TerminatorKind::Goto { target } |
+            mir::TerminatorKind::Drop { target, .. } |
+            mir::TerminatorKind::Assert { target, .. } |
+            mir::TerminatorKind::Call { destination: Some((_, target)), .. } => target,
+            mir::TerminatorKind::Return => return true,
+            _ => return false,
+        };
+    }
+}
+
+#[derive(Clone)]
+enum CachedMir<'a, 'tcx: 'a> {
+    Ref(&'a mir::Mir<'tcx>),
+    /// The MIR of an item from another crate, or of a `const fn` built
+    /// during type checking, or of one of their promoted constants.
+    Owned(Rc<mir::Mir<'tcx>>, Option<mir::Promoted>),
+}
+
+impl<'a, 'tcx> CachedMir<'a, 'tcx> {
+    fn promoted(&self, index: mir::Promoted) -> CachedMir<'a, 'tcx> {
+        match *self {
+            CachedMir::Ref(mir) => CachedMir::Ref(&mir.promoted[index]),
+            CachedMir::Owned(ref mir, None) => CachedMir::Owned(mir.clone(), Some(index)),
+            CachedMir::Owned(_, Some(_)) => bug!("promoted constant within a promoted constant"),
+        }
+    }
+}
+
+impl<'a, 'tcx> Deref for CachedMir<'a, 'tcx> {
+    type Target = mir::Mir<'tcx>;
+
+    fn deref(&self) -> &mir::Mir<'tcx> {
+        match *self {
+            CachedMir::Ref(mir) => mir,
+            CachedMir::Owned(ref mir, None) => mir,
+            CachedMir::Owned(ref mir, Some(index)) => &mir.promoted[index],
+        }
+    }
+}
+
+struct Frame<'a, 'tcx: 'a> {
+    mir: CachedMir<'a, 'tcx>,
+    /// The type parameters of the function, or constant, being evaluated.
+    substs: &'tcx Substs<'tcx>,
+    /// Pointers to the allocations of the locals, in `Mir::local_index` order.
+    locals: Vec<Pointer>,
+}
+
+enum Place {
+    Ptr(Pointer),
+    /// `len` elements of the array pointed to, starting at the given index.
+    Slice(Pointer, u64, u64),
+}
+
+pub struct EvalContext<'a, 'tcx: 'a> {
+    tcx: TyCtxt<'a, 'tcx, 'tcx>,
+    /// The MIR of the current crate, if it has been built already.
+    mir_map: Option<&'a MirMap<'tcx>>,
+    extern_mir: DefIdMap<Rc<mir::Mir<'tcx>>>,
+    pub memory: Memory<'tcx>,
+    /// The span of the statement or terminator being evaluated.
+    span: Span,
+    steps: u64,
+    depth: usize,
+}
+
+impl<'a, 'tcx> EvalContext<'a, 'tcx> {
+    pub fn new(tcx: TyCtxt<'a, 'tcx, 'tcx>,
+               mir_map: Option<&'a MirMap<'tcx>>,
+               span: Span)
+               -> EvalContext<'a, 'tcx> {
+        EvalContext {
+            tcx: tcx,
+            mir_map: mir_map,
+            extern_mir: DefIdMap(),
+            memory: Memory::new(),
+            span: span,
+            steps: 0,
+            depth: 0,
+        }
+    }
+
+    /// Evaluates a call to the `const fn` `def_id`, with fully substituted
+    /// type parameters.
+    pub fn call_const_fn(&mut self,
+                         def_id: DefId,
+                         substs: &'tcx Substs<'tcx>,
+                         args: Vec<Value<'tcx>>)
+                         -> Result<Value<'tcx>, ConstEvalErr> {
+        if self.tcx.trait_of_item(def_id).is_some() ||
+           lookup_const_fn_by_id(self.tcx, def_id).is_none() {
+            return self.err(ErrKind::NonConstFnCall(self.tcx.item_path_str(def_id)));
+        }
+        match self.load_mir(def_id) {
+            Some(mir) => self.call(mir, substs, args),
+            None => {
+                self.err(ErrKind::UnimplementedConstVal("calls to `const fn`s with control \
+                                                         flow before their body is type-checked"))
+            }
+        }
+    }
+
+    fn load_mir(&mut self, def_id: DefId) -> Option<CachedMir<'a, 'tcx>> {
+        if def_id.is_local() {
+            if let Some(mir) = self.mir_map.and_then(|map| map.map.get(&def_id)) {
+                return Some(CachedMir::Ref(mir));
+            }
+
+            // During type checking, the MIR of the current crate hasn't been
+            // built yet, so build the MIR of its `const fn`s on demand.
+            let cached = self.tcx.local_const_fn_mir.borrow().get(&def_id).cloned();
+            if let Some(mir) = cached {
+                return Some(CachedMir::Owned(mir, None));
+            }
+            let builder = match self.tcx.const_fn_mir_builder.get() {
+                Some(builder) => builder,
+                None => return None,
+            };
+            return builder(self.tcx, def_id).map(|mir| {
+                let mir = Rc::new(mir);
+                self.tcx.local_const_fn_mir.borrow_mut().insert(def_id, mir.clone());
+                CachedMir::Owned(mir, None)
+            });
+        }
+        if let Some(mir) = self.extern_mir.get(&def_id) {
+            return Some(CachedMir::Owned(mir.clone(), None));
+        }
+        self.tcx.sess.cstore.maybe_get_item_mir(self.tcx, def_id).map(|mir| {
+            let mir = Rc::new(mir);
+            self.extern_mir.insert(def_id, mir.clone());
+            CachedMir::Owned(mir, None)
+        })
+    }
+
+    fn err<T>(&self, kind: ErrKind) -> Result<T, ConstEvalErr> {
+        Err(ConstEvalErr { span: self.span, kind: kind })
+    }
+
+    fn wrap<T>(&self, result: Result<T, ErrKind>) -> Result<T, ConstEvalErr> {
+        result.map_err(|kind| ConstEvalErr { span: self.span, kind: kind })
+    }
+
+    fn call(&mut self,
+            mir: CachedMir<'a, 'tcx>,
+            substs: &'tcx Substs<'tcx>,
+            args: Vec<Value<'tcx>>)
+            -> Result<Value<'tcx>, ConstEvalErr> {
+        if self.depth >= self.tcx.sess.recursion_limit.get() {
+            return self.err(ErrKind::RecursionLimitReached);
+        }
+        let span = self.span;
+        self.depth += 1;
+        let result = self.run(mir, substs, args);
+        self.depth -= 1;
+        self.span = span;
+        result
+    }
+
+    fn run(&mut self,
+           mir: CachedMir<'a, 'tcx>,
+           substs: &'tcx Substs<'tcx>,
+           args: Vec<Value<'tcx>>)
+           -> Result<Value<'tcx>, ConstEvalErr> {
+        let locals = (0..mir.count_locals()).map(|_| {
+            self.memory.allocate(Value::Undef)
+        }).collect();
+        let frame = Frame {
+            mir: mir,
+            substs: substs,
+            locals: locals,
+        };
+        for (i, arg) in args.into_iter().enumerate() {
+            let ptr = frame.local(&mir::Lvalue::Arg(mir::Arg::new(i)));
+            let result = self.memory.write(&ptr, arg);
+            self.wrap(result)?;
+        }
+
+        let mut bb = mir::START_BLOCK;
+        loop {
+            let data = &frame.mir[bb];
+            for statement in &data.statements {
+                self.step(statement.source_info.span)?;
+                self.statement(&frame, statement)?;
+            }
+
+            let terminator = data.terminator();
+            self.step(terminator.source_info.span)?;
+            match self.terminator(&frame, terminator)? {
+                Some(target) => bb = target,
+                None => return self.read(&frame.local(&mir::Lvalue::ReturnPointer)),
+            }
+        }
+    }
+
+    fn step(&mut self, span: Span) -> Result<(), ConstEvalErr> {
+        self.span = span;
+        self.steps += 1;
+        if self.steps > STEP_LIMIT {
+            return self.err(ErrKind::StepLimitReached(STEP_LIMIT));
+        }
+        Ok(())
+    }
+
+    fn monomorphize(&self, frame: &Frame<'a, 'tcx>, ty: Ty<'tcx>) -> Ty<'tcx> {
+        self.tcx.normalize_associated_type(&ty.subst(self.tcx, frame.substs))
+    }
+
+    fn read(&self, ptr: &Pointer) -> Result<Value<'tcx>, ConstEvalErr> {
+        self.wrap(self.memory.read(ptr).map(|value| value.clone()))
+    }
+
+    fn statement(&mut self,
+                 frame: &Frame<'a, 'tcx>,
+                 statement: &mir::Statement<'tcx>)
+                 -> Result<(), ConstEvalErr> {
+        match statement.kind {
+            mir::StatementKind::Assign(ref lvalue, ref rvalue) => {
+                let value = self.rvalue(frame, rvalue)?;
+                let place = self.lvalue(frame, lvalue)?;
+                self.write_place(place, value)
+            }
+            mir::StatementKind::SetDiscriminant { ref lvalue, variant_index } => {
+                let ptr = match self.lvalue(frame, lvalue)? {
+                    Place::Ptr(ptr) => ptr,
+                    Place::Slice(..) => return self.err(ErrKind::InvalidMemoryAccess),
+                };
+                let span = self.span;
+                let value = match self.memory.get_mut(&ptr) {
+                    Ok(value) => value,
+                    Err(kind) => return Err(ConstEvalErr { span: span, kind: kind }),
+                };
+                let is_undef = if let Value::Undef = *value { true } else { false };
+                if is_undef {
+                    *value = Value::Aggregate(variant_index, vec![]);
+                } else if let Value::Aggregate(ref mut variant, _) = *value {
+                    *variant = variant_index;
+                } else {
+                    return Err(ConstEvalErr { span: span, kind: ErrKind::InvalidMemoryAccess });
+                }
+                Ok(())
+            }
+            mir::StatementKind::StorageLive(_) |
+            mir::StatementKind::StorageDead(_) => Ok(()),
+        }
+    }
+
+    fn write_place(&mut self, place: Place, value: Value<'tcx>) -> Result<(), ConstEvalErr> {
+        match place {
+            Place::Ptr(ptr) => {
+                let result = self.memory.write(&ptr, value);
+                self.wrap(result)
+            }
+            Place::Slice(..) => self.err(ErrKind::UnimplementedConstVal("unsized assignments")),
+        }
+    }
+
+    fn lvalue(&mut self,
+              frame: &Frame<'a, 'tcx>,
+              lvalue: &mir::Lvalue<'tcx>)
+              -> Result<Place, ConstEvalErr> {
+        let proj = match *lvalue {
+            mir::Lvalue::Static(_) => {
+                return self.err(ErrKind::UnimplementedConstVal("references to statics"));
+            }
+            mir::Lvalue::Projection(ref proj) => proj,
+            _ => return Ok(Place::Ptr(frame.local(lvalue))),
+        };
+
+        let base = self.lvalue(frame, &proj.base)?;
+        Ok(match proj.elem {
+            mir::ProjectionElem::Deref => {
+                let ptr = match base {
+                    Place::Ptr(ptr) => ptr,
+                    Place::Slice(..) => return self.err(ErrKind::InvalidMemoryAccess),
+                };
+                match self.read(&ptr)? {
+                    Value::Ptr(ptr) => Place::Ptr(ptr),
+                    Value::Slice(ptr, start, len) => Place::Slice(ptr, start, len),
+                    Value::Undef => return self.err(ErrKind::UndefinedValue),
+                    _ => {
+                        return self.err(ErrKind::UnimplementedConstVal("dereferencing \
+                                                                        this value"));
+                    }
+                }
+            }
+            mir::ProjectionElem::Field(field, _) => {
+                match base {
+                    Place::Ptr(ptr) => Place::Ptr(ptr.offset(field.index())),
+                    Place::Slice(..) => return self.err(ErrKind::InvalidMemoryAccess),
+                }
+            }
+            mir::ProjectionElem::Downcast(..) => base,
+            mir::ProjectionElem::Index(ref index) => {
+                let index = match self.operand(frame, index)? {
+                    Value::Int(i) => match i.to_u64() {
+                        Some(i) => i,
+                        None => return self.err(ErrKind::IndexNegative),
+                    },
+                    _ => return self.err(ErrKind::IndexNotInt),
+                };
+                self.index(base, index)?
+            }
+            mir::ProjectionElem::ConstantIndex { offset, from_end, .. } => {
+                let len = self.place_len(frame, &proj.base, &base)?;
+                let index = if from_end {
+                    len - offset as u64
+                } else {
+                    offset as u64
+                };
+                self.index(base, index)?
+            }
+            mir::ProjectionElem::Subslice { from, to } => {
+                let len = self.place_len(frame, &proj.base, &base)?;
+                let (ptr, start) = match base {
+                    Place::Ptr(ptr) => (ptr, 0),
+                    Place::Slice(ptr, start, _) => (ptr, start),
+                };
+                Place::Slice(ptr, start + from as u64, len - from as u64 - to as u64)
+            }
+        })
+    }
+
+    fn index(&self, place: Place, index: u64) -> Result<Place, ConstEvalErr> {
+        match place {
+            Place::Ptr(ptr) => Ok(Place::Ptr(ptr.offset(index as usize))),
+            Place::Slice(_, _, len) if index >= len => {
+                self.err(ErrKind::IndexOutOfBounds { len: len, index: index })
+            }
+            Place::Slice(ptr, start, _) => Ok(Place::Ptr(ptr.offset((start + index) as usize))),
+        }
+    }
+
+    /// The number of elements of the array or slice `lvalue`, which was
+    /// evaluated to `place`.
+    fn place_len(&self,
+                 frame: &Frame<'a, 'tcx>,
+                 lvalue: &mir::Lvalue<'tcx>,
+                 place: &Place)
+                 -> Result<u64, ConstEvalErr> {
+        if let Place::Slice(_, _, len) = *place {
+            return Ok(len);
+        }
+        let ty = lvalue.ty(&frame.mir, self.tcx).to_ty(self.tcx);
+        match self.monomorphize(frame, ty).sty {
+            ty::TyArray(_, len) => Ok(len as u64),
+            _ => self.err(ErrKind::InvalidMemoryAccess),
+        }
+    }
+
+    fn operand(&mut self,
+               frame: &Frame<'a, 'tcx>,
+               operand: &mir::Operand<'tcx>)
+               -> Result<Value<'tcx>, ConstEvalErr> {
+        let constant = match *operand {
+            mir::Operand::Consume(ref lvalue) => {
+                return match self.lvalue(frame, lvalue)? {
+                    Place::Ptr(ptr) => self.read(&ptr),
+                    Place::Slice(..) => self.err(ErrKind::UnimplementedConstVal("unsized values")),
+                };
+            }
+            mir::Operand::Constant(ref constant) => constant,
+        };
+
+        let ty = self.monomorphize(frame, constant.ty);
+        if let ty::TyFnDef(def_id, substs, _) = ty.sty {
+            return Ok(Value::Fn(def_id, substs));
+        }
+        match constant.literal {
+            mir::Literal::Item { def_id, substs } => {
+                let substs = self.tcx.normalize_associated_type(&substs.subst(self.tcx,
+                                                                              frame.substs));
+                self.constant_item(def_id, substs, ty)
+            }
+            mir::Literal::Promoted { index } => {
+                let mir = frame.mir.promoted(index);
+                self.call(mir, frame.substs, vec![])
+            }
+            mir::Literal::Value { ref value } => self.from_const_val(value.clone()),
+        }
+    }
+
+    fn constant_item(&mut self,
+                     def_id: DefId,
+                     substs: &'tcx Substs<'tcx>,
+                     ty: Ty<'tcx>)
+                     -> Result<Value<'tcx>, ConstEvalErr> {
+        if self.tcx.trait_of_item(def_id).is_none() {
+            if let Some(mir) = self.load_mir(def_id) {
+                return self.call(mir, substs, vec![]);
+            }
+        }
+
+        // Associated constants still have to be resolved to their impl, and
+        // the constants of the current crate have no MIR yet during type
+        // checking, so fall back to evaluating the HIR.
+        let expr = match lookup_const_by_id(self.tcx, def_id, Some(substs)) {
+            Some((expr, _)) => expr,
+            None => return self.err(ErrKind::NonConstPath),
+        };
+        match eval_const_expr_partial(self.tcx, expr, EvalHint::UncheckedExprHint(ty), None) {
+            Ok(value) => self.from_const_val(value),
+            Err(err) => self.err(ErrKind::ErroneousReferencedConstant(box err)),
+        }
+    }
+
+    fn from_const_val(&mut self, value: ConstVal) -> Result<Value<'tcx>, ConstEvalErr> {
+        Ok(match value {
+            ConstVal::Bool(b) => Value::Bool(b),
+            ConstVal::Char(c) => Value::Char(c),
+            ConstVal::Integral(i) => Value::Int(i),
+            ConstVal::Float(f) => Value::Float(f),
+            ConstVal::Str(s) => Value::Str(s),
+            ConstVal::ByteStr(bytes) => {
+                let array = bytes.iter().map(|&b| Value::Int(ConstInt::U8(b))).collect();
+                Value::Ptr(self.memory.allocate(Value::Aggregate(0, array)))
+            }
+            ConstVal::Function(def_id) => Value::Fn(def_id, Substs::empty(self.tcx)),
+            ConstVal::Struct(_) |
+            ConstVal::Tuple(_) |
+            ConstVal::Array(..) |
+            ConstVal::Repeat(..) => {
+                return self.err(ErrKind::UnimplementedConstVal("aggregates evaluated \
+                                                                from the HIR"));
+            }
+            ConstVal::Dummy => return self.err(ErrKind::MiscCatchAll),
+        })
+    }
+
+    /// Converts a scalar value back to a `ConstVal`.
+    pub fn to_const_val(&self, value: &Value<'tcx>) -> Result<ConstVal, ErrKind> {
+        match *value {
+            Value::Bool(b) => Ok(ConstVal::Bool(b)),
+            Value::Char(c) => Ok(ConstVal::Char(c)),
+            Value::Int(i) => Ok(ConstVal::Integral(i)),
+            Value::Float(f) => Ok(ConstVal::Float(f)),
+            Value::Str(ref s) => Ok(ConstVal::Str(s.clone())),
+            Value::Undef => Err(ErrKind::UndefinedValue),
+            _ => Err(ErrKind::UnimplementedConstVal("non-scalar results of `const fn`s")),
+        }
+    }
+
+    fn rvalue(&mut self,
+              frame: &Frame<'a, 'tcx>,
+              rvalue: &mir::Rvalue<'tcx>)
+              -> Result<Value<'tcx>, ConstEvalErr> {
+        let tcx = self.tcx;
+        match *rvalue {
+            mir::Rvalue::Use(ref operand) => self.operand(frame, operand),
+
+            mir::Rvalue::Repeat(ref operand, ref count) => {
+                let value = self.operand(frame, operand)?;
+                let count = count.value.as_u64(tcx.sess.target.uint_type);
+                Ok(Value::Aggregate(0, vec![value; count as usize]))
+            }
+
+            mir::Rvalue::Ref(_, _, ref lvalue) => {
+                Ok(match self.lvalue(frame, lvalue)? {
+                    Place::Ptr(ptr) => Value::Ptr(ptr),
+                    Place::Slice(ptr, start, len) => Value::Slice(ptr, start, len),
+                })
+            }
+
+            mir::Rvalue::Len(ref lvalue) => {
+                let place = self.lvalue(frame, lvalue)?;
+                let len = self.place_len(frame, lvalue, &place)?;
+                let len = ConstUsize::new_truncating(len, tcx.sess.target.uint_type);
+                Ok(Value::Int(ConstInt::Usize(len)))
+            }
+
+            mir::Rvalue::Cast(kind, ref operand, cast_ty) => {
+                let value = self.operand(frame, operand)?;
+                let operand_ty = self.monomorphize(frame, operand.ty(&frame.mir, tcx));
+                let cast_ty = self.monomorphize(frame, cast_ty);
+                self.cast(kind, value, operand_ty, cast_ty)
+            }
+
+            mir::Rvalue::BinaryOp(op, ref lhs, ref rhs) => {
+                let lhs = self.operand(frame, lhs)?;
+                let rhs = self.operand(frame, rhs)?;
+                self.wrap(binary_op(op, lhs, rhs))
+            }
+
+            mir::Rvalue::CheckedBinaryOp(op, ref lhs, ref rhs) => {
+                let lhs = self.operand(frame, lhs)?;
+                let rhs = self.operand(frame, rhs)?;
+                match binary_op(op, lhs, rhs) {
+                    Ok(value) => Ok(Value::Aggregate(0, vec![value, Value::Bool(false)])),
+                    // The overflow is reported by the `Assert` terminator.
+                    Err(ErrKind::Math(ConstMathErr::Overflow(_))) |
+                    Err(ErrKind::Math(ConstMathErr::ShiftNegative)) => {
+                        Ok(Value::Aggregate(0, vec![Value::Undef, Value::Bool(true)]))
+                    }
+                    Err(kind) => self.err(kind),
+                }
+            }
+
+            mir::Rvalue::UnaryOp(op, ref operand) => {
+                let value = self.operand(frame, operand)?;
+                self.wrap(unary_op(op, value))
+            }
+
+            mir::Rvalue::Aggregate(ref kind, ref operands) => {
+                let variant = match *kind {
+                    mir::AggregateKind::Adt(_, variant, _) => variant,
+                    mir::AggregateKind::Vec | mir::AggregateKind::Tuple => 0,
+                    mir::AggregateKind::Closure(..) => {
+                        return self.err(ErrKind::UnimplementedConstVal("closures"));
+                    }
+                };
+                let mut fields = Vec::with_capacity(operands.len());
+                for operand in operands {
+                    fields.push(self.operand(frame, operand)?);
+                }
+                Ok(Value::Aggregate(variant, fields))
+            }
+
+            mir::Rvalue::Box(_) => self.err(ErrKind::UnimplementedConstVal("allocations")),
+            mir::Rvalue::InlineAsm { .. } => {
+                self.err(ErrKind::UnimplementedConstVal("inline assembly"))
+            }
+        }
+    }
+
+    fn cast(&mut self,
+            kind: mir::CastKind,
+            value: Value<'tcx>,
+            from_ty: Ty<'tcx>,
+            to_ty: Ty<'tcx>)
+            -> Result<Value<'tcx>, ConstEvalErr> {
+        match kind {
+            mir::CastKind::ReifyFnPointer |
+            mir::CastKind::UnsafeFnPointer => Ok(value),
+            mir::CastKind::Unsize => {
+                let pointee = from_ty.builtin_deref(true, ty::NoPreference).map(|mt| &mt.ty.sty);
+                match (value, pointee) {
+                    (Value::Ptr(ptr), Some(&ty::TyArray(_, len))) => {
+                        Ok(Value::Slice(ptr, 0, len as u64))
+                    }
+                    _ => self.err(ErrKind::UnimplementedConstVal("unsizing casts other \
+                                                                  than arrays to slices")),
+                }
+            }
+            mir::CastKind::Misc => {
+                let value = match (value, &from_ty.sty) {
+                    (Value::Aggregate(variant, _), &ty::TyEnum(adt, _)) => {
+                        ConstVal::Integral(adt.variants[variant].disr_val)
+                    }
+                    (value, _) => self.wrap(self.to_const_val(&value))?,
+                };
+                let value = self.wrap(cast_const(self.tcx, value, to_ty))?;
+                self.from_const_val(value)
+            }
+        }
+    }
+
+    /// Evaluates a terminator, returning the block to go to next, if any.
+    fn terminator(&mut self,
+                  frame: &Frame<'a, 'tcx>,
+                  terminator: &mir::Terminator<'tcx>)
+                  -> Result<Option<mir::BasicBlock>, ConstEvalErr> {
+        Ok(Some(match terminator.kind {
+            mir::TerminatorKind::Goto { target } |
+            mir::TerminatorKind::Drop { target, .. } => target,
+
+            mir::TerminatorKind::If { ref cond, targets: (then_bb, else_bb) } => {
+                if self.operand_bool(frame, cond)? { then_bb } else { else_bb }
+            }
+
+            mir::TerminatorKind::Switch { ref discr, ref targets, .. } => {
+                let ptr = match self.lvalue(frame, discr)? {
+                    Place::Ptr(ptr) => ptr,
+                    Place::Slice(..) => return self.err(ErrKind::InvalidMemoryAccess),
+                };
+                match self.read(&ptr)? {
+                    Value::Aggregate(variant, _) if variant < targets.len() => targets[variant],
+                    Value::Undef => return self.err(ErrKind::UndefinedValue),
+                    _ => return self.err(ErrKind::InvalidMemoryAccess),
+                }
+            }
+
+            mir::TerminatorKind::SwitchInt { ref discr, ref values, ref targets, .. } => {
+                let value = match self.lvalue(frame, discr)? {
+                    Place::Ptr(ptr) => self.read(&ptr)?,
+                    Place::Slice(..) => return self.err(ErrKind::InvalidMemoryAccess),
+                };
+                let mut target = targets[values.len()];
+                for (i, candidate) in values.iter().enumerate() {
+                    if self.wrap(switch_matches(&value, candidate))? {
+                        target = targets[i];
+                        break;
+                    }
+                }
+                target
+            }
+
+            mir::TerminatorKind::Return => return Ok(None),
+            mir::TerminatorKind::Unreachable => return self.err(ErrKind::Unreachable),
+            mir::TerminatorKind::Resume => {
+                return self.err(ErrKind::UnimplementedConstVal("unwinding"));
+            }
+
+            mir::TerminatorKind::DropAndReplace { ref location, ref value, target, .. } => {
+                let value = self.operand(frame, value)?;
+                let place = self.lvalue(frame, location)?;
+                self.write_place(place, value)?;
+                target
+            }
+
+            mir::TerminatorKind::Call { ref func, ref args, ref destination, .. } => {
+                let (def_id, substs) = match self.operand(frame, func)? {
+                    Value::Fn(def_id, substs) => (def_id, substs),
+                    _ => {
+                        return self.err(ErrKind::UnimplementedConstVal("calls through \
+                                                                        function pointers"));
+                    }
+                };
+                let (dest, target) = match *destination {
+                    Some((ref dest, target)) => (dest, target),
+                    // Calls which never return, like those to `panic!`,
+                    // are the only way for a `const fn` to fail on purpose.
+                    None => return self.err(ErrKind::Panic),
+                };
+                let mut arg_values = Vec::with_capacity(args.len());
+                for arg in args {
+                    arg_values.push(self.operand(frame, arg)?);
+                }
+                let value = self.call_const_fn(def_id, substs, arg_values)?;
+                let place = self.lvalue(frame, dest)?;
+                self.write_place(place, value)?;
+                target
+            }
+
+            mir::TerminatorKind::Assert { ref cond, expected, ref msg, target, .. } => {
+                if self.operand_bool(frame, cond)? != expected {
+                    let kind = match *msg {
+                        mir::AssertMessage::BoundsCheck { ref len, ref index } => {
+                            ErrKind::IndexOutOfBounds {
+                                len: self.operand_u64(frame, len)?,
+                                index: self.operand_u64(frame, index)?,
+                            }
+                        }
+                        mir::AssertMessage::Math(ref err) => ErrKind::Math(err.clone()),
+                    };
+                    return self.err(kind);
+                }
+                target
+            }
+        }))
+    }
+
+    fn operand_bool(&mut self,
+                    frame: &Frame<'a, 'tcx>,
+                    operand: &mir::Operand<'tcx>)
+                    -> Result<bool, ConstEvalErr> {
+        match self.operand(frame, operand)? {
+            Value::Bool(b) => Ok(b),
+            Value::Undef => self.err(ErrKind::UndefinedValue),
+            _ => self.err(ErrKind::MiscCatchAll),
+        }
+    }
+
+    fn operand_u64(&mut self,
+                   frame: &Frame<'a, 'tcx>,
+                   operand: &mir::Operand<'tcx>)
+                   -> Result<u64, ConstEvalErr> {
+        match self.operand(frame, operand)? {
+            Value::Int(i) => Ok(i.to_u64_unchecked()),
+            Value::Undef => self.err(ErrKind::UndefinedValue),
+            _ => self.err(ErrKind::MiscCatchAll),
+        }
+    }
+}
+
+impl<'a, 'tcx> Frame<'a, 'tcx> {
+    fn local(&self, lvalue: &mir::Lvalue<'tcx>) -> Pointer {
+        let index = self.mir.local_index(lvalue).expect("lvalue is not a local");
+        self.locals[index.index()].clone()
+    }
+}
+
+/// Evaluates a binary operation on scalar constants, as MIR optimizations
+/// folding operations on constant operands do.
+pub fn eval_const_binary_op(op: mir::BinOp, lhs: &ConstVal, rhs: &ConstVal)
+                            -> Result<ConstVal, ErrKind> {
+    let result = binary_op(op, scalar_value(lhs)?, scalar_value(rhs)?)?;
+    const_val(result)
+}
+
+/// Evaluates a unary operation on a scalar constant.
+pub fn eval_const_unary_op(op: mir::UnOp, value: &ConstVal) -> Result<ConstVal, ErrKind> {
+    const_val(unary_op(op, scalar_value(value)?)?)
+}
+
+fn scalar_value<'tcx>(value: &ConstVal) -> Result<Value<'tcx>, ErrKind> {
+    match *value {
+        ConstVal::Bool(b) => Ok(Value::Bool(b)),
+        ConstVal::Char(c) => Ok(Value::Char(c)),
+        ConstVal::Integral(i) => Ok(Value::Int(i)),
+        ConstVal::Float(f) => Ok(Value::Float(f)),
+        _ => Err(ErrKind::MiscCatchAll),
+    }
+}
+
+fn const_val(value: Value) -> Result<ConstVal, ErrKind> {
+    match value {
+        Value::Bool(b) => Ok(ConstVal::Bool(b)),
+        Value::Char(c) => Ok(ConstVal::Char(c)),
+        Value::Int(i) => Ok(ConstVal::Integral(i)),
+        Value::Float(f) => Ok(ConstVal::Float(f)),
+        _ => Err(ErrKind::MiscCatchAll),
+    }
+}
+
+fn compare(op: mir::BinOp, ordering: Ordering) -> Option<bool> {
+    match op {
+        mir::BinOp::Eq => Some(ordering == Ordering::Equal),
+        mir::BinOp::Ne => Some(ordering != Ordering::Equal),
+        mir::BinOp::Lt => Some(ordering == Ordering::Less),
+        mir::BinOp::Le => Some(ordering != Ordering::Greater),
+        mir::BinOp::Gt => Some(ordering == Ordering::Greater),
+        mir::BinOp::Ge => Some(ordering != Ordering::Less),
+        _ => None,
+    }
+}
+
+fn binary_op<'tcx>(op: mir::BinOp, lhs: Value<'tcx>, rhs: Value<'tcx>)
+                   -> Result<Value<'tcx>, ErrKind> {
+    use rustc::mir::repr::BinOp::*;
+
+    match (lhs, rhs) {
+        (Value::Int(a), Value::Int(b)) => {
+            if let Some(result) = compare(op, a.try_cmp(b)?) {
+                return Ok(Value::Bool(result));
+            }
+            Ok(Value::Int(match op {
+                Add => (a + b)?,
+                Sub => (a - b)?,
+                Mul => (a * b)?,
+                Div => (a / b)?,
+                Rem => (a % b)?,
+                BitXor => (a ^ b)?,
+                BitAnd => (a & b)?,
+                BitOr => (a | b)?,
+                Shl => (a << b)?,
+                Shr => (a >> b)?,
+                Eq | Ne | Lt | Le | Gt | Ge => bug!(),
+            }))
+        }
+        (Value::Float(a), Value::Float(b)) => {
+            if let Some(result) = compare(op, a.try_cmp(b)?) {
+                return Ok(Value::Bool(result));
+            }
+            Ok(Value::Float(match op {
+                Add => (a + b)?,
+                Sub => (a - b)?,
+                Mul => (a * b)?,
+                Div => (a / b)?,
+                Rem => (a % b)?,
+                _ => return Err(ErrKind::MiscBinaryOp),
+            }))
+        }
+        (Value::Bool(a), Value::Bool(b)) => {
+            if let Some(result) = compare(op, a.cmp(&b)) {
+                return Ok(Value::Bool(result));
+            }
+            Ok(Value::Bool(match op {
+                BitXor => a ^ b,
+                BitAnd => a & b,
+                BitOr => a | b,
+                _ => return Err(ErrKind::MiscBinaryOp),
+            }))
+        }
+        (Value::Char(a), Value::Char(b)) => {
+            match compare(op, a.cmp(&b)) {
+                Some(result) => Ok(Value::Bool(result)),
+                None => Err(ErrKind::MiscBinaryOp),
+            }
+        }
+        (Value::Undef, _) | (_, Value::Undef) => Err(ErrKind::UndefinedValue),
+        _ => Err(ErrKind::MiscBinaryOp),
+    }
+}
+
+fn unary_op<'tcx>(op: mir::UnOp, value: Value<'tcx>) -> Result<Value<'tcx>, ErrKind> {
+    match (op, value) {
+        (mir::UnOp::Not, Value::Int(i)) => Ok(Value::Int((!i)?)),
+        (mir::UnOp::Not, Value::Bool(b)) => Ok(Value::Bool(!b)),
+        (mir::UnOp::Neg, Value::Int(i)) => Ok(Value::Int((-i)?)),
+        (mir::UnOp::Neg, Value::Float(f)) => Ok(Value::Float(-f)),
+        (_, Value::Undef) => Err(ErrKind::UndefinedValue),
+        _ => Err(ErrKind::MiscCatchAll),
+    }
+}
+
+fn switch_matches(value: &Value, candidate: &ConstVal) -> Result<bool, ErrKind> {
+    match (value, candidate) {
+        (&Value::Int(a), &ConstVal::Integral(b)) => Ok(a.try_cmp(b)? == Ordering::Equal),
+        (&Value::Bool(a), &ConstVal::Bool(b)) => Ok(a == b),
+        (&Value::Char(a), &ConstVal::Char(b)) => Ok(a == b),
+        (&Value::Undef, _) => Err(ErrKind::UndefinedValue),
+        _ => Err(ErrKind::MiscCatchAll),
+    }
+}
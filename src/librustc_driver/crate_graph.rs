@@ -0,0 +1,203 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! The graph of the crates loaded while compiling a crate, printed by
+//! `--print=crate-graph` as JSON and by `--print=crate-graph-dot` as a
+//! Graphviz graph.
+//!
+//! This is mostly useful to find out why several versions of the same crate
+//! ended up being loaded, or why a crate is linked the way it is.
+
+use graphviz as dot;
+use rustc::middle::cstore::LOCAL_CRATE;
+use rustc::middle::dependency_format::{self, Linkage};
+use rustc::session::Session;
+use rustc::session::config::PrintRequest;
+use serialize::json;
+use syntax::ast::CrateNum;
+
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::io;
+
+#[derive(RustcEncodable)]
+struct CrateNode {
+    cnum: CrateNum,
+    name: String,
+    disambiguator: String,
+    /// Missing for the crate being compiled.
+    hash: Option<String>,
+    /// The files the crate was loaded from.
+    paths: Vec<String>,
+    /// How the crate is linked into the outputs of each crate type being
+    /// produced. Crate types which link nothing, like rlibs, are left out.
+    linkage: BTreeMap<String, &'static str>,
+    dependencies: Vec<CrateNum>,
+    /// The crates depending on this one directly.
+    dependents: Vec<CrateNum>,
+    /// The `extern crate` item which caused the crate to be loaded, either
+    /// because it names the crate or because it names one depending on it.
+    extern_crate: Option<ExternCrateSite>,
+}
+
+#[derive(RustcEncodable)]
+struct ExternCrateSite {
+    span: String,
+    /// Whether the `extern crate` item names this crate.
+    direct: bool,
+}
+
+fn linkage_str(linkage: Linkage) -> &'static str {
+    match linkage {
+        Linkage::NotLinked => "not-linked",
+        Linkage::IncludedFromDylib => "included-from-dylib",
+        Linkage::Static => "static",
+        Linkage::Dynamic => "dynamic",
+    }
+}
+
+fn build(sess: &Session, crate_name: &str) -> BTreeMap<CrateNum, CrateNode> {
+    let cstore = &sess.cstore;
+    let crates = cstore.crates();
+    // The linkage is computed here rather than taken from the session, as it
+    // is normally only decided right before translation.
+    let linkages = sess.crate_types.borrow().iter().map(|&ty| {
+        (ty.to_string(), dependency_format::calculate_type(sess, ty))
+    }).collect::<Vec<_>>();
+
+    let mut nodes = BTreeMap::new();
+    nodes.insert(LOCAL_CRATE, CrateNode {
+        cnum: LOCAL_CRATE,
+        name: crate_name.to_string(),
+        disambiguator: sess.local_crate_disambiguator().to_string(),
+        hash: None,
+        paths: sess.local_crate_source_file.iter()
+                                           .map(|path| path.display().to_string())
+                                           .collect(),
+        linkage: BTreeMap::new(),
+        dependencies: crates.iter().cloned().filter(|&cnum| {
+            cstore.extern_crate(cnum).map_or(false, |extern_crate| extern_crate.direct)
+        }).collect(),
+        dependents: vec![],
+        extern_crate: None,
+    });
+
+    for &cnum in &crates {
+        let src = cstore.used_crate_source(cnum);
+        nodes.insert(cnum, CrateNode {
+            cnum: cnum,
+            name: cstore.original_crate_name(cnum).to_string(),
+            disambiguator: cstore.crate_disambiguator(cnum).to_string(),
+            hash: Some(cstore.crate_hash(cnum).to_string()),
+            paths: src.dylib.iter().chain(&src.rlib).chain(&src.rmeta)
+                      .map(|&(ref path, _)| path.display().to_string())
+                      .collect(),
+            linkage: linkages.iter()
+                             .filter(|&&(_, ref list)| !list.is_empty())
+                             .map(|&(ref crate_type, ref list)| {
+                                 (crate_type.clone(), linkage_str(list[cnum as usize - 1]))
+                             })
+                             .collect(),
+            dependencies: cstore.crate_dependencies(cnum),
+            dependents: vec![],
+            extern_crate: cstore.extern_crate(cnum).map(|extern_crate| {
+                ExternCrateSite {
+                    span: sess.codemap().span_to_string(extern_crate.span),
+                    direct: extern_crate.direct,
+                }
+            }),
+        });
+    }
+
+    for (dependent, dependency) in edges(&nodes) {
+        if let Some(node) = nodes.get_mut(&dependency) {
+            node.dependents.push(dependent);
+        }
+    }
+
+    nodes
+}
+
+fn edges(nodes: &BTreeMap<CrateNum, CrateNode>) -> Vec<(CrateNum, CrateNum)> {
+    nodes.values().flat_map(|node| {
+        node.dependencies.iter().map(move |&dep| (node.cnum, dep))
+    }).collect()
+}
+
+/// Prints the crate graph in the formats requested by `--print`, once the
+/// crates have been loaded.
+pub fn print(sess: &Session, crate_name: &str) {
+    let nodes = build(sess, crate_name);
+    for req in &sess.opts.prints {
+        match *req {
+            PrintRequest::CrateGraph => {
+                println!("{}", json::as_json(&nodes.values().collect::<Vec<_>>()));
+            }
+            PrintRequest::CrateGraphDot => {
+                let graph = Graph { nodes: &nodes, edges: edges(&nodes) };
+                if let Err(e) = dot::render(&graph, &mut io::stdout()) {
+                    sess.err(&format!("failed to print the crate graph: {}", e));
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+struct Graph<'a> {
+    nodes: &'a BTreeMap<CrateNum, CrateNode>,
+    edges: Vec<(CrateNum, CrateNum)>,
+}
+
+impl<'a> dot::Labeller<'a> for Graph<'a> {
+    type Node = CrateNum;
+    type Edge = (CrateNum, CrateNum);
+
+    fn graph_id(&'a self) -> dot::Id<'a> {
+        dot::Id::new("crate_graph").unwrap()
+    }
+
+    fn node_id(&'a self, n: &CrateNum) -> dot::Id<'a> {
+        dot::Id::new(format!("crate{}", n)).unwrap()
+    }
+
+    fn node_label(&'a self, n: &CrateNum) -> dot::LabelText<'a> {
+        let node = &self.nodes[n];
+        let mut lines = vec![node.name.clone()];
+        if let Some(ref hash) = node.hash {
+            lines.push(hash.clone());
+        }
+        for (crate_type, linkage) in &node.linkage {
+            lines.push(format!("{}: {}", crate_type, linkage));
+        }
+        dot::LabelText::EscStr(lines.join("\\n").into())
+    }
+}
+
+impl<'a> dot::GraphWalk<'a> for Graph<'a> {
+    type Node = CrateNum;
+    type Edge = (CrateNum, CrateNum);
+
+    fn nodes(&'a self) -> dot::Nodes<'a, CrateNum> {
+        self.nodes.keys().cloned().collect()
+    }
+
+    fn edges(&'a self) -> dot::Edges<'a, (CrateNum, CrateNum)> {
+        Cow::Borrowed(&self.edges[..])
+    }
+
+    fn source(&'a self, edge: &(CrateNum, CrateNum)) -> CrateNum {
+        edge.0
+    }
+
+    fn target(&'a self, edge: &(CrateNum, CrateNum)) -> CrateNum {
+        edge.1
+    }
+}
@@ -16,13 +16,14 @@ use rustc_mir as mir;
 use rustc::mir::mir_map::MirMap;
 use rustc::session::{Session, CompileResult, compile_result_from_err_count};
 use rustc::session::config::{self, Input, OutputFilenames, OutputType,
-                             OutputTypes};
+                             OutputTypes, PrintRequest};
 use rustc::session::search_paths::PathKind;
 use rustc::lint;
 use rustc::middle::{self, dependency_format, stability, reachable};
 use rustc::middle::privacy::AccessLevels;
 use rustc::ty::{self, TyCtxt};
 use rustc::util::common::time;
+use rustc::util::profiling;
 use rustc::util::nodemap::NodeSet;
 use rustc_back::sha2::{Sha256, Digest};
 use rustc_borrowck as borrowck;
@@ -50,6 +51,7 @@ use std::io::{self, Write};
 use std::path::{Path, PathBuf};
 use syntax::{ast, diagnostics, visit};
 use syntax::attr::{self, AttrMetaMethods};
+use syntax::fixit;
 use syntax::parse::{self, PResult, token};
 use syntax::util::node_count::NodeCounter;
 use syntax;
@@ -71,6 +73,49 @@ pub fn compile_input(sess: &Session,
                      output: &Option<PathBuf>,
                      addl_plugins: Option<Vec<String>>,
                      control: &CompileController) -> CompileResult {
+    let profile_path = sess.opts.debugging_opts.self_profile.as_ref().map(PathBuf::from);
+    if profile_path.is_some() {
+        profiling::start();
+    }
+
+    let result = compile_input_phases(sess, cstore, cfg, input, outdir, output,
+                                      addl_plugins, control);
+
+    if let Some(ref profile_path) = profile_path {
+        if let Err(e) = profiling::finish(profile_path) {
+            sess.err(&format!("failed to write self-profile to `{}`: {}",
+                              profile_path.display(), e));
+        }
+    }
+    // Suggestions are applied even if compilation failed, as fixing errors
+    // is just as useful as fixing warnings.
+    if sess.opts.debugging_opts.apply_suggestions {
+        apply_suggestions(sess);
+    }
+
+    result.and_then(|_| compile_result_from_err_count(sess.err_count()))
+}
+
+fn apply_suggestions(sess: &Session) {
+    let replacements = sess.suggestion_replacements.borrow();
+    match fixit::apply_replacements(&replacements) {
+        Ok(0) => {}
+        Ok(n) => {
+            sess.note_without_error(&format!("applied {} suggestion{}",
+                                             n, if n == 1 { "" } else { "s" }));
+        }
+        Err(e) => sess.err(&format!("failed to apply suggestions: {}", e)),
+    }
+}
+
+fn compile_input_phases(sess: &Session,
+                        cstore: &CStore,
+                        cfg: ast::CrateConfig,
+                        input: &Input,
+                        outdir: &Option<PathBuf>,
+                        output: &Option<PathBuf>,
+                        addl_plugins: Option<Vec<String>>,
+                        control: &CompileController) -> CompileResult {
     macro_rules! controller_entry_point {
         ($point: ident, $tsess: expr, $make_state: expr, $phase_result: expr) => {{
             let state = &mut $make_state;
@@ -203,13 +248,18 @@ pub fn compile_input(sess: &Session,
             let trans = phase_4_translate_to_llvm(tcx,
                                                   mir_map.unwrap(),
                                                   analysis,
-                                                  &incremental_hashes_map);
+                                                  &incremental_hashes_map,
+                                                  &outputs);
 
             if log_enabled!(::log::INFO) {
                 println!("Post-trans");
                 tcx.print_debug_stats();
             }
 
+            if sess.opts.debugging_opts.print_type_sizes {
+                sess.code_stats.borrow().print_type_sizes();
+            }
+
             // Discard interned strings as they are no longer required.
             token::clear_ident_interner();
 
@@ -217,7 +267,13 @@ pub fn compile_input(sess: &Session,
         })??
     };
 
-    let phase5_result = phase_5_run_llvm_passes(sess, &crate_name, &trans, &outputs);
+    // Metadata and dep-info don't need LLVM to do anything, so skip running
+    // it if those are the only outputs requested.
+    let phase5_result = if sess.opts.output_types.should_trans() {
+        phase_5_run_llvm_passes(sess, &crate_name, &trans, &outputs)
+    } else {
+        Ok(())
+    };
 
     controller_entry_point!(after_llvm,
                             sess,
@@ -602,6 +658,7 @@ pub fn phase_2_configure_and_expand<'a, F>(sess: &Session,
 
         for registrar in registrars {
             registry.args_hidden = Some(registrar.args);
+            registry.plugin_name_hidden = Some(registrar.name);
             (registrar.fun)(&mut registry);
         }
     });
@@ -632,6 +689,11 @@ pub fn phase_2_configure_and_expand<'a, F>(sess: &Session,
         super::describe_lints(&sess.lint_store.borrow(), true);
         return Err(0);
     }
+    if sess.opts.prints.contains(&PrintRequest::DiagnosticsJson) {
+        super::print_diagnostics_json(&super::diagnostics_registry(),
+                                      &sess.lint_store.borrow());
+        return Err(0);
+    }
     sess.track_errors(|| sess.lint_store.borrow_mut().process_command_line(sess))?;
 
     krate = time(time_passes, "expansion", || {
@@ -728,6 +790,12 @@ pub fn phase_2_configure_and_expand<'a, F>(sess: &Session,
         read_local_crates(sess, &cstore, defs, &krate, crate_name, &sess.dep_graph)
     });
 
+    if sess.opts.prints.contains(&PrintRequest::CrateGraph) ||
+       sess.opts.prints.contains(&PrintRequest::CrateGraphDot) {
+        super::crate_graph::print(sess, crate_name);
+        return Err(sess.err_count());
+    }
+
     time(sess.time_passes(),
          "early lint checks",
          || lint::check_ast_crate(sess, &krate));
@@ -870,6 +938,10 @@ pub fn phase_3_run_analysis_passes<'tcx, F, R>(sess: &'tcx Session,
              "load_dep_graph",
              || rustc_incremental::load_dep_graph(tcx, &incremental_hashes_map));
 
+        // Let constant evaluation during type checking build the MIR of the
+        // `const fn`s of the crate on demand.
+        tcx.const_fn_mir_builder.set(Some(mir::mir_map::build_const_fn_mir));
+
         // passes are timed inside typeck
         try_with_f!(typeck::check_crate(tcx), (tcx, None, analysis, incremental_hashes_map));
 
@@ -926,6 +998,7 @@ pub fn phase_3_run_analysis_passes<'tcx, F, R>(sess: &'tcx Session,
             passes.push_pass(
                 box mir::transform::simplify_branches::SimplifyBranches::new("initial"));
             passes.push_pass(box mir::transform::simplify_cfg::SimplifyCfg::new("qualify-consts"));
+            passes.push_pass(box mir::transform::const_prop::PanicLint);
             // And run everything.
             passes.run_passes(tcx, &mut mir_map);
         });
@@ -988,7 +1061,8 @@ pub fn phase_3_run_analysis_passes<'tcx, F, R>(sess: &'tcx Session,
 pub fn phase_4_translate_to_llvm<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
                                            mut mir_map: MirMap<'tcx>,
                                            analysis: ty::CrateAnalysis,
-                                           incremental_hashes_map: &IncrementalHashesMap)
+                                           incremental_hashes_map: &IncrementalHashesMap,
+                                           output_filenames: &OutputFilenames)
                                            -> trans::CrateTranslation {
     let time_passes = tcx.sess.time_passes();
 
@@ -1011,6 +1085,12 @@ pub fn phase_4_translate_to_llvm<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
         passes.push_pass(box mir::transform::no_landing_pads::NoLandingPads);
         passes.push_pass(box mir::transform::simplify_cfg::SimplifyCfg::new("elaborate-drops"));
 
+        passes.push_pass(box mir::transform::inline::Inline);
+        passes.push_pass(box mir::transform::const_prop::ConstPropagation);
+        passes.push_pass(
+            box mir::transform::simplify_branches::SimplifyBranches::new("after-const-prop"));
+        passes.push_pass(box mir::transform::dead_stores::DeadStoreElimination);
+        passes.push_pass(box mir::transform::simplify_cfg::SimplifyCfg::new("after-const-prop"));
         passes.push_pass(box mir::transform::deaggregator::Deaggregator);
 
         passes.push_pass(box mir::transform::add_call_guards::AddCallGuards);
@@ -1022,7 +1102,11 @@ pub fn phase_4_translate_to_llvm<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
     let translation =
         time(time_passes,
              "translation",
-             move || trans::trans_crate(tcx, &mir_map, analysis, &incremental_hashes_map));
+             move || trans::trans_crate(tcx,
+                                        &mir_map,
+                                        analysis,
+                                        &incremental_hashes_map,
+                                        output_filenames));
 
     time(time_passes,
          "assert dep graph",
@@ -1097,6 +1181,9 @@ fn write_out_deps(sess: &Session, outputs: &OutputFilenames, crate_name: &str) {
                     out_filenames.push(p);
                 }
             }
+            OutputType::Metadata => {
+                out_filenames.push(link::filename_for_metadata(sess, crate_name, outputs));
+            }
             _ => {
                 out_filenames.push(file);
             }
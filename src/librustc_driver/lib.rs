@@ -54,6 +54,7 @@ extern crate rustc_save_analysis;
 extern crate rustc_trans;
 extern crate rustc_typeck;
 extern crate serialize;
+extern crate serialize as rustc_serialize; // used by deriving
 extern crate rustc_llvm as llvm;
 #[macro_use]
 extern crate log;
@@ -105,6 +106,7 @@ use errors::emitter::Emitter;
 #[cfg(test)]
 pub mod test;
 
+pub mod crate_graph;
 pub mod driver;
 pub mod pretty;
 pub mod target_features;
@@ -602,6 +604,17 @@ impl RustcDefaultCalls {
             return Compilation::Continue;
         }
 
+        // Lints registered by plugins are only known once the plugins have
+        // been loaded, so when given a crate the diagnostics database is
+        // printed by the driver instead, right after loading them.
+        let defer_diagnostics_json = input.is_some() &&
+            sess.opts.prints.contains(&PrintRequest::DiagnosticsJson);
+        // Likewise for the crate graph, which is printed once the crates are
+        // loaded.
+        let print_crate_graph = sess.opts.prints.iter().any(|req| {
+            *req == PrintRequest::CrateGraph || *req == PrintRequest::CrateGraphDot
+        });
+
         let attrs = match input {
             None => None,
             Some(input) => {
@@ -691,8 +704,23 @@ impl RustcDefaultCalls {
                     }
                     println!("");
                 }
+                PrintRequest::DiagnosticsJson => {
+                    if !defer_diagnostics_json {
+                        print_diagnostics_json(&diagnostics_registry(),
+                                               &sess.lint_store.borrow());
+                    }
+                }
+                PrintRequest::CrateGraph |
+                PrintRequest::CrateGraphDot => {
+                    if input.is_none() {
+                        early_error(ErrorOutputType::default(), "no input file provided");
+                    }
+                }
             }
         }
+        if defer_diagnostics_json || print_crate_graph {
+            return Compilation::Continue;
+        }
         return Compilation::Stop;
     }
 }
@@ -891,6 +919,82 @@ Available lint options:
     }
 }
 
+/// Prints every error code with an explanation, and every registered lint and
+/// lint group, as a single JSON object for consumption by tools.
+fn print_diagnostics_json(registry: &errors::registry::Registry,
+                          lint_store: &lint::LintStore) {
+    #[derive(RustcEncodable)]
+    struct Diagnostics {
+        errors: Vec<ErrorCode>,
+        lints: Vec<LintInfo>,
+        lint_groups: Vec<LintGroupInfo>,
+    }
+
+    #[derive(RustcEncodable)]
+    struct ErrorCode {
+        code: &'static str,
+        /// The explanation as printed by `--explain`, in markdown.
+        explanation: &'static str,
+    }
+
+    #[derive(RustcEncodable)]
+    struct LintInfo {
+        /// The name as used in lint attributes and `-W`/`-A`/`-D`/`-F`.
+        name: String,
+        /// "allow", "warn", "deny" or "forbid".
+        default_level: &'static str,
+        description: &'static str,
+        /// The names of the lint groups containing this lint.
+        groups: Vec<String>,
+        /// Was this lint registered by a compiler plugin?
+        plugin: bool,
+    }
+
+    #[derive(RustcEncodable)]
+    struct LintGroupInfo {
+        name: String,
+        lints: Vec<String>,
+        plugin: bool,
+    }
+
+    let mut groups = lint_store.get_lint_groups();
+    groups.sort_by(|&(x, _, _), &(y, _, _)| x.cmp(y));
+
+    let mut lints = lint_store.get_lints().to_vec();
+    lints.sort_by(|&(x, _), &(y, _)| x.name.cmp(y.name));
+
+    let diagnostics = Diagnostics {
+        errors: registry.descriptions().into_iter().map(|(code, explanation)| {
+            ErrorCode {
+                code: code,
+                explanation: explanation,
+            }
+        }).collect(),
+        lints: lints.into_iter().map(|(lint, plugin)| {
+            let id = lint::LintId::of(lint);
+            LintInfo {
+                name: lint.name_lower(),
+                default_level: lint.default_level.as_str(),
+                description: lint.desc,
+                groups: groups.iter()
+                              .filter(|&&(_, ref to, _)| to.contains(&id))
+                              .map(|&(name, _, _)| name.to_lowercase())
+                              .collect(),
+                plugin: plugin,
+            }
+        }).collect(),
+        lint_groups: groups.iter().map(|&(name, ref to, plugin)| {
+            LintGroupInfo {
+                name: name.to_lowercase(),
+                lints: to.iter().map(|id| id.to_string()).collect(),
+                plugin: plugin,
+            }
+        }).collect(),
+    };
+
+    println!("{}", serialize::json::as_json(&diagnostics));
+}
+
 fn describe_debug_flags() {
     println!("\nAvailable debug options:\n");
     print_flag_list("-Z", config::DB_OPTIONS);
@@ -26,6 +26,10 @@ use term;
 pub trait Emitter {
     /// Emit a structured diagnostic.
     fn emit(&mut self, db: &DiagnosticBuilder);
+
+    /// Emit the names of the crates passed with `--extern` which are never
+    /// used. Only emitters whose output is read by tools do anything with them.
+    fn emit_unused_externs(&mut self, _lint_level: &str, _unused_externs: &[String]) {}
 }
 
 impl Emitter for EmitterWriter {
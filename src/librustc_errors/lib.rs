@@ -73,6 +73,31 @@ pub enum RenderSpan {
 pub struct CodeSuggestion {
     pub msp: MultiSpan,
     pub substitutes: Vec<String>,
+    pub applicability: Applicability,
+}
+
+/// How confident we are that a suggestion is correct, which tools
+/// consuming the suggestion (like `-Z apply-suggestions`) use to decide
+/// whether they can apply it without a human looking at it.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Applicability {
+    /// The suggestion is definitely what the user intended, and applying it
+    /// yields code that compiles (as far as this diagnostic is concerned).
+    MachineApplicable,
+    /// The suggestion may be what the user intended, but it is uncertain.
+    MaybeIncorrect,
+    /// The applicability of the suggestion is unknown.
+    Unspecified,
+}
+
+impl Applicability {
+    pub fn to_str(self) -> &'static str {
+        match self {
+            Applicability::MachineApplicable => "MachineApplicable",
+            Applicability::MaybeIncorrect => "MaybeIncorrect",
+            Applicability::Unspecified => "Unspecified",
+        }
+    }
 }
 
 pub trait CodeMapper {
@@ -321,9 +346,21 @@ impl<'a> DiagnosticBuilder<'a> {
                                                msg: &str,
                                                suggestion: String)
                                                -> &mut DiagnosticBuilder<'a> {
+        self.span_suggestion_with_applicability(sp, msg, suggestion, Applicability::Unspecified)
+    }
+
+    /// Like `span_suggestion`, but also records how confident we are that
+    /// the suggestion is correct.
+    pub fn span_suggestion_with_applicability<S: Into<MultiSpan>>(&mut self,
+                                                                  sp: S,
+                                                                  msg: &str,
+                                                                  suggestion: String,
+                                                                  applicability: Applicability)
+                                                                  -> &mut DiagnosticBuilder<'a> {
         self.sub(Level::Help, msg, MultiSpan::new(), Some(Suggestion(CodeSuggestion {
             msp: sp.into(),
             substitutes: vec![suggestion],
+            applicability: applicability,
         })));
         self
     }
@@ -705,6 +742,12 @@ impl Handler {
         db.emit();
         if !self.continue_after_error.get() { self.abort_if_errors(); }
     }
+
+    /// Reports the crates passed with `--extern` which are never used, under
+    /// the level of the lint reporting them, to the emitter.
+    pub fn emit_unused_externs(&self, lint_level: &str, unused_externs: &[String]) {
+        self.emitter.borrow_mut().emit_unused_externs(lint_level, unused_externs);
+    }
 }
 
 
@@ -23,4 +23,14 @@ impl Registry {
     pub fn find_description(&self, code: &str) -> Option<&'static str> {
         self.descriptions.get(code).cloned()
     }
+
+    /// All error codes with a description, along with that description,
+    /// sorted by code.
+    pub fn descriptions(&self) -> Vec<(&'static str, &'static str)> {
+        let mut descriptions: Vec<_> = self.descriptions.iter()
+                                                        .map(|(&code, &desc)| (code, desc))
+                                                        .collect();
+        descriptions.sort();
+        descriptions
+    }
 }
@@ -8,11 +8,13 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
+use rustc::hir::def::Def;
+use rustc::hir::def_id::DefId;
 use rustc::hir::pat_util;
 use rustc::ty;
 use rustc::ty::adjustment;
-use util::nodemap::FnvHashMap;
-use lint::{LateContext, EarlyContext, LintContext, LintArray};
+use util::nodemap::{FnvHashMap, FnvHashSet};
+use lint::{Level, LateContext, EarlyContext, LintContext, LintArray};
 use lint::{LintPass, EarlyLintPass, LateLintPass};
 
 use std::collections::hash_map::Entry::{Occupied, Vacant};
@@ -101,7 +103,7 @@ impl LateLintPass for UnusedMut {
 declare_lint! {
     pub UNUSED_MUST_USE,
     Warn,
-    "unused result of a type flagged as #[must_use]"
+    "unused result of a type or function flagged as #[must_use]"
 }
 
 declare_lint! {
@@ -130,6 +132,16 @@ impl LateLintPass for UnusedResults {
             return;
         }
 
+        // A function or method flagged as #[must_use] is checked first, as
+        // whatever its return type is, discarding it is a mistake.
+        if let Some(def_id) = callee_def_id(cx, expr) {
+            let msg = format!("unused return value of `{}` which must be used",
+                              cx.tcx.item_path_str(def_id));
+            if check_must_use(cx, &cx.tcx.get_attrs(def_id), &msg, s.span) {
+                return;
+            }
+        }
+
         let t = cx.tcx.expr_ty(&expr);
         let warned = match t.sty {
             ty::TyTuple(ref tys) if tys.is_empty() => return,
@@ -138,7 +150,7 @@ impl LateLintPass for UnusedResults {
             ty::TyStruct(def, _) |
             ty::TyEnum(def, _) => {
                 let attrs = cx.tcx.get_attrs(def.did);
-                check_must_use(cx, &attrs[..], s.span)
+                check_must_use(cx, &attrs[..], "unused result which must be used", s.span)
             }
             _ => false,
         };
@@ -146,10 +158,11 @@ impl LateLintPass for UnusedResults {
             cx.span_lint(UNUSED_RESULTS, s.span, "unused result");
         }
 
-        fn check_must_use(cx: &LateContext, attrs: &[ast::Attribute], sp: Span) -> bool {
+        fn check_must_use(cx: &LateContext, attrs: &[ast::Attribute], msg: &str, sp: Span)
+                          -> bool {
             for attr in attrs {
                 if attr.check_name("must_use") {
-                    let mut msg = "unused result which must be used".to_string();
+                    let mut msg = msg.to_string();
                     // check for #[must_use="..."]
                     if let Some(s) = attr.value_str() {
                         msg.push_str(": ");
@@ -161,6 +174,26 @@ impl LateLintPass for UnusedResults {
             }
             false
         }
+
+        // The function or method called by `expr`, if it's a call at all.
+        // Calls through a trait find the trait's method, not the impl's.
+        fn callee_def_id(cx: &LateContext, expr: &hir::Expr) -> Option<DefId> {
+            match expr.node {
+                hir::ExprCall(ref callee, _) => {
+                    // The callee is an arbitrary expression,
+                    // it doesn't necessarily have a definition.
+                    match cx.tcx.expect_def_or_none(callee.id) {
+                        Some(Def::Fn(def_id)) | Some(Def::Method(def_id)) => Some(def_id),
+                        _ => None,
+                    }
+                }
+                hir::ExprMethodCall(..) => {
+                    let method_call = ty::MethodCall::expr(expr.id);
+                    cx.tcx.tables.borrow().method_map.get(&method_call).map(|m| m.def_id)
+                }
+                _ => None,
+            }
+        }
     }
 }
 
@@ -446,3 +479,102 @@ impl LateLintPass for UnusedAllocation {
         }
     }
 }
+
+declare_lint! {
+    pub UNUSED_CRATE_DEPENDENCIES,
+    Allow,
+    "crates passed with `--extern` that are never used"
+}
+
+#[derive(Copy, Clone)]
+pub struct UnusedCrateDependencies;
+
+impl LintPass for UnusedCrateDependencies {
+    fn get_lints(&self) -> LintArray {
+        lint_array!(UNUSED_CRATE_DEPENDENCIES)
+    }
+}
+
+impl UnusedCrateDependencies {
+    /// The other crates the type checker resolved method calls and trait
+    /// impls to, and those of the types it inferred, which name resolution
+    /// may not have seen a path to.
+    fn crates_used_by_typeck(&self, cx: &LateContext) -> FnvHashSet<ast::CrateNum> {
+        let mut crates = cx.tcx.selected_impl_crates.borrow().clone();
+        let tables = cx.tcx.tables.borrow();
+        for callee in tables.method_map.values() {
+            if callee.def_id.is_local() {
+                continue;
+            }
+            crates.insert(callee.def_id.krate);
+        }
+        for &ty in tables.node_types.values() {
+            for ty in ty.walk() {
+                let def_id = match ty.sty {
+                    ty::TyStruct(adt, _) | ty::TyEnum(adt, _) => adt.did,
+                    ty::TyTrait(ref obj) => obj.principal.def_id(),
+                    ty::TyFnDef(def_id, _, _) | ty::TyClosure(def_id, _) => def_id,
+                    _ => continue,
+                };
+                if def_id.is_local() {
+                    continue;
+                }
+                crates.insert(def_id.krate);
+            }
+        }
+        crates
+    }
+
+    /// The name `cnum` was passed with `--extern` under: the original name
+    /// in the `extern crate` item which loaded it, or else the name another
+    /// crate depends on it by.
+    fn extern_name(&self, cx: &LateContext, cnum: ast::CrateNum) -> String {
+        let cstore = &cx.sess().cstore;
+        let node_id = cstore.extern_crate(cnum).and_then(|extern_crate| {
+            if extern_crate.direct {
+                cx.tcx.map.as_local_node_id(extern_crate.def_id)
+            } else {
+                None
+            }
+        });
+        if let Some(node_id) = node_id {
+            let item = cx.tcx.map.expect_item(node_id);
+            if let hir::ItemExternCrate(orig_name) = item.node {
+                return orig_name.unwrap_or(item.name).as_str().to_string();
+            }
+        }
+        cstore.crate_name(cnum).to_string()
+    }
+}
+
+impl LateLintPass for UnusedCrateDependencies {
+    fn check_crate(&mut self, cx: &LateContext, krate: &hir::Crate) {
+        let level = cx.current_level(UNUSED_CRATE_DEPENDENCIES);
+        if level == Level::Allow || cx.sess().unused_extern_names.borrow().is_empty() {
+            return;
+        }
+
+        // Name resolution found no paths into these crates, see
+        // librustc_resolve/check_unused.rs, but type checking may still
+        // have used them.
+        let used_names = self.crates_used_by_typeck(cx).into_iter().map(|cnum| {
+            self.extern_name(cx, cnum)
+        }).collect::<FnvHashSet<_>>();
+        let unused_externs = cx.sess().unused_extern_names.borrow().iter().filter(|name| {
+            !used_names.contains(*name)
+        }).cloned().collect::<Vec<_>>();
+        if unused_externs.is_empty() {
+            return;
+        }
+
+        // Point at the start of the crate rather than at all of it.
+        let span = Span { hi: krate.span.lo, ..krate.span };
+        for name in unused_externs.iter() {
+            cx.span_lint(UNUSED_CRATE_DEPENDENCIES, span,
+                         &format!("crate `{}` is passed with `--extern` but never used", name));
+        }
+
+        // Let tools pruning dependency lists know, when they're listening.
+        cx.sess().diagnostic().emit_unused_externs(level.as_str(), &unused_externs);
+    }
+}
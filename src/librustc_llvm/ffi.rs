@@ -637,6 +637,10 @@ extern {
     pub fn LLVMAddNamedMetadataOperand(M: ModuleRef,
                                        Str: *const c_char,
                                        Val: ValueRef);
+    pub fn LLVMGetNamedMetadataNumOperands(M: ModuleRef, Name: *const c_char) -> c_uint;
+    pub fn LLVMGetNamedMetadataOperands(M: ModuleRef,
+                                        Name: *const c_char,
+                                        Dest: *mut ValueRef);
 
     /* Operations on scalar constants */
     pub fn LLVMConstInt(IntTy: TypeRef, N: c_ulonglong, SignExtend: Bool)
@@ -1969,7 +1973,9 @@ extern {
                                                OptLevel: CodeGenOptLevel,
                                                MergeFunctions: bool,
                                                SLPVectorize: bool,
-                                               LoopVectorize: bool);
+                                               LoopVectorize: bool,
+                                               PGOGenPath: *const c_char,
+                                               PGOUsePath: *const c_char);
     pub fn LLVMRustAddLibraryInfo(PM: PassManagerRef, M: ModuleRef,
                                   DisableSimplifyLibCalls: bool);
     pub fn LLVMRustRunFunctionPassManager(PM: PassManagerRef, M: ModuleRef);
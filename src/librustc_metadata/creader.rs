@@ -74,9 +74,10 @@ fn dump_crates(cstore: &CStore) {
         info!("  hash: {}", data.hash());
         info!("  reqd: {}", data.explicitly_linked.get());
         opt_source.map(|cs| {
-            let CrateSource { dylib, rlib, cnum: _ } = cs;
+            let CrateSource { dylib, rlib, rmeta, cnum: _ } = cs;
             dylib.map(|dl| info!("  dylib: {}", dl.0.display()));
             rlib.map(|rl|  info!("   rlib: {}", rl.0.display()));
+            rmeta.map(|rl| info!("  rmeta: {}", rl.0.display()));
         });
     })
 }
@@ -218,7 +219,8 @@ impl<'a> CrateReader<'a> {
                 let found = locs.iter().any(|l| {
                     let l = fs::canonicalize(l).ok();
                     source.dylib.as_ref().map(|p| &p.0) == l.as_ref() ||
-                    source.rlib.as_ref().map(|p| &p.0) == l.as_ref()
+                    source.rlib.as_ref().map(|p| &p.0) == l.as_ref() ||
+                    source.rmeta.as_ref().map(|p| &p.0) == l.as_ref()
                 });
                 if found {
                     ret = Some(cnum);
@@ -232,7 +234,9 @@ impl<'a> CrateReader<'a> {
             // have to make sure that this crate was found in the crate lookup
             // path (this is a top-level dependency) as we don't want to
             // implicitly load anything inside the dependency lookup path.
-            let prev_kind = source.dylib.as_ref().or(source.rlib.as_ref())
+            let prev_kind = source.dylib.as_ref()
+                                  .or(source.rlib.as_ref())
+                                  .or(source.rmeta.as_ref())
                                   .unwrap().1;
             if ret.is_none() && (prev_kind == kind || prev_kind == PathKind::All) {
                 ret = Some(cnum);
@@ -294,6 +298,7 @@ impl<'a> CrateReader<'a> {
                 ident: ident.to_string(),
                 dylib: lib.dylib.clone().map(|p| p.0),
                 rlib:  lib.rlib.clone().map(|p| p.0),
+                rmeta: lib.rmeta.clone().map(|p| p.0),
             })
         } else {
             None
@@ -301,7 +306,7 @@ impl<'a> CrateReader<'a> {
         // Maintain a reference to the top most crate.
         let root = if root.is_some() { root } else { &crate_paths };
 
-        let loader::Library { dylib, rlib, metadata } = lib;
+        let loader::Library { dylib, rlib, rmeta, metadata } = lib;
 
         let cnum_map = self.resolve_crate_deps(root, metadata.as_slice(), cnum, span);
         let staged_api = self.is_staged_api(metadata.as_slice());
@@ -323,6 +328,7 @@ impl<'a> CrateReader<'a> {
         let source = cstore::CrateSource {
             dylib: dylib,
             rlib: rlib,
+            rmeta: rmeta,
             cnum: cnum,
         };
 
@@ -797,7 +803,12 @@ impl<'a> CrateReader<'a> {
         // * Binaries use jemalloc
         // * Staticlibs and Rust dylibs use system malloc
         // * Rust dylibs used as dependencies to rust use jemalloc
-        let name = if need_lib_alloc && !self.sess.opts.cg.prefer_dynamic {
+        //
+        // Except that anything built with `-Z sanitizer` uses system malloc,
+        // as the sanitizer runtimes only know about the allocations made
+        // through the malloc they intercept.
+        let sanitizer = self.sess.opts.debugging_opts.sanitizer.is_some();
+        let name = if (need_lib_alloc && !self.sess.opts.cg.prefer_dynamic) || sanitizer {
             &self.sess.target.target.options.lib_allocation_crate
         } else {
             &self.sess.target.target.options.exe_allocation_crate
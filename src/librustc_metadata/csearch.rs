@@ -413,6 +413,17 @@ impl<'tcx> CrateStore<'tcx> for cstore::CStore {
         attr::contains_name(&self.crate_attrs(cnum), "no_builtins")
     }
 
+    fn crate_dependencies(&self, cnum: ast::CrateNum) -> Vec<ast::CrateNum> {
+        // The holes in the crate number map of a crate map to the crate itself.
+        let cdata = self.get_crate_data(cnum);
+        let mut deps: Vec<_> = cdata.cnum_map.borrow().iter()
+                                    .cloned()
+                                    .filter(|&dep| dep != cnum)
+                                    .collect();
+        deps.sort();
+        deps
+    }
+
     fn def_index_for_def_key(&self,
                              cnum: ast::CrateNum,
                              def: DefKey)
@@ -52,6 +52,8 @@ pub type CrateNumMap = IndexVec<ast::CrateNum, ast::CrateNum>;
 pub enum MetadataBlob {
     MetadataVec(Bytes),
     MetadataArchive(loader::ArchiveMetadata),
+    // A metadata-only file (`.rmeta`), read straight off the disk
+    MetadataRaw(Vec<u8>),
 }
 
 /// Holds information about a syntax_pos::FileMap imported from another crate.
@@ -340,6 +342,7 @@ impl MetadataBlob {
         match *self {
             MetadataVec(ref vec) => &vec[..],
             MetadataArchive(ref ar) => ar.as_slice(),
+            MetadataRaw(ref vec) => &vec[..],
         }
     }
 
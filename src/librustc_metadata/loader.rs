@@ -61,6 +61,14 @@
 //! Most parts of this loading systems keep the dylib/rlib as just separate
 //! variables.
 //!
+//! ## Metadata-only files
+//!
+//! A crate compiled with `--emit=metadata` produces a `lib*.rmeta` file which
+//! contains nothing but the crate's metadata. This is enough to type-check
+//! downstream crates against it, but not to link them, so these files are
+//! found alongside rlibs and dylibs and it is up to linkage (see
+//! `middle::dependency_format`) to complain if nothing better was available.
+//!
 //! ## Where to look?
 //!
 //! We can't exactly scan your whole hard drive when looking for dependencies,
@@ -212,7 +220,7 @@
 //! no means all of the necessary details. Take a look at the rest of
 //! metadata::loader or metadata::creader for all the juicy details!
 
-use cstore::{MetadataBlob, MetadataVec, MetadataArchive};
+use cstore::{MetadataBlob, MetadataVec, MetadataArchive, MetadataRaw};
 use common::{metadata_encoding_version, rustc_version};
 use decoder;
 
@@ -233,7 +241,7 @@ use std::cmp;
 use std::collections::HashMap;
 use std::fmt;
 use std::fs;
-use std::io;
+use std::io::{self, Read};
 use std::path::{Path, PathBuf};
 use std::ptr;
 use std::slice;
@@ -267,6 +275,7 @@ pub struct Context<'a> {
 pub struct Library {
     pub dylib: Option<(PathBuf, PathKind)>,
     pub rlib: Option<(PathBuf, PathKind)>,
+    pub rmeta: Option<(PathBuf, PathKind)>,
     pub metadata: MetadataBlob,
 }
 
@@ -279,7 +288,8 @@ pub struct ArchiveMetadata {
 pub struct CratePaths {
     pub ident: String,
     pub dylib: Option<PathBuf>,
-    pub rlib: Option<PathBuf>
+    pub rlib: Option<PathBuf>,
+    pub rmeta: Option<PathBuf>,
 }
 
 pub const METADATA_FILENAME: &'static str = "rust.metadata.bin";
@@ -287,6 +297,7 @@ pub const METADATA_FILENAME: &'static str = "rust.metadata.bin";
 #[derive(Copy, Clone, PartialEq)]
 enum CrateFlavor {
     Rlib,
+    Rmeta,
     Dylib
 }
 
@@ -294,6 +305,7 @@ impl fmt::Display for CrateFlavor {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         f.write_str(match *self {
             CrateFlavor::Rlib => "rlib",
+            CrateFlavor::Rmeta => "rmeta",
             CrateFlavor::Dylib => "dylib"
         })
     }
@@ -301,12 +313,8 @@ impl fmt::Display for CrateFlavor {
 
 impl CratePaths {
     fn paths(&self) -> Vec<PathBuf> {
-        match (&self.dylib, &self.rlib) {
-            (&None,    &None)              => vec!(),
-            (&Some(ref p), &None) |
-            (&None, &Some(ref p))          => vec!(p.clone()),
-            (&Some(ref p1), &Some(ref p2)) => vec!(p1.clone(), p2.clone()),
-        }
+        self.dylib.iter().chain(self.rlib.iter()).chain(self.rmeta.iter())
+                  .cloned().collect()
     }
 }
 
@@ -434,14 +442,18 @@ impl<'a> Context<'a> {
                 None => return FileDoesntMatch,
                 Some(file) => file,
             };
-            let (hash, rlib) = if file.starts_with(&rlib_prefix[..]) &&
-                                  file.ends_with(".rlib") {
+            let (hash, found_kind) = if file.starts_with(&rlib_prefix[..]) &&
+                                        file.ends_with(".rlib") {
                 (&file[(rlib_prefix.len()) .. (file.len() - ".rlib".len())],
-                 true)
+                 CrateFlavor::Rlib)
+            } else if file.starts_with(&rlib_prefix[..]) &&
+                      file.ends_with(".rmeta") {
+                (&file[(rlib_prefix.len()) .. (file.len() - ".rmeta".len())],
+                 CrateFlavor::Rmeta)
             } else if file.starts_with(&dylib_prefix) &&
                       file.ends_with(&dypair.1) {
                 (&file[(dylib_prefix.len()) .. (file.len() - dypair.1.len())],
-                 false)
+                 CrateFlavor::Dylib)
             } else {
                 if file.starts_with(&staticlib_prefix[..]) &&
                    file.ends_with(&staticpair.1) {
@@ -456,13 +468,15 @@ impl<'a> Context<'a> {
 
             let hash_str = hash.to_string();
             let slot = candidates.entry(hash_str)
-                                 .or_insert_with(|| (HashMap::new(), HashMap::new()));
-            let (ref mut rlibs, ref mut dylibs) = *slot;
+                                 .or_insert_with(|| {
+                                     (HashMap::new(), HashMap::new(), HashMap::new())
+                                 });
+            let (ref mut rlibs, ref mut rmetas, ref mut dylibs) = *slot;
             fs::canonicalize(path).map(|p| {
-                if rlib {
-                    rlibs.insert(p, kind);
-                } else {
-                    dylibs.insert(p, kind);
+                match found_kind {
+                    CrateFlavor::Rlib => { rlibs.insert(p, kind); }
+                    CrateFlavor::Rmeta => { rmetas.insert(p, kind); }
+                    CrateFlavor::Dylib => { dylibs.insert(p, kind); }
                 }
                 FileMatches
             }).unwrap_or(FileDoesntMatch)
@@ -471,21 +485,23 @@ impl<'a> Context<'a> {
 
         // We have now collected all known libraries into a set of candidates
         // keyed of the filename hash listed. For each filename, we also have a
-        // list of rlibs/dylibs that apply. Here, we map each of these lists
-        // (per hash), to a Library candidate for returning.
+        // list of rlibs/dylibs/rmetas that apply. Here, we map each of these
+        // lists (per hash), to a Library candidate for returning.
         //
         // A Library candidate is created if the metadata for the set of
         // libraries corresponds to the crate id and hash criteria that this
         // search is being performed for.
         let mut libraries = HashMap::new();
-        for (_hash, (rlibs, dylibs)) in candidates {
+        for (_hash, (rlibs, rmetas, dylibs)) in candidates {
             let mut slot = None;
             let rlib = self.extract_one(rlibs, CrateFlavor::Rlib, &mut slot);
             let dylib = self.extract_one(dylibs, CrateFlavor::Dylib, &mut slot);
+            let rmeta = self.extract_one(rmetas, CrateFlavor::Rmeta, &mut slot);
             if let Some((h, m)) = slot {
                 libraries.insert(h, Library {
                     dylib: dylib,
                     rlib: rlib,
+                    rmeta: rmeta,
                     metadata: m,
                 });
             }
@@ -509,6 +525,9 @@ impl<'a> Context<'a> {
                     if let Some((ref p, _)) = lib.rlib {
                         err.note(&format!("path: {}", p.display()));
                     }
+                    if let Some((ref p, _)) = lib.rmeta {
+                        err.note(&format!("path: {}", p.display()));
+                    }
                     let data = lib.metadata.as_slice();
                     let name = decoder::get_crate_name(data);
                     note_crate_name(&mut err, &name);
@@ -670,6 +689,7 @@ impl<'a> Context<'a> {
         let sess = self.sess;
         let dylibname = self.dylibname();
         let mut rlibs = HashMap::new();
+        let mut rmetas = HashMap::new();
         let mut dylibs = HashMap::new();
         {
             let locs = locs.map(|l| PathBuf::from(l)).filter(|loc| {
@@ -686,7 +706,8 @@ impl<'a> Context<'a> {
                         return false;
                     }
                 };
-                if file.starts_with("lib") && file.ends_with(".rlib") {
+                if file.starts_with("lib") &&
+                   (file.ends_with(".rlib") || file.ends_with(".rmeta")) {
                     return true
                 } else {
                     let (ref prefix, ref suffix) = dylibname;
@@ -697,18 +718,22 @@ impl<'a> Context<'a> {
                 }
                 sess.struct_err(&format!("extern location for {} is of an unknown type: {}",
                                          self.crate_name, loc.display()))
-                    .help(&format!("file name should be lib*.rlib or {}*.{}",
+                    .help(&format!("file name should be lib*.rlib, lib*.rmeta or {}*.{}",
                                    dylibname.0, dylibname.1))
                     .emit();
                 false
             });
 
             // Now that we have an iterator of good candidates, make sure
-            // there's at most one rlib and at most one dylib.
+            // there's at most one rlib, rmeta and dylib.
             for loc in locs {
-                if loc.file_name().unwrap().to_str().unwrap().ends_with(".rlib") {
+                let file = loc.file_name().unwrap().to_str().unwrap();
+                if file.ends_with(".rlib") {
                     rlibs.insert(fs::canonicalize(&loc).unwrap(),
                                  PathKind::ExternFlag);
+                } else if file.ends_with(".rmeta") {
+                    rmetas.insert(fs::canonicalize(&loc).unwrap(),
+                                  PathKind::ExternFlag);
                 } else {
                     dylibs.insert(fs::canonicalize(&loc).unwrap(),
                                   PathKind::ExternFlag);
@@ -716,16 +741,18 @@ impl<'a> Context<'a> {
             }
         };
 
-        // Extract the rlib/dylib pair.
+        // Extract the rlib/dylib/rmeta triple.
         let mut slot = None;
         let rlib = self.extract_one(rlibs, CrateFlavor::Rlib, &mut slot);
         let dylib = self.extract_one(dylibs, CrateFlavor::Dylib, &mut slot);
+        let rmeta = self.extract_one(rmetas, CrateFlavor::Rmeta, &mut slot);
 
-        if rlib.is_none() && dylib.is_none() { return None }
+        if rlib.is_none() && dylib.is_none() && rmeta.is_none() { return None }
         match slot {
             Some((_, metadata)) => Some(Library {
                 dylib: dylib,
                 rlib: rlib,
+                rmeta: rmeta,
                 metadata: metadata,
             }),
             None => None,
@@ -812,6 +839,18 @@ fn get_metadata_section_imp(target: &Target, flavor: CrateFlavor, filename: &Pat
             }
         };
     }
+    if flavor == CrateFlavor::Rmeta {
+        // Metadata-only files are written out exactly as they would appear
+        // inside an rlib, so there's no container to look through here.
+        let mut buf = Vec::new();
+        if let Err(e) = fs::File::open(filename).and_then(|mut f| f.read_to_end(&mut buf)) {
+            return Err(format!("failed to read rmeta metadata: '{}': {}",
+                               filename.display(), e));
+        }
+        let blob = MetadataRaw(buf);
+        try!(verify_decompressed_encoding_version(&blob, filename));
+        return Ok(blob);
+    }
     unsafe {
         let buf = common::path2cstr(filename);
         let mb = llvm::LLVMRustCreateMemoryBufferWithContentsOfFile(buf.as_ptr());
@@ -898,7 +937,13 @@ pub fn read_meta_section_name(_target: &Target) -> &'static str {
 pub fn list_file_metadata(target: &Target, path: &Path,
                           out: &mut io::Write) -> io::Result<()> {
     let filename = path.file_name().unwrap().to_str().unwrap();
-    let flavor = if filename.ends_with(".rlib") { CrateFlavor::Rlib } else { CrateFlavor::Dylib };
+    let flavor = if filename.ends_with(".rlib") {
+        CrateFlavor::Rlib
+    } else if filename.ends_with(".rmeta") {
+        CrateFlavor::Rmeta
+    } else {
+        CrateFlavor::Dylib
+    };
     match get_metadata_section(target, flavor, path) {
         Ok(bytes) => decoder::list_crate_metadata(bytes.as_slice(), out),
         Err(msg) => {
@@ -49,4 +49,5 @@ pub mod graphviz;
 mod hair;
 pub mod mir_map;
 pub mod pretty;
+pub mod pretty_html;
 pub mod transform;
@@ -32,6 +32,7 @@ use rustc::ty::{self, Ty, TyCtxt};
 use rustc::ty::subst::Substs;
 use rustc::hir;
 use rustc::hir::intravisit::{self, FnKind, Visitor};
+use rustc::hir::map::blocks::FnLikeNode;
 use syntax::ast;
 use syntax_pos::Span;
 
@@ -49,6 +50,35 @@ pub fn build_mir_for_crate<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>) -> MirMap<'tcx
     map
 }
 
+/// Builds the MIR of the `const fn` `def_id` of the current crate, so that
+/// calls to it can be evaluated during type checking. Returns `None` if its
+/// body hasn't been type-checked (successfully) yet.
+pub fn build_const_fn_mir<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>, def_id: DefId)
+                                    -> Option<Mir<'tcx>> {
+    let fn_id = match tcx.map.as_local_node_id(def_id) {
+        Some(fn_id) => fn_id,
+        None => return None,
+    };
+    let fn_like = match FnLikeNode::from_node(tcx.map.get(fn_id)) {
+        Some(fn_like) => fn_like,
+        None => return None,
+    };
+    // The tables may contain error types after a type error.
+    if tcx.sess.has_errors() || !tcx.tables.borrow().liberated_fn_sigs.contains_key(&fn_id) {
+        return None;
+    }
+
+    let mut map = MirMap::new(tcx.dep_graph.clone());
+    {
+        let mut build = BuildMir {
+            tcx: tcx,
+            map: &mut map,
+        };
+        build.visit_fn(fn_like.kind(), fn_like.decl(), fn_like.body(), fn_like.span(), fn_id);
+    }
+    map.map.get(&def_id).cloned()
+}
+
 /// A pass to lift all the types and substitutions in a Mir
 /// to the global tcx. Sadly, we don't have a "folder" that
 /// can change 'tcx so we have to transmute afterwards.
@@ -9,11 +9,13 @@
 // except according to those terms.
 
 use build::{ScopeAuxiliaryVec, ScopeId};
+use pretty_html;
 use rustc::hir;
 use rustc::hir::def_id::DefId;
 use rustc::mir::repr::*;
 use rustc::mir::mir_map::MirMap;
 use rustc::mir::transform::MirSource;
+use rustc::session::config::MirDumpFormat;
 use rustc::ty::TyCtxt;
 use rustc_data_structures::fnv::FnvHashMap;
 use rustc_data_structures::indexed_vec::{Idx};
@@ -40,6 +42,10 @@ const ALIGN: usize = 40;
 /// - `substring1&substring2,...` -- `&`-separated list of substrings
 ///   that can appear in the pass-name or the `item_path_str` for the given
 ///   node-id. If any one of the substrings match, the data is dumped out.
+///
+/// With `-Z dump-mir-format=html`, the dumps for each node are instead
+/// collected into a single `rustc.node<node_id>.html` report; see
+/// `pretty_html`.
 pub fn dump_mir<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
                           pass_name: &str,
                           disambiguator: &Display,
@@ -63,19 +69,12 @@ pub fn dump_mir<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
         return;
     }
 
-    let promotion_id = match src {
-        MirSource::Promoted(_, id) => format!("-{:?}", id),
-        _ => String::new()
-    };
+    if tcx.sess.opts.debugging_opts.dump_mir_format == MirDumpFormat::Html {
+        pretty_html::dump_pass(tcx, pass_name, disambiguator, src, mir, auxiliary);
+        return;
+    }
 
-    let mut file_path = PathBuf::new();
-    if let Some(ref file_dir) = tcx.sess.opts.debugging_opts.dump_mir_dir {
-        let p = Path::new(file_dir);
-        file_path.push(p);
-    };
-    let file_name = format!("rustc.node{}{}.{}.{}.mir",
-                            node_id, promotion_id, pass_name, disambiguator);
-    file_path.push(&file_name);
+    let file_path = dump_file_path(tcx, src, &format!("{}.{}.mir", pass_name, disambiguator));
     let _ = fs::File::create(&file_path).and_then(|mut file| {
         try!(writeln!(file, "// MIR for `{}`", node_path));
         try!(writeln!(file, "// node_id = {}", node_id));
@@ -87,6 +86,23 @@ pub fn dump_mir<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
     });
 }
 
+/// The path of a file named `rustc.node<node_id>.<suffix>` for `src`, in the
+/// directory given by `-Z dump-mir-dir`.
+pub fn dump_file_path(tcx: TyCtxt, src: MirSource, suffix: &str) -> PathBuf {
+    let promotion_id = match src {
+        MirSource::Promoted(_, id) => format!("-{:?}", id),
+        _ => String::new()
+    };
+
+    let mut file_path = PathBuf::new();
+    if let Some(ref file_dir) = tcx.sess.opts.debugging_opts.dump_mir_dir {
+        let p = Path::new(file_dir);
+        file_path.push(p);
+    };
+    file_path.push(&format!("rustc.node{}{}.{}", src.item_id(), promotion_id, suffix));
+    file_path
+}
+
 /// Write out a human-readable textual representation for the given MIR.
 pub fn write_mir_pretty<'a, 'b, 'tcx, I>(tcx: TyCtxt<'b, 'tcx, 'tcx>,
                                          iter: I,
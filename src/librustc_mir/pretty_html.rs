@@ -0,0 +1,300 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! The report written by `-Z dump-mir-format=html`.
+//!
+//! Rather than one text file for each pass, every node (and every promoted
+//! constant) gets a single `rustc.node<node_id>.html` page, listing its MIR
+//! as of each dump in the order the dumps were taken, along with a diff
+//! against the previous dump. Analyses may also attach the state they
+//! computed on entry to each basic block with `dump_dataflow`; it is shown
+//! under the block's label in the dump it was computed from, which is how
+//! `ElaborateDrops` explains where it put drop flags.
+//!
+//! There is no point at which all the MIR passes are known to be done, so
+//! the report is simply rewritten every time something is added to it.
+
+use build::ScopeAuxiliaryVec;
+use pretty::{self, write_mir_fn};
+use rustc::mir::repr::*;
+use rustc::mir::transform::MirSource;
+use rustc::session::config::MirDumpFormat;
+use rustc::ty::TyCtxt;
+use rustc_data_structures::fnv::FnvHashMap;
+use std::cell::RefCell;
+use std::fmt::Display;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+/// The number of unchanged lines shown around each change in a diff.
+const DIFF_CONTEXT: usize = 3;
+
+thread_local! {
+    /// The reports started so far, by the path they are written to.
+    static REPORTS: RefCell<FnvHashMap<PathBuf, Report>> = RefCell::new(FnvHashMap())
+}
+
+struct Report {
+    node_path: String,
+    dumps: Vec<Dump>,
+}
+
+/// The MIR as of one pass, along with any dataflow results computed on it.
+struct Dump {
+    title: String,
+    lines: Vec<String>,
+    dataflow: Vec<Dataflow>,
+}
+
+struct Dataflow {
+    analysis: String,
+    entry_sets: Vec<String>,
+}
+
+/// Adds the MIR for `src` as of `pass_name` to its report. Called by
+/// `pretty::dump_mir`, once it has checked the `-Z dump-mir` filter.
+pub fn dump_pass<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
+                           pass_name: &str,
+                           disambiguator: &Display,
+                           src: MirSource,
+                           mir: &Mir<'tcx>,
+                           auxiliary: Option<&ScopeAuxiliaryVec>) {
+    let mut text = vec![];
+    if write_mir_fn(tcx, src, mir, &mut text, auxiliary).is_err() {
+        return;
+    }
+    let dump = Dump {
+        title: format!("{}.{}", pass_name, disambiguator),
+        lines: String::from_utf8_lossy(&text).lines().map(|line| line.to_string()).collect(),
+        dataflow: vec![],
+    };
+
+    let file_path = pretty::dump_file_path(tcx, src, "html");
+    REPORTS.with(|reports| {
+        let mut reports = reports.borrow_mut();
+        let report = reports.entry(file_path.clone()).or_insert_with(|| {
+            Report {
+                node_path: tcx.item_path_str(tcx.map.local_def_id(src.item_id())),
+                dumps: vec![],
+            }
+        });
+        report.dumps.push(dump);
+        write_report(&file_path, report);
+    });
+}
+
+/// Whether a report is being written for `src`, so that `dump_dataflow`
+/// would record anything. Analyses should check this before going to the
+/// trouble of rendering their results.
+pub fn dataflow_enabled(tcx: TyCtxt, src: MirSource) -> bool {
+    if tcx.sess.opts.debugging_opts.dump_mir_format != MirDumpFormat::Html {
+        return false;
+    }
+    let file_path = pretty::dump_file_path(tcx, src, "html");
+    REPORTS.with(|reports| reports.borrow().contains_key(&file_path))
+}
+
+/// Attaches the state computed by `analysis` on entry to each basic block,
+/// indexed by block, to the latest dump in the report for `src`. That dump
+/// should be of the MIR the analysis ran on, which is the case for a pass
+/// looking at the MIR it was given.
+pub fn dump_dataflow(tcx: TyCtxt, src: MirSource, analysis: &str, entry_sets: Vec<String>) {
+    if !dataflow_enabled(tcx, src) {
+        return;
+    }
+    let file_path = pretty::dump_file_path(tcx, src, "html");
+    REPORTS.with(|reports| {
+        let mut reports = reports.borrow_mut();
+        let report = reports.get_mut(&file_path).unwrap();
+        match report.dumps.last_mut() {
+            Some(dump) => {
+                dump.dataflow.push(Dataflow {
+                    analysis: analysis.to_string(),
+                    entry_sets: entry_sets,
+                });
+            }
+            None => return,
+        }
+        write_report(&file_path, report);
+    });
+}
+
+fn write_report(file_path: &Path, report: &Report) {
+    let _ = fs::File::create(file_path).and_then(|mut file| write_html(&mut file, report));
+}
+
+fn write_html(w: &mut Write, report: &Report) -> io::Result<()> {
+    let node_path = escape(&report.node_path);
+    writeln!(w, "<!DOCTYPE html>")?;
+    writeln!(w, "<html>")?;
+    writeln!(w, "<head>")?;
+    writeln!(w, "<meta charset=\"utf-8\">")?;
+    writeln!(w, "<title>MIR for {}</title>", node_path)?;
+    writeln!(w, "<style>")?;
+    writeln!(w, "body {{ font-family: sans-serif; }}")?;
+    writeln!(w, "pre {{ background: #f8f8f8; padding: 0.5em; }}")?;
+    writeln!(w, ".unchanged {{ color: #888; }}")?;
+    writeln!(w, ".added {{ background: #dfd; }}")?;
+    writeln!(w, ".removed {{ background: #fdd; }}")?;
+    writeln!(w, ".elided {{ color: #888; }}")?;
+    writeln!(w, ".dataflow {{ color: #06c; }}")?;
+    writeln!(w, "</style>")?;
+    writeln!(w, "</head>")?;
+    writeln!(w, "<body>")?;
+    writeln!(w, "<h1>MIR for <code>{}</code></h1>", node_path)?;
+
+    // An index of the dumps, greying out those which changed nothing.
+    writeln!(w, "<ol start=\"0\">")?;
+    for (i, dump) in report.dumps.iter().enumerate() {
+        let unchanged = i > 0 && report.dumps[i - 1].lines == dump.lines;
+        writeln!(w, "<li><a href=\"#dump{}\"{}>{}</a></li>",
+                 i,
+                 if unchanged { " class=\"unchanged\"" } else { "" },
+                 escape(&dump.title))?;
+    }
+    writeln!(w, "</ol>")?;
+
+    for (i, dump) in report.dumps.iter().enumerate() {
+        writeln!(w, "<h2 id=\"dump{}\">{}. {}</h2>", i, i, escape(&dump.title))?;
+        if i > 0 {
+            let previous = &report.dumps[i - 1];
+            let link = format!("<a href=\"#dump{}\">{}</a>", i - 1, escape(&previous.title));
+            if previous.lines == dump.lines {
+                writeln!(w, "<p>No changes since {}.</p>", link)?;
+            } else {
+                writeln!(w, "<p>Changes since {}:</p>", link)?;
+                writeln!(w, "<pre>")?;
+                write_diff(w, &previous.lines, &dump.lines)?;
+                writeln!(w, "</pre>")?;
+            }
+        }
+        writeln!(w, "<pre>")?;
+        write_dump(w, dump)?;
+        writeln!(w, "</pre>")?;
+    }
+
+    writeln!(w, "</body>")?;
+    writeln!(w, "</html>")
+}
+
+/// Writes out the MIR of `dump`, with the dataflow state on entry to each
+/// basic block under its label.
+fn write_dump(w: &mut Write, dump: &Dump) -> io::Result<()> {
+    for line in &dump.lines {
+        writeln!(w, "{}", escape(line))?;
+        if let Some(block) = block_label(line) {
+            for dataflow in &dump.dataflow {
+                if let Some(entry_set) = dataflow.entry_sets.get(block) {
+                    writeln!(w, "<span class=\"dataflow\">        // {} on entry: {}</span>",
+                             escape(&dataflow.analysis),
+                             escape(entry_set))?;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// The index of the basic block `line` is the label of, if any.
+fn block_label(line: &str) -> Option<usize> {
+    let line = line.trim();
+    if line.starts_with("bb") && line.ends_with(": {") {
+        line[2..line.len() - 3].parse().ok()
+    } else {
+        None
+    }
+}
+
+#[derive(Copy, Clone, PartialEq)]
+enum Change {
+    Unchanged,
+    Added,
+    Removed,
+}
+
+/// Writes out the lines of `new` which are not in `old` and vice versa,
+/// with a few unchanged lines around each of them.
+fn write_diff(w: &mut Write, old: &[String], new: &[String]) -> io::Result<()> {
+    let changes = diff_lines(old, new);
+    let near_change = |i: usize| {
+        let start = if i < DIFF_CONTEXT { 0 } else { i - DIFF_CONTEXT };
+        let end = ::std::cmp::min(i + DIFF_CONTEXT + 1, changes.len());
+        changes[start..end].iter().any(|&(change, _)| change != Change::Unchanged)
+    };
+
+    let mut elided = false;
+    for (i, &(change, line)) in changes.iter().enumerate() {
+        if !near_change(i) {
+            if !elided {
+                writeln!(w, "<span class=\"elided\">...</span>")?;
+                elided = true;
+            }
+            continue;
+        }
+        elided = false;
+        match change {
+            Change::Unchanged => writeln!(w, "  {}", escape(line))?,
+            Change::Added => writeln!(w, "<span class=\"added\">+ {}</span>", escape(line))?,
+            Change::Removed => writeln!(w, "<span class=\"removed\">- {}</span>", escape(line))?,
+        }
+    }
+    Ok(())
+}
+
+/// Finds a longest common subsequence of the lines of `old` and `new`, and
+/// returns the lines of both in order, marked as being in it or not.
+fn diff_lines<'a>(old: &'a [String], new: &'a [String]) -> Vec<(Change, &'a str)> {
+    // Passes usually touch a few lines in the middle, so only the part
+    // between the common prefix and suffix needs the quadratic search.
+    let prefix = old.iter().zip(new).take_while(|&(a, b)| a == b).count();
+    let suffix = old[prefix..].iter().rev().zip(new[prefix..].iter().rev())
+                              .take_while(|&(a, b)| a == b).count();
+    let old_middle = &old[prefix..old.len() - suffix];
+    let new_middle = &new[prefix..new.len() - suffix];
+
+    // `lengths[i][j]` is the length of the longest common subsequence of
+    // `old_middle[i..]` and `new_middle[j..]`.
+    let mut lengths = vec![vec![0; new_middle.len() + 1]; old_middle.len() + 1];
+    for i in (0..old_middle.len()).rev() {
+        for j in (0..new_middle.len()).rev() {
+            lengths[i][j] = if old_middle[i] == new_middle[j] {
+                lengths[i + 1][j + 1] + 1
+            } else {
+                ::std::cmp::max(lengths[i + 1][j], lengths[i][j + 1])
+            };
+        }
+    }
+
+    let mut changes: Vec<_> = old[..prefix].iter().map(|line| {
+        (Change::Unchanged, &line[..])
+    }).collect();
+    let (mut i, mut j) = (0, 0);
+    while i < old_middle.len() || j < new_middle.len() {
+        if i < old_middle.len() && j < new_middle.len() && old_middle[i] == new_middle[j] {
+            changes.push((Change::Unchanged, &old_middle[i][..]));
+            i += 1;
+            j += 1;
+        } else if j < new_middle.len() &&
+                  (i == old_middle.len() || lengths[i][j + 1] >= lengths[i + 1][j]) {
+            changes.push((Change::Added, &new_middle[j][..]));
+            j += 1;
+        } else {
+            changes.push((Change::Removed, &old_middle[i][..]));
+            i += 1;
+        }
+    }
+    changes.extend(old[old.len() - suffix..].iter().map(|line| (Change::Unchanged, &line[..])));
+    changes
+}
+
+fn escape(s: &str) -> String {
+    s.replace("&", "&amp;").replace("<", "&lt;").replace(">", "&gt;")
+}
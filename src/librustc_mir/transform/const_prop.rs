@@ -0,0 +1,625 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! `ConstPropagation` propagates constants assigned to locals to the places
+//! the locals are read, and folds operations whose operands all end up
+//! being constant. Branches on the folded values are left for
+//! `SimplifyBranches` to remove. It only runs with `-Z mir-opt-level=2` or
+//! higher.
+//!
+//! `PanicLint` uses the same analysis to find the `Assert` terminators
+//! which are bound to fail, such as bounds checks on constant indices past
+//! the end of an array or checks for division by a zero, and reports them
+//! through the `const_err` lint. Failing operations on constant
+//! expressions are left out, as the constant checker already reports
+//! those; it only evaluates indexing with `#![feature(const_indexing)]`
+//! though, so constant out-of-bounds indexing is reported here otherwise.
+
+use rustc::lint::builtin::CONST_ERR;
+use rustc::middle::const_val::ConstVal;
+use rustc::mir::repr::*;
+use rustc::mir::transform::{MirPass, MirSource, Pass};
+use rustc::mir::visit::{LvalueContext, Visitor};
+use rustc::ty::{self, Ty, TyCtxt};
+use rustc_const_eval::ErrKind;
+use rustc_const_eval::interpret::{eval_const_binary_op, eval_const_unary_op};
+use rustc_const_math::{ConstInt, ConstMathErr, ConstUsize, Op};
+use rustc_data_structures::bitvec::BitVector;
+use rustc_data_structures::indexed_vec::Idx;
+use syntax_pos::Span;
+
+pub struct ConstPropagation;
+
+impl Pass for ConstPropagation {}
+
+impl<'tcx> MirPass<'tcx> for ConstPropagation {
+    fn run_pass<'a>(&mut self, tcx: TyCtxt<'a, 'tcx, 'tcx>,
+                    src: MirSource, mir: &mut Mir<'tcx>) {
+        if tcx.sess.opts.mir_opt_level < 2 { return; }
+        // Constants and promoted rvalues are evaluated as a whole.
+        if let MirSource::Fn(_) = src {} else { return; }
+
+        let propagator = Propagator::new(tcx, mir);
+        let entry_states = propagator.analyze(mir);
+
+        for (bb, entry_state) in entry_states.into_iter().enumerate() {
+            // Blocks which are never reached are left for `SimplifyCfg`.
+            if let Some(mut state) = entry_state {
+                let block = &mut mir.basic_blocks_mut()[BasicBlock::new(bb)];
+                for stmt in &mut block.statements {
+                    propagator.propagate_statement(&mut state, stmt);
+                }
+                propagator.propagate_terminator(&state, block.terminator_mut());
+            }
+        }
+    }
+}
+
+/// Runs with the passes preparing the MIR for borrowck, so that the lints
+/// are reported along with all others.
+pub struct PanicLint;
+
+impl Pass for PanicLint {}
+
+impl<'tcx> MirPass<'tcx> for PanicLint {
+    fn run_pass<'a>(&mut self, tcx: TyCtxt<'a, 'tcx, 'tcx>,
+                    src: MirSource, mir: &mut Mir<'tcx>) {
+        if let MirSource::Fn(_) = src {} else { return; }
+
+        let propagator = Propagator::new(tcx, mir);
+        let entry_states = propagator.analyze(mir);
+
+        for (bb, entry_state) in entry_states.into_iter().enumerate() {
+            // Blocks which are never reached can not panic.
+            if let Some(mut state) = entry_state {
+                let bb = BasicBlock::new(bb);
+                for stmt in &mir[bb].statements {
+                    propagator.apply_statement(&mut state, stmt);
+                }
+                if let Some(msg) = propagator.assert_failure(mir, &state, bb) {
+                    tcx.sess.add_lint(CONST_ERR,
+                                      src.item_id(),
+                                      mir[bb].terminator().source_info.span,
+                                      msg);
+                }
+            }
+        }
+    }
+}
+
+/// A constant the value of a local is known to be.
+#[derive(Clone, PartialEq)]
+enum Value<'tcx> {
+    Scalar(ConstVal, Ty<'tcx>),
+    /// The result of a `CheckedBinaryOp` and whether it overflowed; the
+    /// result is unknown if it did.
+    Checked(Option<(ConstVal, Ty<'tcx>)>, bool),
+}
+
+/// The values known for each local at some point of the function.
+type State<'tcx> = Vec<Option<Value<'tcx>>>;
+
+/// The locals whose values are tracked, which are those which are only
+/// ever assigned to as a whole, never borrowed and never written by inline
+/// assembly.
+struct Locals {
+    args: usize,
+    vars: usize,
+    count: usize,
+    untracked: BitVector,
+    /// The length of each local which is an array, tracked or not.
+    array_lens: Vec<Option<usize>>,
+}
+
+impl Locals {
+    fn new(mir: &Mir) -> Locals {
+        let count = mir.arg_decls.len() + mir.var_decls.len() + mir.temp_decls.len();
+        let tys = mir.arg_decls.iter().map(|decl| decl.ty)
+            .chain(mir.var_decls.iter().map(|decl| decl.ty))
+            .chain(mir.temp_decls.iter().map(|decl| decl.ty));
+        let mut locals = Locals {
+            args: mir.arg_decls.len(),
+            vars: mir.var_decls.len(),
+            count: count,
+            untracked: BitVector::new(count),
+            array_lens: tys.map(|ty| {
+                if let ty::TyArray(_, len) = ty.sty { Some(len) } else { None }
+            }).collect(),
+        };
+        locals.visit_mir(mir);
+        locals
+    }
+
+    fn index(&self, lvalue: &Lvalue) -> Option<usize> {
+        match *lvalue {
+            Lvalue::Arg(arg) => Some(arg.index()),
+            Lvalue::Var(var) => Some(self.args + var.index()),
+            Lvalue::Temp(temp) => Some(self.args + self.vars + temp.index()),
+            Lvalue::Static(_) |
+            Lvalue::ReturnPointer |
+            Lvalue::Projection(_) => None,
+        }
+    }
+
+    fn tracked(&self, lvalue: &Lvalue) -> Option<usize> {
+        self.index(lvalue).and_then(|index| {
+            if self.untracked.contains(index) { None } else { Some(index) }
+        })
+    }
+
+    /// Stops tracking the local `lvalue` is a part of.
+    fn untrack(&mut self, lvalue: &Lvalue) {
+        let mut base = lvalue;
+        while let Lvalue::Projection(ref proj) = *base {
+            base = &proj.base;
+        }
+        if let Some(index) = self.index(base) {
+            self.untracked.insert(index);
+        }
+    }
+}
+
+impl<'tcx> Visitor<'tcx> for Locals {
+    fn visit_lvalue(&mut self,
+                    lvalue: &Lvalue<'tcx>,
+                    context: LvalueContext<'tcx>,
+                    location: Location) {
+        let escapes = match context {
+            LvalueContext::Borrow { .. } |
+            LvalueContext::Slice { .. } => true,
+            LvalueContext::Store |
+            LvalueContext::Call |
+            LvalueContext::Drop => {
+                if let Lvalue::Projection(_) = *lvalue { true } else { false }
+            }
+            _ => false,
+        };
+        if escapes {
+            self.untrack(lvalue);
+        }
+        self.super_lvalue(lvalue, context, location);
+    }
+
+    fn visit_rvalue(&mut self, rvalue: &Rvalue<'tcx>, location: Location) {
+        // The outputs are written without an assignment to them.
+        if let Rvalue::InlineAsm { ref outputs, .. } = *rvalue {
+            for output in outputs {
+                self.untrack(output);
+            }
+        }
+        self.super_rvalue(rvalue, location);
+    }
+}
+
+struct Propagator<'a, 'tcx: 'a> {
+    tcx: TyCtxt<'a, 'tcx, 'tcx>,
+    locals: Locals,
+}
+
+impl<'a, 'tcx> Propagator<'a, 'tcx> {
+    fn new(tcx: TyCtxt<'a, 'tcx, 'tcx>, mir: &Mir<'tcx>) -> Self {
+        Propagator {
+            tcx: tcx,
+            locals: Locals::new(mir),
+        }
+    }
+
+    /// Computes the values of the locals at the entry of each block, or
+    /// `None` for blocks which are never reached.
+    fn analyze(&self, mir: &Mir<'tcx>) -> Vec<Option<State<'tcx>>> {
+        let mut entry_states = vec![None; mir.basic_blocks().len()];
+        entry_states[START_BLOCK.index()] = Some(vec![None; self.locals.count]);
+        let mut worklist = vec![START_BLOCK];
+        let mut in_worklist = vec![false; mir.basic_blocks().len()];
+        in_worklist[START_BLOCK.index()] = true;
+
+        while let Some(bb) = worklist.pop() {
+            in_worklist[bb.index()] = false;
+            let mut state = entry_states[bb.index()].clone().unwrap();
+            let data = &mir[bb];
+            for stmt in &data.statements {
+                self.apply_statement(&mut state, stmt);
+            }
+            let terminator = data.terminator();
+            match terminator.kind {
+                TerminatorKind::Call { destination: Some((ref lvalue, _)), .. } |
+                TerminatorKind::DropAndReplace { location: ref lvalue, .. } => {
+                    self.write(&mut state, lvalue, None);
+                }
+                _ => {}
+            }
+
+            for &succ in terminator.successors().iter() {
+                let changed = if entry_states[succ.index()].is_none() {
+                    entry_states[succ.index()] = Some(state.clone());
+                    true
+                } else {
+                    join(entry_states[succ.index()].as_mut().unwrap(), &state)
+                };
+                if changed && !in_worklist[succ.index()] {
+                    in_worklist[succ.index()] = true;
+                    worklist.push(succ);
+                }
+            }
+        }
+        entry_states
+    }
+
+    fn write(&self, state: &mut State<'tcx>, lvalue: &Lvalue<'tcx>, value: Option<Value<'tcx>>) {
+        if let Some(index) = self.locals.tracked(lvalue) {
+            state[index] = value;
+        }
+    }
+
+    fn apply_statement(&self, state: &mut State<'tcx>, stmt: &Statement<'tcx>) {
+        match stmt.kind {
+            StatementKind::Assign(ref lvalue, ref rvalue) => {
+                let value = self.eval_rvalue(state, rvalue);
+                self.write(state, lvalue, value);
+            }
+            StatementKind::StorageLive(ref lvalue) |
+            StatementKind::StorageDead(ref lvalue) => self.write(state, lvalue, None),
+            StatementKind::SetDiscriminant { .. } => {}
+        }
+    }
+
+    fn eval_operand(&self, state: &State<'tcx>, operand: &Operand<'tcx>)
+                    -> Option<(ConstVal, Ty<'tcx>)> {
+        match *operand {
+            Operand::Constant(Constant { literal: Literal::Value { ref value }, ty, .. }) => {
+                match *value {
+                    ConstVal::Bool(_) |
+                    ConstVal::Char(_) |
+                    ConstVal::Integral(_) |
+                    ConstVal::Float(_) => Some((value.clone(), ty)),
+                    _ => None,
+                }
+            }
+            Operand::Constant(_) => None,
+            Operand::Consume(ref lvalue) => self.eval_lvalue(state, lvalue),
+        }
+    }
+
+    fn eval_lvalue(&self, state: &State<'tcx>, lvalue: &Lvalue<'tcx>)
+                   -> Option<(ConstVal, Ty<'tcx>)> {
+        if let Lvalue::Projection(ref proj) = *lvalue {
+            if let ProjectionElem::Field(field, _) = proj.elem {
+                let index = match self.locals.tracked(&proj.base) {
+                    Some(index) => index,
+                    None => return None,
+                };
+                if let Some(Value::Checked(ref result, overflowed)) = state[index] {
+                    return match field.index() {
+                        0 => result.clone(),
+                        _ => Some((ConstVal::Bool(overflowed), self.tcx.types.bool)),
+                    };
+                }
+            }
+            return None;
+        }
+        match self.locals.tracked(lvalue).and_then(|index| state[index].clone()) {
+            Some(Value::Scalar(value, ty)) => Some((value, ty)),
+            _ => None,
+        }
+    }
+
+    fn eval_rvalue(&self, state: &State<'tcx>, rvalue: &Rvalue<'tcx>) -> Option<Value<'tcx>> {
+        match *rvalue {
+            Rvalue::Use(ref operand) => {
+                self.eval_operand(state, operand).map(|(value, ty)| Value::Scalar(value, ty))
+            }
+            Rvalue::UnaryOp(op, ref operand) => {
+                let (value, ty) = match self.eval_operand(state, operand) {
+                    Some(operand) => operand,
+                    None => return None,
+                };
+                eval_const_unary_op(op, &value).ok().map(|value| Value::Scalar(value, ty))
+            }
+            Rvalue::BinaryOp(op, ref lhs, ref rhs) => {
+                let (lhs_value, lhs_ty) = match self.eval_operand(state, lhs) {
+                    Some(lhs) => lhs,
+                    None => return None,
+                };
+                let (rhs_value, rhs_ty) = match self.eval_operand(state, rhs) {
+                    Some(rhs) => rhs,
+                    None => return None,
+                };
+                let ty = op.ty(self.tcx, lhs_ty, rhs_ty);
+                eval_const_binary_op(op, &lhs_value, &rhs_value).ok()
+                    .map(|value| Value::Scalar(value, ty))
+            }
+            Rvalue::CheckedBinaryOp(op, ref lhs, ref rhs) => {
+                let (lhs_value, lhs_ty) = match self.eval_operand(state, lhs) {
+                    Some(lhs) => lhs,
+                    None => return None,
+                };
+                let (rhs_value, rhs_ty) = match self.eval_operand(state, rhs) {
+                    Some(rhs) => rhs,
+                    None => return None,
+                };
+                let ty = op.ty(self.tcx, lhs_ty, rhs_ty);
+                match eval_const_binary_op(op, &lhs_value, &rhs_value) {
+                    Ok(value) => Some(Value::Checked(Some((value, ty)), false)),
+                    Err(ErrKind::Math(ConstMathErr::Overflow(_))) |
+                    Err(ErrKind::Math(ConstMathErr::ShiftNegative)) => {
+                        Some(Value::Checked(None, true))
+                    }
+                    Err(_) => None,
+                }
+            }
+            Rvalue::Len(ref lvalue) => {
+                let len = match self.locals.index(lvalue) {
+                    Some(index) => self.locals.array_lens[index],
+                    None => None,
+                };
+                len.and_then(|len| {
+                    ConstUsize::new(len as u64, self.tcx.sess.target.uint_type).ok()
+                }).map(|len| {
+                    Value::Scalar(ConstVal::Integral(ConstInt::Usize(len)), self.tcx.types.usize)
+                })
+            }
+            _ => None,
+        }
+    }
+
+    /// The message for the panic the `Assert` terminating `bb` is bound to
+    /// cause given `state`, the state at the end of `bb`, if it is bound to.
+    fn assert_failure(&self, mir: &Mir<'tcx>, state: &State<'tcx>, bb: BasicBlock)
+                      -> Option<String> {
+        let (cond, expected, msg) = match mir[bb].terminator().kind {
+            TerminatorKind::Assert { ref cond, expected, ref msg, .. } => (cond, expected, msg),
+            _ => return None,
+        };
+        match self.eval_operand(state, cond) {
+            Some((ConstVal::Bool(value), _)) if value != expected => {}
+            _ => return None,
+        }
+        match *msg {
+            AssertMessage::BoundsCheck { ref len, ref index } => {
+                if self.tcx.sess.features.borrow().const_indexing &&
+                   is_constant(mir, len) && is_constant(mir, index) {
+                    return None;
+                }
+                match (self.eval_operand(state, len), self.eval_operand(state, index)) {
+                    (Some((ConstVal::Integral(len), _)), Some((ConstVal::Integral(index), _))) => {
+                        Some(format!("index out of bounds: the len is {} but the index is {}",
+                                     len.to_u64_unchecked(), index.to_u64_unchecked()))
+                    }
+                    _ => Some("index out of bounds".to_string()),
+                }
+            }
+            AssertMessage::Math(ref err) => {
+                if has_constant_operands(mir, bb, cond, err) {
+                    None
+                } else {
+                    Some(err.description().to_string())
+                }
+            }
+        }
+    }
+
+    /// Replaces the operands of `stmt` known to be constant with those
+    /// constants, and operations on constants by their result.
+    fn propagate_statement(&self, state: &mut State<'tcx>, stmt: &mut Statement<'tcx>) {
+        let span = stmt.source_info.span;
+        if let StatementKind::Assign(_, ref mut rvalue) = stmt.kind {
+            let folded = match self.eval_rvalue(state, rvalue) {
+                Some(Value::Scalar(value, ty)) => {
+                    match *rvalue {
+                        Rvalue::UnaryOp(..) | Rvalue::BinaryOp(..) => Some((value, ty)),
+                        _ => None,
+                    }
+                }
+                _ => None,
+            };
+            match folded {
+                Some(constant) => *rvalue = Rvalue::Use(constant_operand(constant, span)),
+                None => self.propagate_rvalue(state, rvalue, span),
+            }
+        }
+        self.apply_statement(state, stmt);
+    }
+
+    fn propagate_rvalue(&self, state: &State<'tcx>, rvalue: &mut Rvalue<'tcx>, span: Span) {
+        match *rvalue {
+            Rvalue::Use(ref mut operand) |
+            Rvalue::Repeat(ref mut operand, _) |
+            Rvalue::Cast(_, ref mut operand, _) |
+            Rvalue::UnaryOp(_, ref mut operand) => {
+                self.propagate_operand(state, operand, span);
+            }
+            Rvalue::BinaryOp(_, ref mut lhs, ref mut rhs) |
+            Rvalue::CheckedBinaryOp(_, ref mut lhs, ref mut rhs) => {
+                self.propagate_operand(state, lhs, span);
+                self.propagate_operand(state, rhs, span);
+            }
+            Rvalue::Aggregate(_, ref mut operands) => {
+                for operand in operands {
+                    self.propagate_operand(state, operand, span);
+                }
+            }
+            Rvalue::Ref(..) |
+            Rvalue::Len(_) |
+            Rvalue::Box(_) |
+            Rvalue::InlineAsm { .. } => {}
+        }
+    }
+
+    fn propagate_terminator(&self, state: &State<'tcx>, terminator: &mut Terminator<'tcx>) {
+        let span = terminator.source_info.span;
+        match terminator.kind {
+            TerminatorKind::If { ref mut cond, .. } |
+            TerminatorKind::Assert { ref mut cond, .. } => {
+                self.propagate_operand(state, cond, span);
+            }
+            TerminatorKind::Call { ref mut args, .. } => {
+                for arg in args {
+                    self.propagate_operand(state, arg, span);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn propagate_operand(&self, state: &State<'tcx>, operand: &mut Operand<'tcx>, span: Span) {
+        let constant = match *operand {
+            Operand::Consume(ref lvalue) => self.eval_lvalue(state, lvalue),
+            Operand::Constant(_) => None,
+        };
+        if let Some(constant) = constant {
+            *operand = constant_operand(constant, span);
+        }
+    }
+}
+
+/// Merges the values known on another path into `state`, returning
+/// whether any were lost.
+fn join<'tcx>(state: &mut State<'tcx>, other: &State<'tcx>) -> bool {
+    let mut changed = false;
+    for (value, other) in state.iter_mut().zip(other) {
+        if value.is_some() && value != other {
+            *value = None;
+            changed = true;
+        }
+    }
+    changed
+}
+
+/// Whether the operation guarded by the `Assert` with the error `err`
+/// terminating `bb` only has constant operands. Overflow of a checked
+/// operation is tested on its result, right after it; other operations
+/// come after their checks, in the block the `Assert` leads to or after
+/// further checks.
+fn has_constant_operands<'tcx>(mir: &Mir<'tcx>,
+                               bb: BasicBlock,
+                               cond: &Operand<'tcx>,
+                               err: &ConstMathErr)
+                               -> bool {
+    if let Operand::Consume(Lvalue::Projection(ref proj)) = *cond {
+        for stmt in mir[bb].statements.iter().rev() {
+            if let StatementKind::Assign(ref lvalue, ref rvalue) = stmt.kind {
+                if *lvalue == proj.base {
+                    return failing_operands(rvalue, err).map_or(false, |operands| {
+                        operands.into_iter().all(|operand| is_constant(mir, operand))
+                    });
+                }
+            }
+        }
+        return false;
+    }
+
+    let mut block = bb;
+    for _ in 0..mir.basic_blocks().len() {
+        block = match mir[block].terminator().kind {
+            TerminatorKind::Assert { target, .. } => target,
+            _ => return false,
+        };
+        for stmt in &mir[block].statements {
+            if let StatementKind::Assign(_, ref rvalue) = stmt.kind {
+                if let Some(operands) = failing_operands(rvalue, err) {
+                    return operands.into_iter().all(|operand| is_constant(mir, operand));
+                }
+            }
+        }
+    }
+    false
+}
+
+/// The operands of `rvalue`, if it is an operation which can fail with `err`.
+fn failing_operands<'r, 'tcx>(rvalue: &'r Rvalue<'tcx>, err: &ConstMathErr)
+                              -> Option<Vec<&'r Operand<'tcx>>> {
+    let op = match *err {
+        ConstMathErr::Overflow(ref op) => op.clone(),
+        ConstMathErr::DivisionByZero => Op::Div,
+        ConstMathErr::RemainderByZero => Op::Rem,
+        _ => return None,
+    };
+    match *rvalue {
+        Rvalue::BinaryOp(bin_op, ref lhs, ref rhs) |
+        Rvalue::CheckedBinaryOp(bin_op, ref lhs, ref rhs) if math_op(bin_op) == Some(op) => {
+            Some(vec![lhs, rhs])
+        }
+        Rvalue::UnaryOp(UnOp::Neg, ref operand) if op == Op::Neg => Some(vec![operand]),
+        _ => None,
+    }
+}
+
+fn math_op(op: BinOp) -> Option<Op> {
+    match op {
+        BinOp::Add => Some(Op::Add),
+        BinOp::Sub => Some(Op::Sub),
+        BinOp::Mul => Some(Op::Mul),
+        BinOp::Div => Some(Op::Div),
+        BinOp::Rem => Some(Op::Rem),
+        BinOp::Shl => Some(Op::Shl),
+        BinOp::Shr => Some(Op::Shr),
+        _ => None,
+    }
+}
+
+/// Whether `operand` is a constant expression, which the constant checker
+/// evaluates (and reports the failures of) on its own: a literal, or a
+/// temporary assigned once from operations on constant expressions.
+fn is_constant<'tcx>(mir: &Mir<'tcx>, operand: &Operand<'tcx>) -> bool {
+    match *operand {
+        Operand::Constant(_) => true,
+        Operand::Consume(ref lvalue) => is_constant_lvalue(mir, lvalue),
+    }
+}
+
+fn is_constant_lvalue<'tcx>(mir: &Mir<'tcx>, lvalue: &Lvalue<'tcx>) -> bool {
+    let temp = match *lvalue {
+        Lvalue::Temp(temp) => temp,
+        Lvalue::Projection(ref proj) => {
+            return match proj.elem {
+                ProjectionElem::Field(..) => is_constant_lvalue(mir, &proj.base),
+                _ => false,
+            };
+        }
+        _ => return false,
+    };
+
+    let statements = mir.basic_blocks().iter().flat_map(|data| &data.statements);
+    let mut assignments = statements.filter_map(|stmt| {
+        match stmt.kind {
+            StatementKind::Assign(Lvalue::Temp(dest), ref rvalue) if dest == temp => Some(rvalue),
+            _ => None,
+        }
+    });
+    let rvalue = match (assignments.next(), assignments.next()) {
+        (Some(rvalue), None) => rvalue,
+        _ => return false,
+    };
+    match *rvalue {
+        Rvalue::Use(ref operand) |
+        Rvalue::Repeat(ref operand, _) |
+        Rvalue::Cast(_, ref operand, _) |
+        Rvalue::UnaryOp(_, ref operand) => is_constant(mir, operand),
+        Rvalue::BinaryOp(_, ref lhs, ref rhs) |
+        Rvalue::CheckedBinaryOp(_, ref lhs, ref rhs) => {
+            is_constant(mir, lhs) && is_constant(mir, rhs)
+        }
+        Rvalue::Aggregate(_, ref operands) => {
+            operands.iter().all(|operand| is_constant(mir, operand))
+        }
+        Rvalue::Len(ref lvalue) => is_constant_lvalue(mir, lvalue),
+        Rvalue::Ref(..) |
+        Rvalue::Box(_) |
+        Rvalue::InlineAsm { .. } => false,
+    }
+}
+
+fn constant_operand<'tcx>((value, ty): (ConstVal, Ty<'tcx>), span: Span) -> Operand<'tcx> {
+    Operand::Constant(Constant {
+        span: span,
+        ty: ty,
+        literal: Literal::Value { value: value },
+    })
+}
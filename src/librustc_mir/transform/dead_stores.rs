@@ -0,0 +1,204 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! This pass removes assignments to locals which are never read, such as
+//! those left behind by `ConstPropagation`, and then the temporaries
+//! which are no longer used at all. Variables are kept for debuginfo.
+//!
+//! Assignments which would move a value that needs dropping are kept, as
+//! drops were already elaborated assuming the move happens.
+//!
+//! It only runs with `-Z mir-opt-level=2` or higher.
+
+use rustc::mir::repr::*;
+use rustc::mir::transform::{MirPass, MirSource, Pass};
+use rustc::mir::visit::{LvalueContext, MutVisitor, Visitor};
+use rustc::ty::{self, TyCtxt};
+use rustc_data_structures::bitvec::BitVector;
+use rustc_data_structures::indexed_vec::{Idx, IndexVec};
+
+pub struct DeadStoreElimination;
+
+impl Pass for DeadStoreElimination {}
+
+impl<'tcx> MirPass<'tcx> for DeadStoreElimination {
+    fn run_pass<'a>(&mut self, tcx: TyCtxt<'a, 'tcx, 'tcx>,
+                    src: MirSource, mir: &mut Mir<'tcx>) {
+        if tcx.sess.opts.mir_opt_level < 2 { return; }
+        if let MirSource::Fn(_) = src {} else { return; }
+
+        let param_env = ty::ParameterEnvironment::for_item(tcx, src.item_id());
+
+        // Removing a store may leave the locals it read unread in turn.
+        loop {
+            let reads = Reads::new(mir);
+            let removable: Vec<Vec<bool>> = mir.basic_blocks().iter().map(|data| {
+                data.statements.iter().map(|stmt| {
+                    is_dead_store(tcx, &param_env, mir, &reads, stmt)
+                }).collect()
+            }).collect();
+            if !removable.iter().any(|block| block.iter().any(|&dead| dead)) {
+                break;
+            }
+            for (data, removable) in mir.basic_blocks_mut().iter_mut().zip(removable) {
+                let mut removable = removable.into_iter();
+                data.statements.retain(|_| !removable.next().unwrap());
+            }
+        }
+
+        remove_unused_temps(mir);
+    }
+}
+
+/// Whether `stmt` assigns a local that is never read, and has no effect
+/// other than that assignment.
+fn is_dead_store<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
+                           param_env: &ty::ParameterEnvironment<'tcx>,
+                           mir: &Mir<'tcx>,
+                           reads: &Reads,
+                           stmt: &Statement<'tcx>) -> bool {
+    let (lvalue, rvalue) = match stmt.kind {
+        StatementKind::Assign(ref lvalue, ref rvalue) => (lvalue, rvalue),
+        _ => return false,
+    };
+    let read = match *lvalue {
+        Lvalue::Var(var) => reads.vars.contains(var.index()),
+        Lvalue::Temp(temp) => reads.temps.contains(temp.index()),
+        _ => return false,
+    };
+    if read {
+        return false;
+    }
+
+    let needs_drop = |operand: &Operand<'tcx>| {
+        tcx.type_needs_drop_given_env(operand.ty(mir, tcx), param_env)
+    };
+    match *rvalue {
+        Rvalue::Use(ref operand) |
+        Rvalue::Repeat(ref operand, _) |
+        Rvalue::Cast(_, ref operand, _) |
+        Rvalue::UnaryOp(_, ref operand) => !needs_drop(operand),
+        Rvalue::BinaryOp(_, ref lhs, ref rhs) |
+        Rvalue::CheckedBinaryOp(_, ref lhs, ref rhs) => !needs_drop(lhs) && !needs_drop(rhs),
+        Rvalue::Aggregate(_, ref operands) => !operands.iter().any(|operand| needs_drop(operand)),
+        Rvalue::Ref(..) |
+        Rvalue::Len(_) => true,
+        Rvalue::Box(_) |
+        Rvalue::InlineAsm { .. } => false,
+    }
+}
+
+/// The variables and temporaries which are read, or used in any other way
+/// than being assigned to as a whole or having their storage marked.
+struct Reads {
+    vars: BitVector,
+    temps: BitVector,
+}
+
+impl Reads {
+    fn new(mir: &Mir) -> Reads {
+        let mut reads = Reads {
+            vars: BitVector::new(mir.var_decls.len()),
+            temps: BitVector::new(mir.temp_decls.len()),
+        };
+        reads.visit_mir(mir);
+        reads
+    }
+}
+
+impl<'tcx> Visitor<'tcx> for Reads {
+    fn visit_lvalue(&mut self,
+                    lvalue: &Lvalue<'tcx>,
+                    context: LvalueContext<'tcx>,
+                    location: Location) {
+        match context {
+            LvalueContext::Store |
+            LvalueContext::StorageLive |
+            LvalueContext::StorageDead => {}
+            _ => {
+                match *lvalue {
+                    Lvalue::Var(var) => { self.vars.insert(var.index()); }
+                    Lvalue::Temp(temp) => { self.temps.insert(temp.index()); }
+                    _ => {}
+                }
+            }
+        }
+        self.super_lvalue(lvalue, context, location);
+    }
+}
+
+/// Removes the temporaries which are not used at all any more, along with
+/// the statements marking their storage, renumbering the others.
+fn remove_unused_temps(mir: &mut Mir) {
+    let mut used = TempUses { used: BitVector::new(mir.temp_decls.len()) };
+    used.visit_mir(mir);
+    let used = used.used;
+
+    let mut map = IndexVec::with_capacity(mir.temp_decls.len());
+    let mut temp_decls = IndexVec::new();
+    for (temp, decl) in mir.temp_decls.iter_enumerated() {
+        if used.contains(temp.index()) {
+            map.push(Some(temp_decls.push(decl.clone())));
+        } else {
+            map.push(None);
+        }
+    }
+    if temp_decls.len() == mir.temp_decls.len() {
+        return;
+    }
+
+    for data in mir.basic_blocks_mut() {
+        data.statements.retain(|stmt| {
+            match stmt.kind {
+                StatementKind::StorageLive(Lvalue::Temp(temp)) |
+                StatementKind::StorageDead(Lvalue::Temp(temp)) => used.contains(temp.index()),
+                _ => true,
+            }
+        });
+    }
+    mir.temp_decls = temp_decls;
+    TempRenumberer { map: map }.visit_mir(mir);
+}
+
+/// The temporaries used other than by having their storage marked.
+struct TempUses {
+    used: BitVector,
+}
+
+impl<'tcx> Visitor<'tcx> for TempUses {
+    fn visit_lvalue(&mut self,
+                    lvalue: &Lvalue<'tcx>,
+                    context: LvalueContext<'tcx>,
+                    location: Location) {
+        match (lvalue, context) {
+            (_, LvalueContext::StorageLive) |
+            (_, LvalueContext::StorageDead) => {}
+            (&Lvalue::Temp(temp), _) => { self.used.insert(temp.index()); }
+            _ => {}
+        }
+        self.super_lvalue(lvalue, context, location);
+    }
+}
+
+struct TempRenumberer {
+    map: IndexVec<Temp, Option<Temp>>,
+}
+
+impl<'tcx> MutVisitor<'tcx> for TempRenumberer {
+    fn visit_lvalue(&mut self,
+                    lvalue: &mut Lvalue<'tcx>,
+                    context: LvalueContext<'tcx>,
+                    location: Location) {
+        if let Lvalue::Temp(ref mut temp) = *lvalue {
+            *temp = self.map[*temp].unwrap();
+        }
+        self.super_lvalue(lvalue, context, location);
+    }
+}
@@ -0,0 +1,457 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! This pass inlines calls to small and `#[inline]` functions, splicing
+//! the MIR of the callee into the caller at the call site. The MIR of
+//! functions from other crates is taken from their metadata, which only
+//! has it for generic, `#[inline]` and `const` functions.
+//!
+//! It only runs with `-Z mir-opt-level=2` or higher.
+
+use rustc::dep_graph::DepNode;
+use rustc::hir::def_id::DefId;
+use rustc::mir::mir_map::MirMap;
+use rustc::mir::repr::*;
+use rustc::mir::transform::{MirMapPass, MirPassHook, MirSource, Pass};
+use rustc::mir::visit::{LvalueContext, MutVisitor};
+use rustc::ty::{self, ClosureSubsts, Ty, TyCtxt};
+use rustc::ty::subst::{Subst, Substs};
+use rustc::util::nodemap::DefIdMap;
+use rustc_data_structures::indexed_vec::{Idx, IndexVec};
+use syntax::abi::Abi;
+use syntax::ast::NodeId;
+use syntax::attr::{self, InlineAttr};
+use syntax_pos::Span;
+
+use std::mem;
+
+use super::no_landing_pads::NoLandingPads;
+
+const DEFAULT_THRESHOLD: usize = 50;
+const HINT_THRESHOLD: usize = 100;
+
+const INSTR_COST: usize = 5;
+const CALL_PENALTY: usize = 25;
+
+pub struct Inline;
+
+impl Pass for Inline {}
+
+impl<'tcx> MirMapPass<'tcx> for Inline {
+    fn run_pass<'a>(&mut self,
+                    tcx: TyCtxt<'a, 'tcx, 'tcx>,
+                    map: &mut MirMap<'tcx>,
+                    hooks: &mut [Box<for<'s> MirPassHook<'s>>]) {
+        if tcx.sess.opts.mir_opt_level < 2 { return; }
+
+        let mut inliner = Inliner {
+            tcx: tcx,
+            extern_mirs: DefIdMap(),
+        };
+
+        let def_ids = map.map.keys();
+        for def_id in def_ids {
+            let _task = tcx.dep_graph.in_task(DepNode::Mir(def_id));
+            let id = tcx.map.as_local_node_id(def_id).unwrap();
+            let src = MirSource::from_node(tcx, id);
+            // Constants are evaluated before any of this runs.
+            if let MirSource::Fn(_) = src {} else { continue; }
+
+            for hook in &mut *hooks {
+                hook.on_mir_pass(tcx, src, map.map.get(&def_id).unwrap(), self, false);
+            }
+            inliner.inline_calls(map, def_id, id);
+            for hook in &mut *hooks {
+                hook.on_mir_pass(tcx, src, map.map.get(&def_id).unwrap(), self, true);
+            }
+        }
+    }
+}
+
+/// A call of a function whose MIR may be inlined.
+struct CallSite<'tcx> {
+    callee: DefId,
+    substs: &'tcx Substs<'tcx>,
+    bb: BasicBlock,
+    source_info: SourceInfo,
+}
+
+struct Inliner<'a, 'tcx: 'a> {
+    tcx: TyCtxt<'a, 'tcx, 'tcx>,
+    /// The MIR of functions from other crates, as decoded from metadata.
+    extern_mirs: DefIdMap<Option<Mir<'tcx>>>,
+}
+
+impl<'a, 'tcx> Inliner<'a, 'tcx> {
+    fn inline_calls(&mut self, map: &mut MirMap<'tcx>, caller: DefId, caller_id: NodeId) {
+        let param_env = ty::ParameterEnvironment::for_item(self.tcx, caller_id);
+
+        // Each block is paired with the functions its code was inlined
+        // from, which are not inlined into it again so that recursion
+        // terminates.
+        let mut worklist: Vec<(BasicBlock, Vec<DefId>)> = {
+            let caller_mir = map.map.get(&caller).unwrap();
+            caller_mir.basic_blocks().indices().map(|bb| (bb, vec![])).collect()
+        };
+
+        while let Some((bb, history)) = worklist.pop() {
+            let callsite = match self.callsite(map.map.get(&caller).unwrap(), bb) {
+                Some(callsite) => callsite,
+                None => continue,
+            };
+            if callsite.callee == caller || history.contains(&callsite.callee) {
+                continue;
+            }
+            let callee_mir = match self.callee_mir(map, callsite.callee) {
+                Some(callee_mir) => callee_mir,
+                None => continue,
+            };
+            if !self.should_inline(&callsite, &callee_mir) {
+                continue;
+            }
+
+            debug!("inlining {:?} into {:?} at {:?}", callsite.callee, caller, bb);
+
+            let caller_mir = map.map.get_mut(&caller).unwrap();
+            let start = caller_mir.basic_blocks().len();
+            let callee = callsite.callee;
+            self.integrate(caller_mir, callsite, callee_mir, &param_env);
+
+            let mut history = history;
+            history.push(callee);
+            for new_bb in start..caller_mir.basic_blocks().len() {
+                worklist.push((BasicBlock::new(new_bb), history.clone()));
+            }
+        }
+    }
+
+    fn callsite(&self, caller_mir: &Mir<'tcx>, bb: BasicBlock) -> Option<CallSite<'tcx>> {
+        let data = &caller_mir[bb];
+        // Cleanup blocks only run while unwinding, and anything unwinding
+        // out of the callee would have nowhere to go from them.
+        if data.is_cleanup {
+            return None;
+        }
+
+        let terminator = data.terminator();
+        if let TerminatorKind::Call { func: Operand::Constant(ref func), .. } = terminator.kind {
+            if let Literal::Item { def_id, substs } = func.literal {
+                if let ty::TyFnDef(_, _, fty) = func.ty.sty {
+                    // Calls of trait methods can only be resolved to an impl
+                    // once the types involved are known, during translation.
+                    if fty.abi == Abi::Rust && self.tcx.trait_of_item(def_id).is_none() {
+                        return Some(CallSite {
+                            callee: def_id,
+                            substs: substs,
+                            bb: bb,
+                            source_info: terminator.source_info,
+                        });
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    fn callee_mir(&mut self, map: &MirMap<'tcx>, callee: DefId) -> Option<Mir<'tcx>> {
+        if callee.is_local() {
+            return map.map.get(&callee).cloned();
+        }
+        let tcx = self.tcx;
+        self.extern_mirs.entry(callee).or_insert_with(|| {
+            tcx.sess.cstore.maybe_get_item_mir(tcx, callee)
+        }).clone()
+    }
+
+    fn should_inline(&self, callsite: &CallSite<'tcx>, callee_mir: &Mir<'tcx>) -> bool {
+        if callee_mir.arg_decls.iter().any(|arg| arg.spread) {
+            return false;
+        }
+
+        let attrs = self.tcx.get_attrs(callsite.callee);
+        let threshold = match attr::find_inline_attr(None, &attrs) {
+            InlineAttr::Always => return true,
+            InlineAttr::Never => return false,
+            InlineAttr::Hint => HINT_THRESHOLD,
+            InlineAttr::None => DEFAULT_THRESHOLD,
+        };
+
+        let mut cost = 0;
+        for data in callee_mir.basic_blocks() {
+            // Cleanup code is only run when unwinding, so it is not counted.
+            if data.is_cleanup {
+                continue;
+            }
+            for stmt in &data.statements {
+                match stmt.kind {
+                    StatementKind::StorageLive(_) |
+                    StatementKind::StorageDead(_) => {}
+                    _ => cost += INSTR_COST,
+                }
+            }
+            cost += match data.terminator().kind {
+                TerminatorKind::Goto { .. } |
+                TerminatorKind::Return |
+                TerminatorKind::Resume |
+                TerminatorKind::Unreachable => 0,
+                TerminatorKind::Call { .. } |
+                TerminatorKind::Drop { .. } |
+                TerminatorKind::DropAndReplace { .. } |
+                TerminatorKind::Assert { .. } => CALL_PENALTY,
+                _ => INSTR_COST,
+            };
+        }
+
+        debug!("should_inline({:?}): cost={} threshold={}", callsite.callee, cost, threshold);
+        cost <= threshold
+    }
+
+    fn integrate(&self,
+                 caller_mir: &mut Mir<'tcx>,
+                 callsite: CallSite<'tcx>,
+                 mut callee_mir: Mir<'tcx>,
+                 param_env: &ty::ParameterEnvironment<'tcx>) {
+        let source_info = callsite.source_info;
+        let terminator = caller_mir[callsite.bb].terminator.take().unwrap();
+        let (args, destination, cleanup) = match terminator.kind {
+            TerminatorKind::Call { args, destination, cleanup, .. } => {
+                (args, destination, cleanup)
+            }
+            _ => span_bug!(source_info.span, "inlining into non-call {:?}", terminator),
+        };
+
+        {
+            // Spans from other crates can not be encoded in the metadata of
+            // this one, so code inlined from them takes the span of the call.
+            let callsite_span = if callsite.callee.is_local() {
+                None
+            } else {
+                Some(source_info.span)
+            };
+            let mut substitutor = Substitutor {
+                tcx: self.tcx,
+                substs: callsite.substs,
+                param_env: param_env,
+                callsite_span: callsite_span,
+            };
+            substitutor.visit_mir(&mut callee_mir);
+            for promoted in callee_mir.promoted.iter_mut() {
+                substitutor.visit_mir(promoted);
+            }
+        }
+        if self.tcx.sess.no_landing_pads() {
+            NoLandingPads.visit_mir(&mut callee_mir);
+        }
+
+        // The callee writes its return value straight to the destination
+        // of the call, unless that is a projection, which would have to
+        // be evaluated before the call; then it goes to a temporary which
+        // is moved to the destination afterwards.
+        let (destination, return_block) = match destination {
+            Some((destination @ Lvalue::Var(_), target)) |
+            Some((destination @ Lvalue::Temp(_), target)) => (destination, Some(target)),
+            Some((destination, target)) => {
+                let temp = caller_mir.temp_decls.push(TempDecl { ty: callee_mir.return_ty });
+                let copy = Rvalue::Use(Operand::Consume(Lvalue::Temp(temp)));
+                let copy_block = caller_mir.basic_blocks_mut().push(BasicBlockData {
+                    statements: vec![Statement {
+                        source_info: source_info,
+                        kind: StatementKind::Assign(destination, copy),
+                    }],
+                    terminator: Some(Terminator {
+                        source_info: source_info,
+                        kind: TerminatorKind::Goto { target: target },
+                    }),
+                    is_cleanup: false,
+                });
+                (Lvalue::Temp(temp), Some(copy_block))
+            }
+            None => {
+                let temp = caller_mir.temp_decls.push(TempDecl { ty: callee_mir.return_ty });
+                (Lvalue::Temp(temp), None)
+            }
+        };
+
+        // The arguments are moved into fresh temporaries, which the
+        // callee's uses of its arguments are replaced with.
+        let mut statements = Vec::with_capacity(args.len());
+        let arg_decls = &callee_mir.arg_decls;
+        let args: IndexVec<Arg, Temp> = args.into_iter().zip(arg_decls).map(|(arg, decl)| {
+            let temp = caller_mir.temp_decls.push(TempDecl { ty: decl.ty });
+            statements.push(Statement {
+                source_info: source_info,
+                kind: StatementKind::Assign(Lvalue::Temp(temp), Rvalue::Use(arg)),
+            });
+            temp
+        }).collect();
+
+        let mut integrator = Integrator {
+            block_offset: caller_mir.basic_blocks().len(),
+            var_offset: caller_mir.var_decls.len(),
+            temp_offset: caller_mir.temp_decls.len(),
+            scope_offset: caller_mir.visibility_scopes.len(),
+            promoted_offset: caller_mir.promoted.len(),
+            args: args,
+            destination: destination,
+            return_block: return_block,
+            cleanup_block: cleanup,
+            in_cleanup_block: false,
+        };
+        integrator.visit_mir(&mut callee_mir);
+
+        for mut scope in callee_mir.visibility_scopes {
+            if scope.parent_scope.is_none() {
+                scope.parent_scope = Some(source_info.scope);
+            }
+            caller_mir.visibility_scopes.push(scope);
+        }
+        caller_mir.var_decls.extend(callee_mir.var_decls);
+        caller_mir.temp_decls.extend(callee_mir.temp_decls);
+        caller_mir.promoted.extend(callee_mir.promoted);
+        let callee_blocks = mem::replace(callee_mir.basic_blocks_mut(), IndexVec::new());
+        caller_mir.basic_blocks_mut().extend(callee_blocks);
+
+        let block = &mut caller_mir.basic_blocks_mut()[callsite.bb];
+        block.statements.extend(statements);
+        block.terminator = Some(Terminator {
+            source_info: source_info,
+            kind: TerminatorKind::Goto { target: BasicBlock::new(integrator.block_offset) },
+        });
+    }
+}
+
+/// Instantiates the callee's MIR with the substitutions of the call.
+struct Substitutor<'a, 'tcx: 'a> {
+    tcx: TyCtxt<'a, 'tcx, 'tcx>,
+    substs: &'tcx Substs<'tcx>,
+    param_env: &'a ty::ParameterEnvironment<'tcx>,
+    callsite_span: Option<Span>,
+}
+
+impl<'a, 'tcx> MutVisitor<'tcx> for Substitutor<'a, 'tcx> {
+    fn visit_ty(&mut self, ty: &mut Ty<'tcx>) {
+        let substituted = ty.subst(self.tcx, self.substs);
+        *ty = self.tcx.normalize_associated_type_in_env(&substituted, self.param_env);
+    }
+
+    fn visit_substs(&mut self, substs: &mut &'tcx Substs<'tcx>) {
+        let substituted = substs.subst(self.tcx, self.substs);
+        *substs = self.tcx.normalize_associated_type_in_env(&substituted, self.param_env);
+    }
+
+    fn visit_closure_substs(&mut self, substs: &mut ClosureSubsts<'tcx>) {
+        let substituted = substs.subst(self.tcx, self.substs);
+        *substs = self.tcx.normalize_associated_type_in_env(&substituted, self.param_env);
+    }
+
+    fn visit_span(&mut self, span: &mut Span) {
+        if let Some(callsite_span) = self.callsite_span {
+            *span = callsite_span;
+        }
+    }
+}
+
+/// Renumbers the blocks, variables, temporaries, scopes and promoted
+/// constants of the callee's MIR to follow those of the caller, and
+/// connects its entry and exits to the call site.
+struct Integrator<'tcx> {
+    block_offset: usize,
+    var_offset: usize,
+    temp_offset: usize,
+    scope_offset: usize,
+    promoted_offset: usize,
+    args: IndexVec<Arg, Temp>,
+    destination: Lvalue<'tcx>,
+    return_block: Option<BasicBlock>,
+    cleanup_block: Option<BasicBlock>,
+    in_cleanup_block: bool,
+}
+
+impl<'tcx> MutVisitor<'tcx> for Integrator<'tcx> {
+    fn visit_basic_block_data(&mut self, block: BasicBlock, data: &mut BasicBlockData<'tcx>) {
+        self.in_cleanup_block = data.is_cleanup;
+        self.super_basic_block_data(block, data);
+    }
+
+    fn visit_lvalue(&mut self,
+                    lvalue: &mut Lvalue<'tcx>,
+                    context: LvalueContext<'tcx>,
+                    location: Location) {
+        match *lvalue {
+            Lvalue::Var(ref mut var) => *var = Var::new(var.index() + self.var_offset),
+            Lvalue::Temp(ref mut temp) => *temp = Temp::new(temp.index() + self.temp_offset),
+            Lvalue::Arg(arg) => {
+                *lvalue = Lvalue::Temp(self.args[arg]);
+                return;
+            }
+            Lvalue::ReturnPointer => {
+                *lvalue = self.destination.clone();
+                return;
+            }
+            Lvalue::Static(_) |
+            Lvalue::Projection(_) => {}
+        }
+        self.super_lvalue(lvalue, context, location);
+    }
+
+    fn visit_visibility_scope(&mut self, scope: &mut VisibilityScope) {
+        *scope = VisibilityScope::new(scope.index() + self.scope_offset);
+    }
+
+    fn visit_literal(&mut self, literal: &mut Literal<'tcx>, location: Location) {
+        if let Literal::Promoted { ref mut index } = *literal {
+            *index = Promoted::new(index.index() + self.promoted_offset);
+        }
+        self.super_literal(literal, location);
+    }
+
+    fn visit_terminator_kind(&mut self,
+                             block: BasicBlock,
+                             kind: &mut TerminatorKind<'tcx>,
+                             location: Location) {
+        self.super_terminator_kind(block, kind, location);
+
+        match *kind {
+            TerminatorKind::Return => {
+                *kind = match self.return_block {
+                    Some(target) => TerminatorKind::Goto { target: target },
+                    None => TerminatorKind::Unreachable,
+                };
+                return;
+            }
+            TerminatorKind::Resume => {
+                if let Some(target) = self.cleanup_block {
+                    *kind = TerminatorKind::Goto { target: target };
+                }
+                return;
+            }
+            _ => {}
+        }
+
+        for target in kind.successors_mut() {
+            *target = BasicBlock::new(target.index() + self.block_offset);
+        }
+
+        // Unwinding out of the callee continues with the cleanup of the call.
+        if !self.in_cleanup_block {
+            match *kind {
+                TerminatorKind::Call { cleanup: ref mut unwind, .. } |
+                TerminatorKind::Assert { cleanup: ref mut unwind, .. } |
+                TerminatorKind::Drop { ref mut unwind, .. } |
+                TerminatorKind::DropAndReplace { ref mut unwind, .. } => {
+                    if unwind.is_none() {
+                        *unwind = self.cleanup_block;
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+}
@@ -18,3 +18,6 @@ pub mod promote_consts;
 pub mod qualify_consts;
 pub mod dump_mir;
 pub mod deaggregator;
+pub mod inline;
+pub mod const_prop;
+pub mod dead_stores;
@@ -290,11 +290,14 @@ impl<'a, 'tcx> Qualifier<'a, 'tcx, 'tcx> {
     fn assign(&mut self, dest: &Lvalue<'tcx>, location: Location) {
         let qualif = self.qualif;
         let span = self.span;
+        let mode = self.mode;
         let store = |slot: &mut Option<Qualif>| {
-            if slot.is_some() {
-                span_bug!(span, "multiple assignments to {:?}", dest);
+            match *slot {
+                // The branches of a `const fn` can each assign the same temp.
+                Some(previous) if mode == Mode::ConstFn => *slot = Some(previous | qualif),
+                Some(_) => span_bug!(span, "multiple assignments to {:?}", dest),
+                None => *slot = Some(qualif),
             }
-            *slot = Some(qualif);
         };
 
         // Only handle promotable temps in non-const functions.
@@ -322,6 +325,13 @@ impl<'a, 'tcx> Qualifier<'a, 'tcx, 'tcx> {
                 // already for the Box allocation Rvalue.
             }
 
+            // `const fn`s are run by the MIR interpreter,
+            // which supports assigning to locals and their fields.
+            _ if self.mode == Mode::ConstFn => {
+                // Catch more errors in the destination.
+                self.visit_lvalue(dest, LvalueContext::Store, location);
+            }
+
             // This must be an explicit assignment.
             _ => {
                 // Catch more errors in the destination.
@@ -516,7 +526,13 @@ impl<'a, 'tcx> Visitor<'tcx> for Qualifier<'a, 'tcx, 'tcx> {
                         ProjectionElem::ConstantIndex {..} |
                         ProjectionElem::Subslice {..} |
                         ProjectionElem::Downcast(..) => {
-                            this.not_const()
+                            // These come from `match`, which is only
+                            // supported in `const fn`s.
+                            if this.mode == Mode::ConstFn {
+                                this.add(Qualif::NOT_CONST);
+                            } else {
+                                this.not_const();
+                            }
                         }
                     }
                 });
@@ -740,9 +756,12 @@ impl<'a, 'tcx> Visitor<'tcx> for Qualifier<'a, 'tcx, 'tcx> {
             let fn_ty = func.ty(self.mir, self.tcx);
             let (is_shuffle, is_const_fn) = match fn_ty.sty {
                 ty::TyFnDef(def_id, _, f) => {
+                    // A `const fn` may panic, which stops its evaluation.
+                    let is_panic = self.mode == Mode::ConstFn &&
+                                   Some(def_id) == self.tcx.lang_items.panic_fn();
                     (f.abi == Abi::PlatformIntrinsic &&
                      self.tcx.item_name(def_id).as_str().starts_with("simd_shuffle"),
-                     is_const_fn(self.tcx, def_id))
+                     is_const_fn(self.tcx, def_id) || is_panic)
                 }
                 _ => (false, false)
             };
@@ -850,8 +869,14 @@ impl<'a, 'tcx> Visitor<'tcx> for Qualifier<'a, 'tcx, 'tcx> {
                     return;
                 }
 
-                // Avoid a generic error for other uses of arguments.
-                if self.qualif.intersects(Qualif::FN_ARGUMENT) {
+                // Other locals may be initialized from arguments,
+                // but arguments can't be destructured or borrowed.
+                let binds_arg = match *rvalue {
+                    Rvalue::Use(Operand::Consume(ref lvalue)) |
+                    Rvalue::Ref(_, _, ref lvalue) => is_arg_based(lvalue),
+                    _ => false
+                };
+                if binds_arg && self.qualif.intersects(Qualif::FN_ARGUMENT) {
                     let decl = &self.mir.var_decls[index];
                     span_err!(self.tcx.sess, decl.source_info.span, E0022,
                               "arguments of constant functions can only \
@@ -890,6 +915,15 @@ impl<'a, 'tcx> Visitor<'tcx> for Qualifier<'a, 'tcx, 'tcx> {
     }
 }
 
+/// Whether `lvalue` is an argument, or a part of one.
+fn is_arg_based(lvalue: &Lvalue) -> bool {
+    match *lvalue {
+        Lvalue::Arg(_) => true,
+        Lvalue::Projection(ref proj) => is_arg_based(&proj.base),
+        _ => false
+    }
+}
+
 fn qualify_const_item_cached<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
                                        qualif_map: &mut DefIdMap<Qualif>,
                                        mir_map: Option<&MirMap<'tcx>>,
@@ -984,13 +1018,10 @@ impl<'tcx> MirMapPass<'tcx> for QualifyAndPromoteConstants {
                 let (temps, candidates) = {
                     let mut qualifier = Qualifier::new(tcx, param_env, &mut qualif_map,
                                                        None, def_id, mir, mode);
-                    if mode == Mode::ConstFn {
-                        // Enforce a constant-like CFG for `const fn`.
-                        qualifier.qualify_const();
-                    } else {
-                        while let Some((bb, data)) = qualifier.rpo.next() {
-                            qualifier.visit_basic_block_data(bb, data);
-                        }
+                    // `const fn` bodies can have any control flow, as
+                    // they are evaluated by the MIR interpreter.
+                    while let Some((bb, data)) = qualifier.rpo.next() {
+                        qualifier.visit_basic_block_data(bb, data);
                     }
 
                     (qualifier.temp_promotion_state,
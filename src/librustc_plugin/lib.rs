@@ -76,6 +76,7 @@ extern crate rustc_errors as errors;
 pub use self::registry::Registry;
 
 pub mod diagnostics;
+pub mod lint_api;
 pub mod registry;
 pub mod load;
 pub mod build;
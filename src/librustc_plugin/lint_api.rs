@@ -0,0 +1,479 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! A versioned lint API for plugins, registered with
+//! `Registry::register_lint_visitor`.
+//!
+//! Lint passes registered with `Registry::register_late_lint_pass` see the
+//! compiler's own HIR and type representations, which change all the time.
+//! Lints written against this module instead see a simplified view of the
+//! crate, built fresh for them: items and expressions with their paths
+//! resolved and their types rendered as strings, and opaque spans to report
+//! lints at. None of the compiler's internal types appear in it.
+//!
+//! Changes to this module are only ever additions, each of which bumps
+//! `API_VERSION`. A plugin passes the version it was written against when
+//! registering, and is refused by compilers providing an older version.
+//!
+//! ```ignore
+//! #![feature(plugin_registrar, rustc_private)]
+//!
+//! extern crate rustc_plugin;
+//!
+//! use rustc_plugin::Registry;
+//! use rustc_plugin::lint_api::{self, Context, Expr, ExprKind, Level, LintDecl, LintVisitor};
+//!
+//! static OPTION_UNWRAP: LintDecl = LintDecl {
+//!     name: "option_unwrap",
+//!     default_level: Level::Warn,
+//!     description: "calls to `unwrap` on an `Option`",
+//! };
+//!
+//! struct OptionUnwrap;
+//!
+//! impl LintVisitor for OptionUnwrap {
+//!     fn lints(&self) -> Vec<&'static LintDecl> {
+//!         vec![&OPTION_UNWRAP]
+//!     }
+//!
+//!     fn check_expr(&mut self, cx: &Context, expr: &Expr) {
+//!         if let ExprKind::MethodCall { ref name, receiver_ty: Some(ref ty), .. } = expr.kind {
+//!             if name == "unwrap" && ty.starts_with("std::option::Option<") {
+//!                 cx.span_lint(&OPTION_UNWRAP, expr.span, "called `unwrap` on an `Option`");
+//!             }
+//!         }
+//!     }
+//! }
+//!
+//! #[plugin_registrar]
+//! pub fn plugin_registrar(reg: &mut Registry) {
+//!     reg.register_lint_visitor(lint_api::API_VERSION, Box::new(OptionUnwrap));
+//! }
+//! ```
+
+use rustc::hir;
+use rustc::hir::def::Def;
+use rustc::lint::{self as rustc_lint, LateContext, LateLintPass, LintArray, LintContext};
+use rustc::lint::LintPass;
+use rustc::ty::{self, Ty};
+use rustc::util::nodemap::{FnvHashMap, FnvHashSet};
+
+use syntax::ast;
+use syntax::attr::AttrMetaMethods;
+use syntax_pos::{self, NO_EXPANSION};
+
+use std::cell::RefCell;
+use std::sync::{Mutex, Once, ONCE_INIT};
+
+/// The version of this API provided by the compiler.
+pub const API_VERSION: u32 = 1;
+
+/// The default level of a lint.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Level {
+    Allow,
+    Warn,
+    Deny,
+}
+
+/// A lint provided by a plugin, declared as a `static` so that it can be
+/// referred to when reporting it.
+#[derive(Debug)]
+pub struct LintDecl {
+    /// The name of the lint, as used in lint attributes and flags, e.g.
+    /// `box_new`.
+    pub name: &'static str,
+    pub default_level: Level,
+    pub description: &'static str,
+}
+
+/// A location in the source code.
+///
+/// Spans can't be looked into directly, `Context` has methods telling where
+/// they are.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Span(syntax_pos::Span);
+
+impl Span {
+    /// Is this code the result of a macro expansion? Lints often want to
+    /// skip such code, as it can't be changed where it's found.
+    pub fn from_expansion(&self) -> bool {
+        self.0.expn_id != NO_EXPANSION
+    }
+}
+
+/// Where a span starts, as returned by `Context::location`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Location {
+    pub file_name: String,
+    /// 1-based.
+    pub line: usize,
+    /// 0-based, in characters.
+    pub column: usize,
+}
+
+/// An item, including methods in traits and impls.
+#[derive(Clone, Debug)]
+pub struct Item {
+    pub name: String,
+    /// The absolute path of the item, e.g. `my_crate::module::Type`.
+    pub path: String,
+    pub kind: ItemKind,
+    pub is_public: bool,
+    /// The names of the attributes of the item, e.g. `inline` for
+    /// `#[inline(always)]`.
+    pub attributes: Vec<String>,
+    pub span: Span,
+}
+
+impl Item {
+    pub fn has_attribute(&self, name: &str) -> bool {
+        self.attributes.iter().any(|attr| attr == name)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum ItemKind {
+    /// A function, or a method if `has_self` is set. Methods are also
+    /// reported for trait methods without a body.
+    Fn { inputs: Vec<String>, output: String, has_self: bool },
+    Struct,
+    Enum,
+    Trait,
+    /// An impl, of `trait_path` if it's a trait impl.
+    Impl { self_ty: String, trait_path: Option<String> },
+    Const { ty: String },
+    Static { ty: String, mutable: bool },
+    TypeAlias,
+    Mod,
+    /// Any other kind of item. More kinds may be singled out by later
+    /// versions of the API.
+    Other,
+    /// Not an actual kind of item, but makes matches on `ItemKind` keep a
+    /// wildcard arm, so that later versions of the API can add kinds.
+    #[doc(hidden)]
+    __Nonexhaustive,
+}
+
+/// An expression. Every expression of the crate is visited, so the
+/// subexpressions of an expression are visited by themselves.
+#[derive(Clone, Debug)]
+pub struct Expr {
+    pub kind: ExprKind,
+    /// The type of the expression, e.g. `std::vec::Vec<u32>`. This is
+    /// missing for the few expressions which aren't type checked, like array
+    /// lengths in types.
+    pub ty: Option<String>,
+    pub span: Span,
+}
+
+#[derive(Clone, Debug)]
+pub enum ExprKind {
+    /// A path, with the absolute path of what it refers to. Local variables
+    /// and the like have no such path.
+    Path { def_path: Option<String> },
+    /// A call, with the absolute path of the function called if the callee
+    /// is a path rather than an arbitrary expression.
+    Call { callee_path: Option<String>, arg_types: Vec<Option<String>> },
+    /// A method call, with the absolute path of the method. Methods called
+    /// through a trait are those of the trait, not of the impl.
+    MethodCall { name: String, method_path: Option<String>, receiver_ty: Option<String> },
+    /// A binary operation, e.g. `+` or `==`.
+    Binary { op: &'static str },
+    /// A unary operation: `*`, `!` or `-`.
+    Unary { op: &'static str },
+    Literal,
+    /// A cast, from the type `from` to the type of the expression.
+    Cast { from: Option<String> },
+    /// Any other kind of expression. More kinds may be singled out by later
+    /// versions of the API.
+    Other,
+    /// Not an actual kind of expression, but makes matches on `ExprKind`
+    /// keep a wildcard arm, so that later versions of the API can add kinds.
+    #[doc(hidden)]
+    __Nonexhaustive,
+}
+
+/// A plugin lint, checking the items and expressions of the crate.
+pub trait LintVisitor {
+    /// The lints which may be reported by this visitor.
+    fn lints(&self) -> Vec<&'static LintDecl>;
+
+    fn check_item(&mut self, _: &Context, _: &Item) { }
+    fn check_expr(&mut self, _: &Context, _: &Expr) { }
+    /// Called once the whole crate has been visited.
+    fn check_crate_post(&mut self, _: &Context) { }
+}
+
+/// What a lint visitor is given to find out more about the crate and to
+/// report lints.
+pub struct Context<'a, 'tcx: 'a> {
+    cx: &'a LateContext<'a, 'tcx>,
+    lints: &'a PluginLints,
+}
+
+impl<'a, 'tcx> Context<'a, 'tcx> {
+    /// The name of the crate being compiled.
+    pub fn crate_name(&self) -> String {
+        self.cx.tcx.crate_name.to_string()
+    }
+
+    pub fn location(&self, span: Span) -> Location {
+        let loc = self.cx.sess().codemap().lookup_char_pos(span.0.lo);
+        Location {
+            file_name: loc.file.name.clone(),
+            line: loc.line,
+            column: loc.col.0,
+        }
+    }
+
+    /// The source code covered by `span`, if it's available.
+    pub fn snippet(&self, span: Span) -> Option<String> {
+        self.cx.sess().codemap().span_to_snippet(span.0).ok()
+    }
+
+    pub fn span_lint(&self, lint: &LintDecl, span: Span, msg: &str) {
+        if let Some(lint) = self.lint(lint) {
+            self.cx.span_lint(lint, span.0, msg);
+        }
+    }
+
+    pub fn span_lint_note(&self, lint: &LintDecl, span: Span, msg: &str, note: &str) {
+        if let Some(lint) = self.lint(lint) {
+            self.cx.struct_span_lint(lint, span.0, msg)
+                   .note(note)
+                   .emit();
+        }
+    }
+
+    pub fn span_lint_help(&self, lint: &LintDecl, span: Span, msg: &str, help: &str) {
+        if let Some(lint) = self.lint(lint) {
+            self.cx.struct_span_lint(lint, span.0, msg)
+                   .help(help)
+                   .emit();
+        }
+    }
+
+    /// The lint registered for `decl`, if the plugin declared it. Reporting
+    /// a lint the plugin didn't declare is an error, reported once per lint.
+    fn lint(&self, decl: &LintDecl) -> Option<&'static rustc_lint::Lint> {
+        let lint = self.lints.lints.iter().find(|&&(name, _)| name == decl.name);
+        if lint.is_none() && self.lints.undeclared.borrow_mut().insert(decl.name) {
+            self.cx.sess().err(&format!("plugin `{}` reported the lint `{}`, which is not \
+                                         part of its `LintVisitor::lints`",
+                                        self.lints.plugin, decl.name));
+        }
+        lint.map(|&(_, lint)| lint)
+    }
+
+    fn ty_string(&self, ty: Ty<'tcx>) -> String {
+        ty.to_string()
+    }
+
+    fn node_ty(&self, id: ast::NodeId) -> Option<String> {
+        self.cx.tcx.tables.borrow().node_types.get(&id).map(|&ty| self.ty_string(ty))
+    }
+
+    fn def_path(&self, def: Def) -> Option<String> {
+        match def {
+            Def::Local(..) | Def::Upvar(..) | Def::Label(..) | Def::PrimTy(..) |
+            Def::TyParam(..) | Def::SelfTy(..) | Def::Err => None,
+            def => Some(self.cx.tcx.item_path_str(def.def_id())),
+        }
+    }
+
+    fn fn_kind(&self, id: ast::NodeId, has_self: bool) -> ItemKind {
+        let def_id = self.cx.tcx.map.local_def_id(id);
+        let sig = self.cx.tcx.lookup_item_type(def_id).ty.fn_sig();
+        ItemKind::Fn {
+            inputs: sig.skip_binder().inputs.iter().map(|&ty| self.ty_string(ty)).collect(),
+            output: self.ty_string(sig.skip_binder().output),
+            has_self: has_self,
+        }
+    }
+
+    fn item(&self, id: ast::NodeId, name: ast::Name, attrs: &[ast::Attribute],
+            is_public: bool, span: syntax_pos::Span, kind: ItemKind) -> Item {
+        Item {
+            name: name.to_string(),
+            path: self.cx.tcx.item_path_str(self.cx.tcx.map.local_def_id(id)),
+            kind: kind,
+            is_public: is_public,
+            attributes: attrs.iter().map(|attr| attr.name().to_string()).collect(),
+            span: Span(span),
+        }
+    }
+
+    fn convert_item(&self, it: &hir::Item) -> Item {
+        let def_id = self.cx.tcx.map.local_def_id(it.id);
+        let kind = match it.node {
+            hir::ItemFn(..) => self.fn_kind(it.id, false),
+            hir::ItemStruct(..) => ItemKind::Struct,
+            hir::ItemEnum(..) => ItemKind::Enum,
+            hir::ItemTrait(..) => ItemKind::Trait,
+            hir::ItemImpl(..) => ItemKind::Impl {
+                self_ty: self.ty_string(self.cx.tcx.lookup_item_type(def_id).ty),
+                trait_path: self.cx.tcx.impl_trait_ref(def_id)
+                                       .map(|t| self.cx.tcx.item_path_str(t.def_id)),
+            },
+            hir::ItemConst(..) => ItemKind::Const {
+                ty: self.ty_string(self.cx.tcx.lookup_item_type(def_id).ty),
+            },
+            hir::ItemStatic(_, mutbl, _) => ItemKind::Static {
+                ty: self.ty_string(self.cx.tcx.lookup_item_type(def_id).ty),
+                mutable: mutbl == hir::MutMutable,
+            },
+            hir::ItemTy(..) => ItemKind::TypeAlias,
+            hir::ItemMod(..) => ItemKind::Mod,
+            _ => ItemKind::Other,
+        };
+        self.item(it.id, it.name, &it.attrs, it.vis == hir::Public, it.span, kind)
+    }
+
+    fn convert_trait_item(&self, ti: &hir::TraitItem) -> Item {
+        let kind = match ti.node {
+            hir::MethodTraitItem(ref sig, _) => self.fn_kind(ti.id, sig.decl.has_self()),
+            _ => ItemKind::Other,
+        };
+        // Trait items are as public as the trait.
+        self.item(ti.id, ti.name, &ti.attrs, true, ti.span, kind)
+    }
+
+    fn convert_impl_item(&self, ii: &hir::ImplItem) -> Item {
+        let kind = match ii.node {
+            hir::ImplItemKind::Method(ref sig, _) => self.fn_kind(ii.id, sig.decl.has_self()),
+            _ => ItemKind::Other,
+        };
+        self.item(ii.id, ii.name, &ii.attrs, ii.vis == hir::Public, ii.span, kind)
+    }
+
+    fn convert_expr(&self, e: &hir::Expr) -> Expr {
+        let tcx = self.cx.tcx;
+        let kind = match e.node {
+            hir::ExprPath(..) => ExprKind::Path {
+                def_path: tcx.expect_def_or_none(e.id).and_then(|def| self.def_path(def)),
+            },
+            hir::ExprCall(ref callee, ref args) => ExprKind::Call {
+                callee_path: tcx.expect_def_or_none(callee.id)
+                                .and_then(|def| self.def_path(def)),
+                arg_types: args.iter().map(|arg| self.node_ty(arg.id)).collect(),
+            },
+            hir::ExprMethodCall(ref name, _, ref args) => {
+                let method_call = ty::MethodCall::expr(e.id);
+                let method = tcx.tables.borrow().method_map.get(&method_call).map(|m| m.def_id);
+                ExprKind::MethodCall {
+                    name: name.node.to_string(),
+                    method_path: method.map(|def_id| tcx.item_path_str(def_id)),
+                    receiver_ty: self.node_ty(args[0].id),
+                }
+            }
+            hir::ExprBinary(op, _, _) => ExprKind::Binary { op: op.node.as_str() },
+            hir::ExprUnary(op, _) => ExprKind::Unary { op: op.as_str() },
+            hir::ExprLit(..) => ExprKind::Literal,
+            hir::ExprCast(ref inner, _) => ExprKind::Cast { from: self.node_ty(inner.id) },
+            _ => ExprKind::Other,
+        };
+        Expr {
+            kind: kind,
+            ty: self.node_ty(e.id),
+            span: Span(e.span),
+        }
+    }
+}
+
+/// The lints of a plugin, as registered with the lint store.
+struct PluginLints {
+    /// The name of the plugin.
+    plugin: String,
+    lints: Vec<(&'static str, &'static rustc_lint::Lint)>,
+    /// The lints reported by the plugin without being declared.
+    undeclared: RefCell<FnvHashSet<&'static str>>,
+}
+
+/// Runs a `LintVisitor` as one of the compiler's lint passes.
+pub struct LintVisitorPass {
+    visitor: Box<LintVisitor>,
+    lints: PluginLints,
+}
+
+impl LintVisitorPass {
+    pub fn new(plugin: String, visitor: Box<LintVisitor>) -> LintVisitorPass {
+        let lints = visitor.lints().into_iter().map(|decl| (decl.name, intern_lint(decl)));
+        LintVisitorPass {
+            visitor: visitor,
+            lints: PluginLints {
+                plugin: plugin,
+                lints: lints.collect(),
+                undeclared: RefCell::new(FnvHashSet()),
+            },
+        }
+    }
+}
+
+/// Returns the lint to register for a lint declared by a plugin.
+///
+/// The lint store wants lints to live forever, like the ones declared with
+/// `declare_lint!`. As plugins are never unloaded, each declaration gets a
+/// single lint, shared by all the sessions of the process loading the plugin.
+fn intern_lint(decl: &'static LintDecl) -> &'static rustc_lint::Lint {
+    static INIT: Once = ONCE_INIT;
+    static mut LINTS: *mut Mutex<FnvHashMap<usize, &'static rustc_lint::Lint>> = 0 as *mut _;
+    unsafe {
+        INIT.call_once(|| {
+            LINTS = Box::into_raw(Box::new(Mutex::new(FnvHashMap())));
+        });
+        let mut lints = (*LINTS).lock().unwrap();
+        *lints.entry(decl as *const LintDecl as usize).or_insert_with(|| {
+            let lint = rustc_lint::Lint {
+                name: decl.name,
+                default_level: match decl.default_level {
+                    Level::Allow => rustc_lint::Allow,
+                    Level::Warn => rustc_lint::Warn,
+                    Level::Deny => rustc_lint::Deny,
+                },
+                desc: decl.description,
+            };
+            &*Box::into_raw(Box::new(lint))
+        })
+    }
+}
+
+impl LintPass for LintVisitorPass {
+    fn get_lints(&self) -> LintArray {
+        self.lints.lints.iter().map(|&(_, lint)| lint).collect()
+    }
+}
+
+impl LateLintPass for LintVisitorPass {
+    fn check_item(&mut self, cx: &LateContext, it: &hir::Item) {
+        let cx = Context { cx: cx, lints: &self.lints };
+        self.visitor.check_item(&cx, &cx.convert_item(it));
+    }
+
+    fn check_trait_item(&mut self, cx: &LateContext, ti: &hir::TraitItem) {
+        let cx = Context { cx: cx, lints: &self.lints };
+        self.visitor.check_item(&cx, &cx.convert_trait_item(ti));
+    }
+
+    fn check_impl_item(&mut self, cx: &LateContext, ii: &hir::ImplItem) {
+        let cx = Context { cx: cx, lints: &self.lints };
+        self.visitor.check_item(&cx, &cx.convert_impl_item(ii));
+    }
+
+    fn check_expr(&mut self, cx: &LateContext, e: &hir::Expr) {
+        let cx = Context { cx: cx, lints: &self.lints };
+        self.visitor.check_expr(&cx, &cx.convert_expr(e));
+    }
+
+    fn check_crate_post(&mut self, cx: &LateContext, _: &hir::Crate) {
+        let cx = Context { cx: cx, lints: &self.lints };
+        self.visitor.check_crate_post(&cx);
+    }
+}
@@ -30,6 +30,7 @@ pub type PluginRegistrarFun =
 
 pub struct PluginRegistrar {
     pub fun: PluginRegistrarFun,
+    pub name: String,
     pub args: Vec<P<ast::MetaItem>>,
 }
 
@@ -110,6 +111,7 @@ impl<'a> PluginLoader<'a> {
             let fun = self.dylink_registrar(span, lib, symbol);
             self.plugins.push(PluginRegistrar {
                 fun: fun,
+                name: name.to_string(),
                 args: args,
             });
         }
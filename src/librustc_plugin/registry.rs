@@ -15,6 +15,8 @@ use rustc::session::Session;
 
 use rustc::mir::transform::MirMapPass;
 
+use lint_api::{self, LintVisitor, LintVisitorPass};
+
 use syntax::ext::base::{SyntaxExtension, NamedSyntaxExtension, NormalTT};
 use syntax::ext::base::{IdentTT, MultiModifier, MultiDecorator};
 use syntax::ext::base::{MacroExpanderFn, MacroRulesTT};
@@ -43,6 +45,9 @@ pub struct Registry<'a> {
     #[doc(hidden)]
     pub args_hidden: Option<Vec<P<ast::MetaItem>>>,
 
+    #[doc(hidden)]
+    pub plugin_name_hidden: Option<String>,
+
     #[doc(hidden)]
     pub krate_span: Span,
 
@@ -74,6 +79,7 @@ impl<'a> Registry<'a> {
         Registry {
             sess: sess,
             args_hidden: None,
+            plugin_name_hidden: None,
             krate_span: krate.span,
             syntax_exts: vec!(),
             early_lint_passes: vec!(),
@@ -138,6 +144,24 @@ impl<'a> Registry<'a> {
     pub fn register_late_lint_pass(&mut self, lint_pass: LateLintPassObject) {
         self.late_lint_passes.push(lint_pass);
     }
+
+    /// Register a lint visitor, written against the versioned lint API of
+    /// `rustc_plugin::lint_api` rather than the compiler's internals.
+    ///
+    /// `api_version` is the `lint_api::API_VERSION` the plugin was written
+    /// against. Visitors needing a newer version of the API than the one
+    /// provided by this compiler are refused.
+    pub fn register_lint_visitor(&mut self, api_version: u32, visitor: Box<LintVisitor>) {
+        if api_version > lint_api::API_VERSION {
+            self.sess.span_err(self.krate_span,
+                               &format!("plugin requires version {} of the lint API, but this \
+                                         compiler only provides version {}",
+                                        api_version, lint_api::API_VERSION));
+            return;
+        }
+        let plugin = self.plugin_name_hidden.clone().unwrap_or(String::new());
+        self.late_lint_passes.push(Box::new(LintVisitorPass::new(plugin, visitor)));
+    }
     /// Register a lint group.
     pub fn register_lint_group(&mut self, name: &'static str, to: Vec<&'static Lint>) {
         self.lint_groups.insert(name, to.into_iter().map(|x| LintId::of(x)).collect());
@@ -18,7 +18,11 @@
 //
 // Unused trait imports can't be checked until the method resolution. We save
 // candidates here, and do the acutal check in librustc_typeck/check_unused.rs.
+//
+// The crates passed with `--extern` that nothing refers to are found here as
+// well, and reported by the `unused_crate_dependencies` lint in librustc_lint.
 
+use std::collections::HashSet;
 use std::ops::{Deref, DerefMut};
 
 use Resolver;
@@ -26,12 +30,15 @@ use Namespace::{TypeNS, ValueNS};
 
 use rustc::lint;
 use syntax::ast::{self, ViewPathGlob, ViewPathList, ViewPathSimple};
+use syntax::attr::{self, AttrMetaMethods};
 use syntax::visit::{self, Visitor};
 use syntax_pos::{Span, DUMMY_SP};
 
 
 struct UnusedImportCheckVisitor<'a, 'b: 'a> {
     resolver: &'a mut Resolver<'b>,
+    // The names of the crates loaded by `extern crate` items which are used.
+    used_extern_names: HashSet<String>,
 }
 
 // Deref and DerefMut impls allow treating UnusedImportCheckVisitor as Resolver.
@@ -69,11 +76,33 @@ impl<'a, 'b> UnusedImportCheckVisitor<'a, 'b> {
             self.maybe_unused_trait_imports.remove(&id);
         }
     }
+
+    // Records the name of the crate loaded by an `extern crate` item if that
+    // crate is used. Public items are re-exports, so they always count as
+    // used, and so do items importing macros, as the uses of macros can't be
+    // told apart once they're expanded.
+    fn record_extern_crate(&mut self, item: &ast::Item, orig_name: Option<ast::Name>) {
+        let used = item.vis == ast::Visibility::Public ||
+                   attr::contains_name(&item.attrs, "macro_use") ||
+                   attr::contains_name(&item.attrs, "macro_reexport") ||
+                   match self.session.cstore.extern_mod_stmt_cnum(item.id) {
+                       Some(crate_num) => self.used_crates.contains(&crate_num),
+                       None => false,
+                   };
+        if used {
+            let name = orig_name.unwrap_or(item.ident.name);
+            self.used_extern_names.insert(name.as_str().to_string());
+        }
+    }
 }
 
 impl<'a, 'b> Visitor for UnusedImportCheckVisitor<'a, 'b> {
     fn visit_item(&mut self, item: &ast::Item) {
         visit::walk_item(self, item);
+        if let ast::ItemKind::ExternCrate(orig_name) = item.node {
+            self.record_extern_crate(item, orig_name);
+        }
+
         // Ignore is_public import statements because there's no way to be sure
         // whether they're used or not. Also ignore imports with a dummy span
         // because this means that they were generated in some fashion by the
@@ -115,6 +144,25 @@ impl<'a, 'b> Visitor for UnusedImportCheckVisitor<'a, 'b> {
 }
 
 pub fn check_crate(resolver: &mut Resolver, krate: &ast::Crate) {
-    let mut visitor = UnusedImportCheckVisitor { resolver: resolver };
+    let mut visitor = UnusedImportCheckVisitor {
+        resolver: resolver,
+        used_extern_names: HashSet::new(),
+    };
     visit::walk_crate(&mut visitor, krate);
+
+    // Plugins are loaded by name rather than through `extern crate`.
+    let mut used_extern_names = visitor.used_extern_names;
+    for attr in krate.attrs.iter().filter(|attr| attr.check_name("plugin")) {
+        for plugin in attr.meta_item_list().unwrap_or(&[]) {
+            used_extern_names.insert(plugin.name().to_string());
+        }
+    }
+
+    let session = visitor.resolver.session;
+    *session.unused_extern_names.borrow_mut() =
+        session.opts.externs.iter()
+                            .map(|(name, _)| name)
+                            .filter(|name| !used_extern_names.contains(&name[..]))
+                            .cloned()
+                            .collect();
 }
@@ -47,13 +47,14 @@ use super::Disr;
 use std;
 use std::rc::Rc;
 
-use llvm::{ValueRef, True, IntEQ, IntNE};
+use llvm::{ValueRef, True, IntEQ, IntNE, IntULE, TypeKind};
+use rustc::traits::Reveal;
+use rustc::ty::layout;
 use rustc::ty::subst::Substs;
 use rustc::ty::{self, Ty, TyCtxt};
 use syntax::ast;
 use syntax::attr;
 use syntax::attr::IntType;
-use abi::FAT_PTR_ADDR;
 use build::*;
 use common::*;
 use debuginfo::DebugLoc;
@@ -106,6 +107,21 @@ pub enum Repr<'tcx> {
         nndiscr: Disr,
         discrfield: DiscrField,
         nullfields: Vec<Ty<'tcx>>,
+    },
+    /// Several cases, all but one of them zero sized, distinguished by a niche:
+    /// the case with discriminant `dataful` is represented by its struct, where
+    /// the `niche`th field is an integer of type `niche_ity` which never holds
+    /// some of its values due to its type. The cases in `niche_variants` are
+    /// stored as those values, starting from `niche_start`.
+    ///
+    /// This is taken from `ty::layout::Layout::NicheFilling`.
+    NicheFilling {
+        dataful: Disr,
+        cases: Vec<Struct<'tcx>>,
+        niche: DiscrField,
+        niche_ity: IntType,
+        niche_start: u64,
+        niche_variants: (Disr, Disr),
     }
 }
 
@@ -119,6 +135,9 @@ pub struct Struct<'tcx> {
     pub sized: bool,
     pub packed: bool,
     pub fields: Vec<Ty<'tcx>>,
+    /// The position of each field in the LLVM struct, by source order
+    /// index, as decided by `ty::layout`.
+    pub memory_index: Vec<u32>,
 }
 
 #[derive(Copy, Clone)]
@@ -162,11 +181,32 @@ pub fn represent_type<'a, 'tcx>(cx: &CrateContext<'a, 'tcx>,
     repr
 }
 
+/// The layout of `t`, which decides how it's represented.
+fn layout_of<'a, 'tcx>(cx: &CrateContext<'a, 'tcx>, t: Ty<'tcx>) -> &'tcx layout::Layout {
+    let layout = cx.tcx().normalizing_infer_ctxt(Reveal::All).enter(|infcx| {
+        t.layout(&infcx)
+    });
+    match layout {
+        Ok(layout) => layout,
+        Err(layout::LayoutError::SizeOverflow(_)) => cx.report_overbig_object(t),
+        Err(err) => bug!("adt::layout_of: layout for `{}` failed: {}", t, err)
+    }
+}
+
+/// The field order of a struct-like type, if it was laid out as one.
+fn univariant_memory_index(layout: &layout::Layout) -> Option<&[u32]> {
+    match *layout {
+        layout::Univariant { ref variant, .. } => Some(&variant.memory_index[..]),
+        _ => None
+    }
+}
+
 fn represent_type_uncached<'a, 'tcx>(cx: &CrateContext<'a, 'tcx>,
                                      t: Ty<'tcx>) -> Repr<'tcx> {
+    let layout = layout_of(cx, t);
     match t.sty {
         ty::TyTuple(ref elems) => {
-            Univariant(mk_struct(cx, &elems[..], false, t))
+            Univariant(mk_struct(cx, &elems[..], false, univariant_memory_index(layout), t))
         }
         ty::TyStruct(def, substs) => {
             let ftys = def.struct_variant().fields.iter().map(|field| {
@@ -174,10 +214,11 @@ fn represent_type_uncached<'a, 'tcx>(cx: &CrateContext<'a, 'tcx>,
             }).collect::<Vec<_>>();
             let packed = cx.tcx().lookup_packed(def.did);
 
-            Univariant(mk_struct(cx, &ftys[..], packed, t))
+            // SIMD vectors are laid out as such, keeping their fields in order.
+            Univariant(mk_struct(cx, &ftys[..], packed, univariant_memory_index(layout), t))
         }
         ty::TyClosure(_, ref substs) => {
-            Univariant(mk_struct(cx, &substs.upvar_tys, false, t))
+            Univariant(mk_struct(cx, &substs.upvar_tys, false, univariant_memory_index(layout), t))
         }
         ty::TyEnum(def, substs) => {
             let cases = get_cases(cx.tcx(), def, substs);
@@ -188,7 +229,7 @@ fn represent_type_uncached<'a, 'tcx>(cx: &CrateContext<'a, 'tcx>,
                 // Uninhabitable; represent as unit
                 // (Typechecking will reject discriminant-sizing attrs.)
                 assert_eq!(hint, attr::ReprAny);
-                return Univariant(mk_struct(cx, &[], false, t));
+                return Univariant(mk_struct(cx, &[], false, None, t));
             }
 
             if cases.iter().all(|c| c.tys.is_empty()) {
@@ -211,121 +252,96 @@ fn represent_type_uncached<'a, 'tcx>(cx: &CrateContext<'a, 'tcx>,
                      cx.tcx().item_path_str(def.did));
             }
 
-            if cases.len() == 1 && hint == attr::ReprAny {
-                // Equivalent to a struct/tuple/newtype.
-                return Univariant(mk_struct(cx, &cases[0].tys, false, t));
-            }
-
-            if cases.len() == 2 && hint == attr::ReprAny {
-                // Nullable pointer optimization
-                let mut discr = 0;
-                while discr < 2 {
-                    if cases[1 - discr].is_zerolen(cx, t) {
-                        let st = mk_struct(cx, &cases[discr].tys,
-                                           false, t);
-                        match cases[discr].find_ptr(cx) {
-                            Some(ref df) if df.len() == 1 && st.fields.len() == 1 => {
-                                return RawNullablePointer {
-                                    nndiscr: Disr::from(discr),
-                                    nnty: st.fields[0],
-                                    nullfields: cases[1 - discr].tys.clone()
-                                };
-                            }
-                            Some(mut discrfield) => {
-                                discrfield.push(0);
-                                discrfield.reverse();
-                                return StructWrappedNullablePointer {
-                                    nndiscr: Disr::from(discr),
-                                    nonnull: st,
-                                    discrfield: discrfield,
-                                    nullfields: cases[1 - discr].tys.clone()
-                                };
-                            }
-                            None => {}
-                        }
+            // The layout code decides which optimizations apply.
+            match *layout {
+                layout::Univariant { ref variant, .. } => {
+                    // Equivalent to a struct/tuple/newtype.
+                    Univariant(mk_struct(cx, &cases[0].tys, false,
+                                         Some(&variant.memory_index[..]), t))
+                }
+                layout::RawNullablePointer { nndiscr, .. } => {
+                    let nndiscr = nndiscr as usize;
+                    RawNullablePointer {
+                        nndiscr: Disr::from(nndiscr),
+                        nnty: cases[nndiscr].tys[0],
+                        nullfields: cases[1 - nndiscr].tys.clone()
                     }
-                    discr += 1;
                 }
-            }
-
-            // The general case.
-            assert!((cases.len() - 1) as i64 >= 0);
-            let bounds = IntBounds { ulo: 0, uhi: (cases.len() - 1) as u64,
-                                     slo: 0, shi: (cases.len() - 1) as i64 };
-            let min_ity = range_to_inttype(cx, hint, &bounds);
-
-            // Create the set of structs that represent each variant
-            // Use the minimum integer type we figured out above
-            let fields : Vec<_> = cases.iter().map(|c| {
-                let mut ftys = vec!(ty_of_inttype(cx.tcx(), min_ity));
-                ftys.extend_from_slice(&c.tys);
-                mk_struct(cx, &ftys, false, t)
-            }).collect();
-
-
-            // Check to see if we should use a different type for the
-            // discriminant. If the overall alignment of the type is
-            // the same as the first field in each variant, we can safely use
-            // an alignment-sized type.
-            // We increase the size of the discriminant to avoid LLVM copying
-            // padding when it doesn't need to. This normally causes unaligned
-            // load/stores and excessive memcpy/memset operations. By using a
-            // bigger integer size, LLVM can be sure about it's contents and
-            // won't be so conservative.
-            // This check is needed to avoid increasing the size of types when
-            // the alignment of the first field is smaller than the overall
-            // alignment of the type.
-            let (_, align) = union_size_and_align(&fields);
-            let mut use_align = true;
-            for st in &fields {
-                // Get the first non-zero-sized field
-                let field = st.fields.iter().skip(1).filter(|ty| {
-                    let t = type_of::sizing_type_of(cx, **ty);
-                    machine::llsize_of_real(cx, t) != 0 ||
-                    // This case is only relevant for zero-sized types with large alignment
-                    machine::llalign_of_min(cx, t) != 1
-                }).next();
-
-                if let Some(field) = field {
-                    let field_align = type_of::align_of(cx, *field);
-                    if field_align != align {
-                        use_align = false;
-                        break;
+                layout::StructWrappedNullablePointer { nndiscr, ref nonnull,
+                                                       ref discrfield } => {
+                    let nndiscr = nndiscr as usize;
+                    StructWrappedNullablePointer {
+                        nndiscr: Disr::from(nndiscr),
+                        nonnull: mk_struct(cx, &cases[nndiscr].tys, false,
+                                           Some(&nonnull.memory_index[..]), t),
+                        discrfield: discrfield.iter().map(|&i| i as usize).collect(),
+                        nullfields: cases[1 - nndiscr].tys.clone()
                     }
                 }
-            }
-
-            // If the alignment is smaller than the chosen discriminant size, don't use the
-            // alignment as the final size.
-            let min_ty = ll_inttype(&cx, min_ity);
-            let min_size = machine::llsize_of_real(cx, min_ty);
-            if (align as u64) < min_size {
-                use_align = false;
-            }
-
-            let ity = if use_align {
-                // Use the overall alignment
-                match align {
-                    1 => attr::UnsignedInt(ast::UintTy::U8),
-                    2 => attr::UnsignedInt(ast::UintTy::U16),
-                    4 => attr::UnsignedInt(ast::UintTy::U32),
-                    8 if machine::llalign_of_min(cx, Type::i64(cx)) == 8 =>
-                        attr::UnsignedInt(ast::UintTy::U64),
-                    _ => min_ity // use min_ity as a fallback
+                layout::NicheFilling { dataful, ref variant, ref niche, niche_value,
+                                       niche_start, niche_variants, .. } => {
+                    let niche_ity = match niche_value {
+                        layout::Int(layout::I8) => attr::UnsignedInt(ast::UintTy::U8),
+                        layout::Int(layout::I16) => attr::UnsignedInt(ast::UintTy::U16),
+                        layout::Int(layout::I32) => attr::UnsignedInt(ast::UintTy::U32),
+                        layout::Int(layout::I64) => attr::UnsignedInt(ast::UintTy::U64),
+                        _ => bug!("represent_type: non-integer niche in `{}`", t)
+                    };
+                    // The other cases only have zero-sized fields.
+                    let cases: Vec<_> = cases.iter().enumerate().map(|(i, c)| {
+                        let memory_index = if i as u64 == dataful {
+                            Some(&variant.memory_index[..])
+                        } else {
+                            None
+                        };
+                        mk_struct(cx, &c.tys, false, memory_index, t)
+                    }).collect();
+                    NicheFilling {
+                        dataful: Disr(dataful),
+                        cases: cases,
+                        niche: niche.iter().map(|&i| i as usize).collect(),
+                        niche_ity: niche_ity,
+                        niche_start: niche_start,
+                        niche_variants: (Disr(niche_variants.0), Disr(niche_variants.1)),
+                    }
                 }
-            } else {
-                min_ity
-            };
+                layout::General { discr, ref variants, .. } => {
+                    // The general case.
+                    assert!((cases.len() - 1) as i64 >= 0);
+                    let bounds = IntBounds { ulo: 0, uhi: (cases.len() - 1) as u64,
+                                             slo: 0, shi: (cases.len() - 1) as i64 };
+                    let min_ity = range_to_inttype(cx, hint, &bounds);
+
+                    // The layout may have used a bigger discriminant than the
+                    // minimum, to avoid LLVM copying padding when it doesn't
+                    // need to.
+                    let min_size = machine::llsize_of_alloc(cx, ll_inttype(cx, min_ity));
+                    let discr_size = layout::Int(discr).size(&cx.tcx().data_layout);
+                    let ity = if discr_size.bytes() == min_size {
+                        min_ity
+                    } else {
+                        match discr {
+                            layout::I8 => attr::UnsignedInt(ast::UintTy::U8),
+                            layout::I16 => attr::UnsignedInt(ast::UintTy::U16),
+                            layout::I32 => attr::UnsignedInt(ast::UintTy::U32),
+                            layout::I64 => attr::UnsignedInt(ast::UintTy::U64),
+                            layout::I1 => bug!("represent_type: i1 discriminant in `{}`", t)
+                        }
+                    };
 
-            let fields : Vec<_> = cases.iter().map(|c| {
-                let mut ftys = vec!(ty_of_inttype(cx.tcx(), ity));
-                ftys.extend_from_slice(&c.tys);
-                mk_struct(cx, &ftys[..], false, t)
-            }).collect();
+                    // Each variant's struct starts with the discriminant.
+                    let fields : Vec<_> = cases.iter().zip(variants).map(|(c, variant)| {
+                        let mut ftys = vec!(ty_of_inttype(cx.tcx(), ity));
+                        ftys.extend_from_slice(&c.tys);
+                        mk_struct(cx, &ftys[..], false, Some(&variant.memory_index[..]), t)
+                    }).collect();
 
-            ensure_enum_fits_in_address_space(cx, &fields[..], t);
+                    ensure_enum_fits_in_address_space(cx, &fields[..], t);
 
-            General(ity, fields)
+                    General(ity, fields)
+                }
+                _ => bug!("adt::represent_type: unexpected layout for `{}`: {:#?}", t, layout)
+            }
         }
         _ => bug!("adt::represent_type called on non-ADT type: {}", t)
     }
@@ -340,109 +356,6 @@ struct Case<'tcx> {
 /// This represents the (GEP) indices to follow to get to the discriminant field
 pub type DiscrField = Vec<usize>;
 
-fn find_discr_field_candidate<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
-                                        ty: Ty<'tcx>,
-                                        mut path: DiscrField)
-                                        -> Option<DiscrField> {
-    match ty.sty {
-        // Fat &T/&mut T/Box<T> i.e. T is [T], str, or Trait
-        ty::TyRef(_, ty::TypeAndMut { ty, .. }) | ty::TyBox(ty) if !type_is_sized(tcx, ty) => {
-            path.push(FAT_PTR_ADDR);
-            Some(path)
-        },
-
-        // Regular thin pointer: &T/&mut T/Box<T>
-        ty::TyRef(..) | ty::TyBox(..) => Some(path),
-
-        // Function pointer: `fn() -> i32`
-        ty::TyFnPtr(_) => Some(path),
-
-        // Is this the NonZero lang item wrapping a pointer or integer type?
-        ty::TyStruct(def, substs) if Some(def.did) == tcx.lang_items.non_zero() => {
-            let nonzero_fields = &def.struct_variant().fields;
-            assert_eq!(nonzero_fields.len(), 1);
-            let field_ty = monomorphize::field_ty(tcx, substs, &nonzero_fields[0]);
-            match field_ty.sty {
-                ty::TyRawPtr(ty::TypeAndMut { ty, .. }) if !type_is_sized(tcx, ty) => {
-                    path.extend_from_slice(&[0, FAT_PTR_ADDR]);
-                    Some(path)
-                },
-                ty::TyRawPtr(..) | ty::TyInt(..) | ty::TyUint(..) => {
-                    path.push(0);
-                    Some(path)
-                },
-                _ => None
-            }
-        },
-
-        // Perhaps one of the fields of this struct is non-zero
-        // let's recurse and find out
-        ty::TyStruct(def, substs) => {
-            for (j, field) in def.struct_variant().fields.iter().enumerate() {
-                let field_ty = monomorphize::field_ty(tcx, substs, field);
-                if let Some(mut fpath) = find_discr_field_candidate(tcx, field_ty, path.clone()) {
-                    fpath.push(j);
-                    return Some(fpath);
-                }
-            }
-            None
-        },
-
-        // Perhaps one of the upvars of this struct is non-zero
-        // Let's recurse and find out!
-        ty::TyClosure(_, ref substs) => {
-            for (j, &ty) in substs.upvar_tys.iter().enumerate() {
-                if let Some(mut fpath) = find_discr_field_candidate(tcx, ty, path.clone()) {
-                    fpath.push(j);
-                    return Some(fpath);
-                }
-            }
-            None
-        },
-
-        // Can we use one of the fields in this tuple?
-        ty::TyTuple(ref tys) => {
-            for (j, &ty) in tys.iter().enumerate() {
-                if let Some(mut fpath) = find_discr_field_candidate(tcx, ty, path.clone()) {
-                    fpath.push(j);
-                    return Some(fpath);
-                }
-            }
-            None
-        },
-
-        // Is this a fixed-size array of something non-zero
-        // with at least one element?
-        ty::TyArray(ety, d) if d > 0 => {
-            if let Some(mut vpath) = find_discr_field_candidate(tcx, ety, path) {
-                vpath.push(0);
-                Some(vpath)
-            } else {
-                None
-            }
-        },
-
-        // Anything else is not a pointer
-        _ => None
-    }
-}
-
-impl<'tcx> Case<'tcx> {
-    fn is_zerolen<'a>(&self, cx: &CrateContext<'a, 'tcx>, scapegoat: Ty<'tcx>) -> bool {
-        mk_struct(cx, &self.tys, false, scapegoat).size == 0
-    }
-
-    fn find_ptr<'a>(&self, cx: &CrateContext<'a, 'tcx>) -> Option<DiscrField> {
-        for (i, &ty) in self.tys.iter().enumerate() {
-            if let Some(mut path) = find_discr_field_candidate(cx.tcx(), ty, vec![]) {
-                path.push(i);
-                return Some(path);
-            }
-        }
-        None
-    }
-}
-
 fn get_cases<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
                        adt: ty::AdtDef<'tcx>,
                        substs: &Substs<'tcx>)
@@ -455,15 +368,23 @@ fn get_cases<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
     }).collect()
 }
 
+/// Build the struct for fields `tys`, placed in memory as `memory_index`
+/// says, or in source order if there's no index.
 fn mk_struct<'a, 'tcx>(cx: &CrateContext<'a, 'tcx>,
                        tys: &[Ty<'tcx>], packed: bool,
+                       memory_index: Option<&[u32]>,
                        scapegoat: Ty<'tcx>)
                        -> Struct<'tcx> {
+    let memory_index = match memory_index {
+        Some(memory_index) => memory_index.to_vec(),
+        None => (0..tys.len() as u32).collect()
+    };
     let sized = tys.iter().all(|&ty| type_is_sized(cx.tcx(), ty));
+    let in_memory = in_memory_order(&memory_index, tys);
     let lltys : Vec<Type> = if sized {
-        tys.iter().map(|&ty| type_of::sizing_type_of(cx, ty)).collect()
+        in_memory.iter().map(|&ty| type_of::sizing_type_of(cx, ty)).collect()
     } else {
-        tys.iter().filter(|&ty| type_is_sized(cx.tcx(), *ty))
+        in_memory.iter().filter(|&ty| type_is_sized(cx.tcx(), *ty))
            .map(|&ty| type_of::sizing_type_of(cx, ty)).collect()
     };
 
@@ -476,7 +397,19 @@ fn mk_struct<'a, 'tcx>(cx: &CrateContext<'a, 'tcx>,
         sized: sized,
         packed: packed,
         fields: tys.to_vec(),
+        memory_index: memory_index,
+    }
+}
+
+/// Rearrange `items`, one for each field in source order, into the
+/// order of the fields in memory.
+pub fn in_memory_order<T: Copy>(memory_index: &[u32], items: &[T]) -> Vec<T> {
+    assert_eq!(memory_index.len(), items.len());
+    let mut in_memory = items.to_vec();
+    for (i, &item) in items.iter().enumerate() {
+        in_memory[memory_index[i] as usize] = item;
     }
+    in_memory
 }
 
 #[derive(Debug)]
@@ -645,8 +578,13 @@ pub fn finish_type_of<'a, 'tcx>(cx: &CrateContext<'a, 'tcx>,
     match *r {
         CEnum(..) | General(..) | RawNullablePointer { .. } => { }
         Univariant(ref st) | StructWrappedNullablePointer { nonnull: ref st, .. } =>
+            llty.set_struct_body(&struct_llfields(cx, st, false, false),
+                                 st.packed),
+        NicheFilling { dataful, ref cases, .. } => {
+            let st = &cases[dataful.0 as usize];
             llty.set_struct_body(&struct_llfields(cx, st, false, false),
                                  st.packed)
+        }
     }
 }
 
@@ -673,6 +611,19 @@ fn generic_type_of<'a, 'tcx>(cx: &CrateContext<'a, 'tcx>,
                 }
             }
         }
+        NicheFilling { dataful, ref cases, .. } => {
+            let st = &cases[dataful.0 as usize];
+            match name {
+                None => {
+                    Type::struct_(cx, &struct_llfields(cx, st, sizing, dst),
+                                  st.packed)
+                }
+                Some(name) => {
+                    assert_eq!(sizing, false);
+                    Type::named_struct(cx, name)
+                }
+            }
+        }
         Univariant(ref st) => {
             match name {
                 None => {
@@ -739,11 +690,12 @@ fn generic_type_of<'a, 'tcx>(cx: &CrateContext<'a, 'tcx>,
 
 fn struct_llfields<'a, 'tcx>(cx: &CrateContext<'a, 'tcx>, st: &Struct<'tcx>,
                              sizing: bool, dst: bool) -> Vec<Type> {
+    let fields = in_memory_order(&st.memory_index, &st.fields);
     if sizing {
-        st.fields.iter().filter(|&ty| !dst || type_is_sized(cx.tcx(), *ty))
+        fields.iter().filter(|&ty| !dst || type_is_sized(cx.tcx(), *ty))
             .map(|&ty| type_of::sizing_type_of(cx, ty)).collect()
     } else {
-        st.fields.iter().map(|&ty| type_of::in_memory_type_of(cx, ty)).collect()
+        fields.iter().map(|&ty| type_of::in_memory_type_of(cx, ty)).collect()
     }
 }
 
@@ -756,7 +708,8 @@ pub fn trans_switch<'blk, 'tcx>(bcx: Block<'blk, 'tcx>,
                                 -> (BranchKind, Option<ValueRef>) {
     match *r {
         CEnum(..) | General(..) |
-        RawNullablePointer { .. } | StructWrappedNullablePointer { .. } => {
+        RawNullablePointer { .. } | StructWrappedNullablePointer { .. } |
+        NicheFilling { .. } => {
             (BranchKind::Switch, Some(trans_get_discr(bcx, r, scrutinee, None, range_assert)))
         }
         Univariant(..) => {
@@ -773,6 +726,7 @@ pub fn is_discr_signed<'tcx>(r: &Repr<'tcx>) -> bool {
         Univariant(..) => false,
         RawNullablePointer { .. } => false,
         StructWrappedNullablePointer { .. } => false,
+        NicheFilling { .. } => false,
     }
 }
 
@@ -800,6 +754,10 @@ pub fn trans_get_discr<'blk, 'tcx>(bcx: Block<'blk, 'tcx>, r: &Repr<'tcx>,
         StructWrappedNullablePointer { nndiscr, ref discrfield, .. } => {
             struct_wrapped_nullable_bitdiscr(bcx, nndiscr, discrfield, scrutinee)
         }
+        NicheFilling { dataful, ref niche, niche_ity, niche_start, niche_variants, .. } => {
+            niche_filling_discr(bcx, dataful, niche, niche_ity, niche_start,
+                                niche_variants, scrutinee)
+        }
     };
     match cast_to {
         None => val,
@@ -815,6 +773,24 @@ fn struct_wrapped_nullable_bitdiscr(bcx: Block, nndiscr: Disr, discrfield: &Disc
     ICmp(bcx, cmp, llptr, C_null(val_ty(llptr)), DebugLoc::None)
 }
 
+/// Map the value of the niche to a discriminant: those starting from
+/// `niche_start` are the cases in `niche_variants`, in order, and any
+/// other is the dataful case.
+fn niche_filling_discr(bcx: Block, dataful: Disr, niche: &DiscrField, niche_ity: IntType,
+                       niche_start: u64, niche_variants: (Disr, Disr),
+                       scrutinee: ValueRef) -> ValueRef {
+    let llty = ll_inttype(bcx.ccx(), niche_ity);
+    let llnicheptr = GEPi(bcx, scrutinee, &niche[..]);
+    assert_eq!(val_ty(llnicheptr), llty.ptr_to());
+    let (first, last) = niche_variants;
+    let relative = Sub(bcx, Load(bcx, llnicheptr), C_integral(llty, niche_start, false),
+                       DebugLoc::None);
+    let is_niche = ICmp(bcx, IntULE, relative, C_integral(llty, last.0 - first.0, false),
+                        DebugLoc::None);
+    let discr = Add(bcx, relative, C_integral(llty, first.0, false), DebugLoc::None);
+    Select(bcx, is_niche, discr, C_integral(llty, dataful.0, false))
+}
+
 /// Helper for cases where the discriminant is simply loaded.
 fn load_discr(bcx: Block, ity: IntType, ptr: ValueRef, min: Disr, max: Disr,
               range_assert: bool)
@@ -861,6 +837,9 @@ pub fn trans_case<'blk, 'tcx>(bcx: Block<'blk, 'tcx>, r: &Repr, discr: Disr)
             assert!(discr == Disr(0) || discr == Disr(1));
             C_bool(bcx.ccx(), discr != Disr(0))
         }
+        NicheFilling { niche_ity, .. } => {
+            C_integral(ll_inttype(bcx.ccx(), niche_ity), discr.0, false)
+        }
     }
 }
 
@@ -894,9 +873,27 @@ pub fn trans_set_discr<'blk, 'tcx>(bcx: Block<'blk, 'tcx>, r: &Repr<'tcx>,
                 Store(bcx, C_null(llptrty), llptrptr);
             }
         }
+        NicheFilling { dataful, ref niche, niche_ity, niche_start, niche_variants, .. } => {
+            if discr != dataful {
+                let llnicheptr = GEPi(bcx, val, &niche[..]);
+                Store(bcx, niche_value(bcx.ccx(), niche_ity, niche_start, niche_variants, discr),
+                      llnicheptr);
+            }
+        }
     }
 }
 
+/// The value of the niche representing `discr`, one of `niche_variants`.
+fn niche_value(ccx: &CrateContext, niche_ity: IntType, niche_start: u64,
+               niche_variants: (Disr, Disr), discr: Disr) -> ValueRef {
+    let (first, last) = niche_variants;
+    assert!(first <= discr && discr <= last);
+    let llty = ll_inttype(ccx, niche_ity);
+    let bits = machine::llbitsize_of_real(ccx, llty);
+    let mask = !0u64 >> (64 - bits);
+    C_integral(llty, niche_start.wrapping_add(discr.0 - first.0) & mask, false)
+}
+
 fn assert_discr_in_range(ity: IntType, min: Disr, max: Disr, discr: Disr) {
     match ity {
         attr::UnsignedInt(_) => {
@@ -958,6 +955,10 @@ pub fn trans_field_ptr_builder<'blk, 'tcx>(bcx: &BlockAndBuilder<'blk, 'tcx>,
             assert_eq!(discr, nndiscr);
             struct_field_ptr(bcx, nonnull, val, ix, false)
         }
+        NicheFilling { dataful, ref cases, .. } => {
+            // The other cases only have zero-sized fields, as above.
+            struct_field_ptr(bcx, &cases[discr.0 as usize], val, ix, discr != dataful)
+        }
     }
 }
 
@@ -971,8 +972,9 @@ fn struct_field_ptr<'blk, 'tcx>(bcx: &BlockAndBuilder<'blk, 'tcx>,
         return C_undef(ll_fty.ptr_to());
     }
 
+    let fields_in_memory = in_memory_order(&st.memory_index, &st.fields);
     let ptr_val = if needs_cast {
-        let fields = st.fields.iter().map(|&ty| {
+        let fields = fields_in_memory.iter().map(|&ty| {
             type_of::in_memory_type_of(ccx, ty)
         }).collect::<Vec<_>>();
         let real_ty = Type::struct_(ccx, &fields[..], st.packed);
@@ -981,19 +983,22 @@ fn struct_field_ptr<'blk, 'tcx>(bcx: &BlockAndBuilder<'blk, 'tcx>,
         val.value
     };
 
+    // The LLVM struct has the fields in memory order.
+    let llix = st.memory_index[ix] as usize;
+
     // Simple case - we can just GEP the field
     //   * First field - Always aligned properly
     //   * Packed struct - There is no alignment padding
     //   * Field is sized - pointer is properly aligned already
-    if ix == 0 || st.packed || type_is_sized(bcx.tcx(), fty) {
-        return bcx.struct_gep(ptr_val, ix);
+    if llix == 0 || st.packed || type_is_sized(bcx.tcx(), fty) {
+        return bcx.struct_gep(ptr_val, llix);
     }
 
     // If the type of the last field is [T] or str, then we don't need to do
     // any adjusments
     match fty.sty {
         ty::TySlice(..) | ty::TyStr => {
-            return bcx.struct_gep(ptr_val, ix);
+            return bcx.struct_gep(ptr_val, llix);
         }
         _ => ()
     }
@@ -1002,7 +1007,7 @@ fn struct_field_ptr<'blk, 'tcx>(bcx: &BlockAndBuilder<'blk, 'tcx>,
     if !val.has_meta() {
         debug!("Unsized field `{}`, of `{:?}` has no metadata for adjustment",
                ix, Value(ptr_val));
-        return bcx.struct_gep(ptr_val, ix);
+        return bcx.struct_gep(ptr_val, llix);
     }
 
     let dbloc = DebugLoc::None;
@@ -1026,7 +1031,7 @@ fn struct_field_ptr<'blk, 'tcx>(bcx: &BlockAndBuilder<'blk, 'tcx>,
 
     // Calculate the unaligned offset of the unsized field.
     let mut offset = 0;
-    for &ty in &st.fields[0..ix] {
+    for &ty in &fields_in_memory[0..llix] {
         let llty = type_of::sizing_type_of(ccx, ty);
         let type_align = type_of::align_of(ccx, ty);
         offset = roundup(offset, type_align);
@@ -1060,6 +1065,15 @@ fn struct_field_ptr<'blk, 'tcx>(bcx: &BlockAndBuilder<'blk, 'tcx>,
     bcx.pointercast(byte_ptr, ll_fty.ptr_to())
 }
 
+/// The index of field `ix` of a struct, tuple or closure in its LLVM type,
+/// for accessing it directly rather than through `trans_field_ptr`.
+pub fn univariant_llfield_index(r: &Repr, ix: usize) -> usize {
+    match *r {
+        Univariant(ref st) => st.memory_index[ix] as usize,
+        _ => bug!("adt::univariant_llfield_index called on non-univariant {:?}", r)
+    }
+}
+
 /// Construct a constant value, suitable for initializing a
 /// GlobalVariable, given a case and constant values for its fields.
 /// Note that this may have a different LLVM type (and different
@@ -1128,16 +1142,69 @@ pub fn trans_const<'a, 'tcx>(ccx: &CrateContext<'a, 'tcx>, r: &Repr<'tcx>, discr
                          false)
             }
         }
+        NicheFilling { dataful, ref cases, ref niche, niche_ity, niche_start,
+                       niche_variants } => {
+            let st = &cases[dataful.0 as usize];
+            if discr == dataful {
+                C_struct(ccx, &build_const_struct(ccx, st, vals), st.packed)
+            } else {
+                // Zero everything but the niche, as above.
+                let llniche = niche_value(ccx, niche_ity, niche_start, niche_variants, discr);
+                let vals = st.fields.iter().enumerate().map(|(i, &ty)| {
+                    let llty = type_of::sizing_type_of(ccx, ty);
+                    if st.memory_index[i] as usize == niche[1] {
+                        const_with_niche(ccx, llty, &niche[2..], llniche)
+                    } else {
+                        C_null(llty)
+                    }
+                }).collect::<Vec<ValueRef>>();
+                C_struct(ccx, &build_const_struct(ccx, st, &vals[..]), st.packed)
+            }
+        }
     }
 }
 
-/// Compute struct field offsets relative to struct begin.
+/// Build a zeroed constant of type `llty`, except for `llniche` at `path`
+/// (relative to `llty`, as for `GEPi` without the leading 0).
+fn const_with_niche(ccx: &CrateContext, llty: Type, path: &[usize], llniche: ValueRef)
+                    -> ValueRef {
+    if path.is_empty() {
+        assert_eq!(llty, val_ty(llniche));
+        return llniche;
+    }
+    match llty.kind() {
+        TypeKind::Struct => {
+            let vals = llty.field_types().into_iter().enumerate().map(|(i, llfty)| {
+                if i == path[0] {
+                    const_with_niche(ccx, llfty, &path[1..], llniche)
+                } else {
+                    C_null(llfty)
+                }
+            }).collect::<Vec<_>>();
+            C_struct(ccx, &vals[..], llty.is_packed())
+        }
+        TypeKind::Array => {
+            let llety = llty.element_type();
+            let vals = (0..llty.array_length()).map(|i| {
+                if i == path[0] {
+                    const_with_niche(ccx, llety, &path[1..], llniche)
+                } else {
+                    C_null(llety)
+                }
+            }).collect::<Vec<_>>();
+            C_array(llety, &vals[..])
+        }
+        _ => bug!("const_with_niche: unexpected type {:?} on the way to the niche", llty)
+    }
+}
+
+/// Compute struct field offsets relative to struct begin, in memory order.
 fn compute_struct_field_offsets<'a, 'tcx>(ccx: &CrateContext<'a, 'tcx>,
                                           st: &Struct<'tcx>) -> Vec<u64> {
     let mut offsets = vec!();
 
     let mut offset = 0;
-    for &ty in &st.fields {
+    for ty in in_memory_order(&st.memory_index, &st.fields) {
         let llty = type_of::sizing_type_of(ccx, ty);
         if !st.packed {
             let type_align = type_of::align_of(ccx, ty);
@@ -1163,6 +1230,8 @@ fn build_const_struct<'a, 'tcx>(ccx: &CrateContext<'a, 'tcx>,
                                 -> Vec<ValueRef> {
     assert_eq!(vals.len(), st.fields.len());
 
+    // The values are given in source order, but go in memory order.
+    let vals = in_memory_order(&st.memory_index, vals);
     let target_offsets = compute_struct_field_offsets(ccx, st);
 
     // offset of current value
@@ -1203,17 +1272,25 @@ fn roundup(x: u64, a: u32) -> u64 { let a = a as u64; ((x + (a - 1)) / a) * a }
 ///
 /// (Not to be confused with `common::const_get_elt`, which operates on
 /// raw LLVM-level structs and arrays.)
-pub fn const_get_field(r: &Repr, val: ValueRef, _discr: Disr,
+pub fn const_get_field(r: &Repr, val: ValueRef, discr: Disr,
                        ix: usize) -> ValueRef {
+    let memory_index = |st: &Struct, ix: usize| st.memory_index[ix] as usize;
     match *r {
         CEnum(..) => bug!("element access in C-like enum const"),
-        Univariant(..) => const_struct_field(val, ix),
-        General(..) => const_struct_field(val, ix + 1),
+        Univariant(ref st) => const_struct_field(val, memory_index(st, ix)),
+        General(_, ref cases) => {
+            const_struct_field(val, memory_index(&cases[discr.0 as usize], ix + 1))
+        }
         RawNullablePointer { .. } => {
             assert_eq!(ix, 0);
             val
         },
-        StructWrappedNullablePointer{ .. } => const_struct_field(val, ix)
+        StructWrappedNullablePointer{ ref nonnull, .. } => {
+            const_struct_field(val, memory_index(nonnull, ix))
+        }
+        NicheFilling { ref cases, .. } => {
+            const_struct_field(val, memory_index(&cases[discr.0 as usize], ix))
+        }
     }
 }
 
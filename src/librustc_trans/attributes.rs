@@ -11,6 +11,7 @@
 
 use llvm::{self, Attribute, ValueRef};
 use llvm::AttributePlace::Function;
+use rustc::session::config::Sanitizer;
 pub use syntax::attr::InlineAttr;
 use syntax::ast;
 use context::CrateContext;
@@ -57,6 +58,16 @@ pub fn naked(val: ValueRef, is_naked: bool) {
     Attribute::Naked.toggle_llfn(Function, val, is_naked);
 }
 
+/// Tell LLVM to instrument the function for the sanitizer in use, if any.
+pub fn sanitize(ccx: &CrateContext, llfn: ValueRef) {
+    match ccx.sess().opts.debugging_opts.sanitizer {
+        Some(Sanitizer::Address) => Attribute::SanitizeAddress.apply_llfn(Function, llfn),
+        Some(Sanitizer::Memory) => Attribute::SanitizeMemory.apply_llfn(Function, llfn),
+        Some(Sanitizer::Thread) => Attribute::SanitizeThread.apply_llfn(Function, llfn),
+        Some(Sanitizer::Leak) | None => {}
+    }
+}
+
 pub fn set_frame_pointer_elimination(ccx: &CrateContext, llfn: ValueRef) {
     // FIXME: #11906: Omitting frame pointers breaks retrieving the value of a
     // parameter.
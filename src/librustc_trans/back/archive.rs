@@ -21,6 +21,7 @@ use libc;
 use llvm::archive_ro::{ArchiveRO, Child};
 use llvm::{self, ArchiveKind};
 use rustc::session::Session;
+use super::lto;
 
 pub struct ArchiveConfig<'a> {
     pub sess: &'a Session,
@@ -134,7 +135,16 @@ impl<'a> ArchiveBuilder<'a> {
     pub fn add_native_library(&mut self, name: &str) {
         let location = find_library(name, &self.config.lib_search_paths,
                                     self.config.sess);
-        self.add_archive(&location, |_| false).unwrap_or_else(|e| {
+        // With LTO, the bitcode in the library is merged into the crate's
+        // module instead.
+        let bitcode = if lto::merges_native_bitcode(self.config.sess) {
+            lto::bitcode_members(&location)
+        } else {
+            Vec::new()
+        };
+        self.add_archive(&location, move |fname: &str| {
+            bitcode.iter().any(|f| f == fname)
+        }).unwrap_or_else(|e| {
             self.config.sess.fatal(&format!("failed to add native library {}: {}",
                                             location.to_string_lossy(), e));
         });
@@ -144,7 +154,8 @@ impl<'a> ArchiveBuilder<'a> {
     /// archive.
     ///
     /// This ignores adding the bytecode from the rlib, and if LTO is enabled
-    /// then the object file also isn't added.
+    /// then neither are the object file and the native objects which are
+    /// LLVM bitcode.
     pub fn add_rlib(&mut self, rlib: &Path, name: &str, lto: bool)
                     -> io::Result<()> {
         // Ignoring obj file starting with the crate name
@@ -157,11 +168,17 @@ impl<'a> ArchiveBuilder<'a> {
         let bc_ext = ".bytecode.deflate";
         let metadata_filename =
             self.config.sess.cstore.metadata_filename().to_owned();
+        let bitcode = if lto && lto::merges_native_bitcode(self.config.sess) {
+            lto::bitcode_members(rlib)
+        } else {
+            Vec::new()
+        };
 
         self.add_archive(rlib, move |fname: &str| {
             let skip_obj = lto && fname.starts_with(&obj_start)
                 && fname.ends_with(".o");
-            skip_obj || fname.ends_with(bc_ext) || fname == metadata_filename
+            skip_obj || fname.ends_with(bc_ext) || fname == metadata_filename ||
+                bitcode.iter().any(|f| f == fname)
         })
     }
 
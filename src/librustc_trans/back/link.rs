@@ -8,14 +8,15 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
-use super::archive::{ArchiveBuilder, ArchiveConfig};
+use super::archive::{self, ArchiveBuilder, ArchiveConfig};
+use super::lto;
 use super::linker::Linker;
 use super::rpath::RPathConfig;
 use super::rpath;
 use super::msvc;
 use session::config;
 use session::config::NoDebugInfo;
-use session::config::{OutputFilenames, Input, OutputType};
+use session::config::{OutputFilenames, Input, OutputType, Sanitizer, SplitDebuginfo};
 use session::filesearch;
 use session::search_paths::PathKind;
 use session::Session;
@@ -188,6 +189,19 @@ pub fn link_binary(sess: &Session,
     let _task = sess.dep_graph.in_task(DepNode::LinkBinary);
 
     let mut out_filenames = Vec::new();
+
+    if sess.opts.output_types.contains_key(&OutputType::Metadata) {
+        let out_filename = filename_for_metadata(sess, crate_name, outputs);
+        emit_metadata(sess, trans, &out_filename);
+        out_filenames.push(out_filename);
+    }
+
+    // If nothing was translated then the metadata (and possibly dep-info,
+    // which has already been written) is all there is to output.
+    if !sess.opts.output_types.should_trans() {
+        return out_filenames;
+    }
+
     for &crate_type in sess.crate_types.borrow().iter() {
         // Ignore executable crates if we have -Z no-trans, as they will error.
         if sess.opts.debugging_opts.no_trans &&
@@ -210,6 +224,22 @@ pub fn link_binary(sess: &Session,
             remove(sess, &obj);
         }
         remove(sess, &outputs.with_extension("metadata.o"));
+
+        // Once packed, the `.dwo` files are only needed by the outputs whose
+        // objects are linked again by something else.
+        let all_linked = sess.crate_types.borrow().iter().all(|&crate_type| {
+            crate_type == config::CrateTypeExecutable ||
+            crate_type == config::CrateTypeDylib ||
+            crate_type == config::CrateTypeCdylib
+        });
+        if packed_split_dwarf(sess) && all_linked {
+            for module in &trans.modules {
+                let dwo = outputs.temp_path_ext("dwo", Some(&module.name[..]));
+                if dwo.exists() {
+                    remove(sess, &dwo);
+                }
+            }
+        }
     }
 
     out_filenames
@@ -286,6 +316,19 @@ pub fn filename_for_input(sess: &Session,
     }
 }
 
+/// Returns the path of the `.rmeta` file written for `--emit=metadata`.
+pub fn filename_for_metadata(sess: &Session,
+                             crate_name: &str,
+                             outputs: &OutputFilenames) -> PathBuf {
+    let libname = format!("{}{}", crate_name, sess.opts.cg.extra_filename);
+    outputs.outputs.get(&OutputType::Metadata)
+                   .and_then(|s| s.to_owned())
+                   .or_else(|| outputs.single_output_file.clone())
+                   .unwrap_or_else(|| {
+                       outputs.out_directory.join(&format!("lib{}.rmeta", libname))
+                   })
+}
+
 pub fn each_linked_rlib(sess: &Session,
                         f: &mut FnMut(ast::CrateNum, &Path)) {
     let crates = sess.cstore.used_crates(LinkagePreference::RequireStatic).into_iter();
@@ -357,6 +400,23 @@ fn link_binary_output(sess: &Session,
     out_filename
 }
 
+// Write the crate metadata on its own, without any of the object files that
+// would normally surround it in an rlib. Downstream crates can be checked
+// against such a file, but nothing can be linked against it.
+fn emit_metadata(sess: &Session, trans: &CrateTranslation, out_filename: &Path) {
+    if !is_writeable(out_filename) {
+        sess.fatal(&format!("output file {} is not writeable -- check its \
+                            permissions", out_filename.display()));
+    }
+
+    let result = fs::File::create(out_filename).and_then(|mut f| {
+        f.write_all(&trans.metadata)
+    });
+    if let Err(e) = result {
+        sess.fatal(&format!("failed to write {}: {}", out_filename.display(), e));
+    }
+}
+
 fn object_filenames(trans: &CrateTranslation,
                     outputs: &OutputFilenames)
                     -> Vec<PathBuf> {
@@ -365,7 +425,7 @@ fn object_filenames(trans: &CrateTranslation,
     }).collect()
 }
 
-fn archive_search_paths(sess: &Session) -> Vec<PathBuf> {
+pub fn archive_search_paths(sess: &Session) -> Vec<PathBuf> {
     let mut search = Vec::new();
     sess.target_filesearch(PathKind::Native).for_each_lib_search_path(|path, _| {
         search.push(path.to_path_buf());
@@ -502,7 +562,7 @@ fn link_rlib<'a>(sess: &'a Session,
                 // of when we do and don't keep .#module-name#.bc files around.
                 let user_wants_numbered_bitcode =
                         sess.opts.output_types.contains_key(&OutputType::Bitcode) &&
-                        sess.opts.cg.codegen_units > 1;
+                        sess.opts.codegen_units() > 1;
                 if !sess.opts.cg.save_temps && !user_wants_numbered_bitcode {
                     remove(sess, &bc_filename);
                 }
@@ -707,6 +767,33 @@ fn link_natively(sess: &Session,
             Err(e) => sess.fatal(&format!("failed to run dsymutil: {}", e)),
         }
     }
+
+    // With packed split debuginfo, the `.dwo` files of this crate and of all
+    // the crates linked in are gathered into the `.dwp` file next to the
+    // output, where debuggers look for it.
+    if packed_split_dwarf(sess) {
+        let mut dwp_out = out_filename.as_os_str().to_owned();
+        dwp_out.push(".dwp");
+        let mut cmd = Command::new("dwp");
+        cmd.arg("-e").arg(out_filename).arg("-o").arg(&dwp_out);
+        info!("{:?}", &cmd);
+        match cmd.output() {
+            Ok(ref prog) if prog.status.success() => {}
+            Ok(prog) => {
+                sess.struct_err(&format!("packing debuginfo with `dwp` failed: {}",
+                                         prog.status))
+                    .note(&format!("{:?}", &cmd))
+                    .note(&String::from_utf8_lossy(&prog.stderr))
+                    .emit();
+                sess.abort_if_errors();
+            }
+            Err(e) => sess.fatal(&format!("failed to run dwp: {}", e)),
+        }
+    }
+}
+
+fn packed_split_dwarf(sess: &Session) -> bool {
+    sess.opts.split_dwarf() && sess.opts.cg.split_debuginfo == SplitDebuginfo::Packed
 }
 
 fn link_args(cmd: &mut Linker,
@@ -807,10 +894,25 @@ fn link_args(cmd: &mut Linker,
     // link line. And finally upstream native libraries can't depend on anything
     // in this DAG so far because they're only dylibs and dylibs can only depend
     // on other dylibs (e.g. other native deps).
-    add_local_native_libraries(cmd, sess);
+    add_local_native_libraries(cmd, sess, tmpdir);
     add_upstream_rust_crates(cmd, sess, crate_type, tmpdir);
     add_upstream_native_libraries(cmd, sess);
 
+    if crate_type == config::CrateTypeExecutable {
+        if let Some(sanitizer) = sess.opts.debugging_opts.sanitizer {
+            add_sanitizer_runtime(cmd, sanitizer, &lib_path);
+        }
+    }
+
+    // The instrumented code calls into the profiler runtime, which writes the
+    // profile or coverage data out when the program exits.
+    if sess.opts.cg.profile_generate.is_some() ||
+       sess.opts.debugging_opts.instrument_coverage {
+        cmd.hint_static();
+        cmd.link_staticlib("rustc_profiler");
+        cmd.hint_dynamic();
+    }
+
     // # Telling the linker what we're doing
 
     if crate_type != config::CrateTypeExecutable {
@@ -843,11 +945,34 @@ fn link_args(cmd: &mut Linker,
     }
 
     // Finally add all the linker arguments provided on the command line along
-    // with any #[link_args] attributes found inside the crate
-    if let Some(ref args) = sess.opts.cg.link_args {
-        cmd.args(args);
+    // with any #[link_args] attributes found inside the crate, except for the
+    // bitcode files LTO has already merged in.
+    let bitcode_args = if lto::merges_native_bitcode(sess) {
+        lto::bitcode_link_args(sess)
+    } else {
+        Vec::new()
+    };
+    let empty_vec = Vec::new();
+    let args = sess.opts.cg.link_args.as_ref().unwrap_or(&empty_vec);
+    let args: Vec<_> = args.iter().chain(used_link_args.iter()).filter(|arg| {
+        !bitcode_args.contains(arg)
+    }).cloned().collect();
+    cmd.args(&args);
+}
+
+// The sanitizer runtimes are static libraries built from compiler-rt along
+// with the standard library. Nothing refers to the libc functions they
+// intercept, so they're linked whole, followed by the system libraries they
+// use, and their symbols are exported for shared libraries to call too.
+fn add_sanitizer_runtime(cmd: &mut Linker, sanitizer: Sanitizer, lib_path: &Path) {
+    let name = format!("rustc_{}", sanitizer.runtime_name());
+    cmd.hint_static();
+    cmd.link_whole_staticlib(&name, &[lib_path.to_path_buf()]);
+    cmd.hint_dynamic();
+    for lib in &["pthread", "rt", "m", "dl"] {
+        cmd.link_dylib(lib);
     }
-    cmd.args(&used_link_args);
+    cmd.args(&["-Wl,--export-dynamic".to_string()]);
 }
 
 // # Native library linking
@@ -861,7 +986,7 @@ fn link_args(cmd: &mut Linker,
 // Also note that the native libraries linked here are only the ones located
 // in the current crate. Upstream crates with native library dependencies
 // may have their native library pulled in above.
-fn add_local_native_libraries(cmd: &mut Linker, sess: &Session) {
+fn add_local_native_libraries(cmd: &mut Linker, sess: &Session, tmpdir: &Path) {
     sess.target_filesearch(PathKind::All).for_each_lib_search_path(|path, k| {
         match k {
             PathKind::Framework => { cmd.framework_path(path); }
@@ -886,6 +1011,31 @@ fn add_local_native_libraries(cmd: &mut Linker, sess: &Session) {
 
     let search_path = archive_search_paths(sess);
     for l in staticlibs {
+        // LTO has merged the bitcode in the library into the crate's module,
+        // so the linker gets a copy of the library without it, if anything.
+        if lto::merges_native_bitcode(sess) {
+            let path = archive::find_library(l, &search_path, sess);
+            let bitcode = lto::bitcode_members(&path);
+            if !bitcode.is_empty() {
+                let dst = tmpdir.join(path.file_name().unwrap());
+                let mut ab = ArchiveBuilder::new(archive_config(sess, &dst, Some(&path)));
+                let mut any_objects = false;
+                for f in ab.src_files() {
+                    if bitcode.contains(&f) {
+                        ab.remove_file(&f);
+                    } else {
+                        any_objects = true;
+                    }
+                }
+                if any_objects {
+                    ab.update_symbols();
+                    ab.build();
+                    cmd.link_whole_rlib(&fix_windows_verbatim_for_gcc(&dst));
+                }
+                continue
+            }
+        }
+
         // Here we explicitly ask that the entire archive is included into the
         // result artifact. For more details see #15460, but the gist is that
         // the linker will strip away any unused objects in the archive if we
@@ -1004,6 +1154,14 @@ fn add_upstream_rust_crates(cmd: &mut Linker,
         let name = cratepath.file_name().unwrap().to_str().unwrap();
         let name = &name[3..name.len() - 5]; // chop off lib/.rlib
 
+        // The bundled native objects that are bitcode have been merged into
+        // the crate's module by LTO.
+        let bitcode = if lto::merges_native_bitcode(sess) {
+            lto::bitcode_members(cratepath)
+        } else {
+            Vec::new()
+        };
+
         time(sess.time_passes(), &format!("altering {}.rlib", name), || {
             let cfg = archive_config(sess, &dst, Some(cratepath));
             let mut archive = ArchiveBuilder::new(cfg);
@@ -1012,7 +1170,7 @@ fn add_upstream_rust_crates(cmd: &mut Linker,
 
             let mut any_objects = false;
             for f in archive.src_files() {
-                if f.ends_with("bytecode.deflate") {
+                if f.ends_with("bytecode.deflate") || bitcode.contains(&f) {
                     archive.remove_file(&f);
                     continue
                 }
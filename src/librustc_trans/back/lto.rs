@@ -8,20 +8,26 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
+use super::archive;
 use super::link;
 use super::write;
+use rustc::middle::cstore::NativeLibraryKind;
 use rustc::session::{self, config};
 use llvm;
 use llvm::archive_ro::ArchiveRO;
 use llvm::{ModuleRef, TargetMachineRef, True, False};
 use rustc::util::common::time;
 use rustc::util::common::path2cstr;
+use rustc::util::nodemap::FnvHashSet;
 use back::write::{ModuleConfig, with_llvm_pmb};
+use base;
 
 use libc;
 use flate;
 
-use std::ffi::CString;
+use std::ffi::{CStr, CString};
+use std::fs::File;
+use std::io::Read;
 use std::path::Path;
 
 pub fn run(sess: &session::Session, llmod: ModuleRef,
@@ -49,16 +55,32 @@ pub fn run(sess: &session::Session, llmod: ModuleRef,
         }
     }
 
+    // The bitcode of the native code the crate links to, which is merged in
+    // after all of the Rust code.
+    let mut native_bitcode = Vec::new();
+
     // For each of our upstream dependencies, find the corresponding rlib and
     // load the bitcode from the archive. Then merge it into the current LLVM
     // module that we've got.
     link::each_linked_rlib(sess, &mut |cnum, path| {
+        let archive = ArchiveRO::open(&path).expect("wanted an rlib");
+
+        // The objects of the native libraries bundled into the rlib.
+        if merges_native_bitcode(sess) {
+            for child in archive.iter().filter_map(|child| child.ok()) {
+                if let Some(name) = child.name() {
+                    if is_llvm_bitcode(child.data()) {
+                        native_bitcode.push((name.to_string(), child.data().to_vec()));
+                    }
+                }
+            }
+        }
+
         // `#![no_builtins]` crates don't participate in LTO.
         if sess.cstore.is_no_builtins(cnum) {
             return;
         }
 
-        let archive = ArchiveRO::open(&path).expect("wanted an rlib");
         let bytecodes = archive.iter().filter_map(|child| {
             child.ok().and_then(|c| c.name().map(|name| (name, c)))
         }).filter(|&(name, _)| name.ends_with("bytecode.deflate"));
@@ -118,6 +140,33 @@ pub fn run(sess: &session::Session, llmod: ModuleRef,
         }
     });
 
+    if merges_native_bitcode(sess) {
+        native_bitcode.extend(local_native_bitcode(sess));
+    }
+
+    // The native code may be called from objects that aren't bitcode, or by
+    // the users of the output, so whatever it defines stays external.
+    let mut reachable = reachable.to_vec();
+    if !native_bitcode.is_empty() {
+        let rust_symbols: FnvHashSet<_> = unsafe { defined_symbols(llmod) }.into_iter().collect();
+
+        for (name, bc) in native_bitcode {
+            debug!("linking native {}", name);
+            time(sess.time_passes(), &format!("ll link {}", name), || unsafe {
+                if !llvm::LLVMRustLinkInExternalBitcode(llmod,
+                                                        bc.as_ptr() as *const libc::c_char,
+                                                        bc.len() as libc::size_t) {
+                    write::llvm_err(sess.diagnostic(),
+                                    format!("failed to load bc of native `{}`", name));
+                }
+            });
+        }
+
+        reachable.extend(unsafe { defined_symbols(llmod) }.into_iter().filter(|symbol| {
+            !rust_symbols.contains(symbol)
+        }));
+    }
+
     // Internalize everything but the reachable symbols of the current module
     let cstrs: Vec<CString> = reachable.iter().map(|s| {
         CString::new(s.clone()).unwrap()
@@ -174,6 +223,88 @@ pub fn run(sess: &session::Session, llmod: ModuleRef,
     debug!("lto done");
 }
 
+/// Whether `-C lto` also merges the LLVM bitcode found among the native
+/// libraries and the linker arguments into the crate's module, so that it's
+/// optimized together with the Rust code. The targets whose objects are all
+/// bitcode leave that to their linker.
+pub fn merges_native_bitcode(sess: &session::Session) -> bool {
+    sess.lto() && !sess.target.target.options.obj_is_bitcode
+}
+
+/// Whether `data` is LLVM bitcode, either bare or in the wrapper some
+/// toolchains put around it.
+pub fn is_llvm_bitcode(data: &[u8]) -> bool {
+    data.starts_with(b"BC\xC0\xDE") || data.starts_with(b"\xDE\xC0\x17\x0B")
+}
+
+/// The names of the members of an archive which are LLVM bitcode.
+pub fn bitcode_members(path: &Path) -> Vec<String> {
+    let archive = match ArchiveRO::open(path) {
+        Some(archive) => archive,
+        None => return Vec::new(),
+    };
+    archive.iter().filter_map(|child| child.ok()).filter(|child| {
+        is_llvm_bitcode(child.data())
+    }).filter_map(|child| child.name().map(|name| name.to_string())).collect()
+}
+
+/// The linker arguments which are paths to LLVM bitcode files.
+pub fn bitcode_link_args(sess: &session::Session) -> Vec<String> {
+    let empty_vec = Vec::new();
+    let args = sess.opts.cg.link_args.as_ref().unwrap_or(&empty_vec);
+    args.iter().chain(sess.cstore.used_link_args().iter()).filter(|arg| {
+        let mut magic = [0; 4];
+        File::open(arg).and_then(|mut f| f.read_exact(&mut magic)).is_ok() &&
+            is_llvm_bitcode(&magic)
+    }).cloned().collect()
+}
+
+/// The bitcode in the crate's own native static libraries, and in the files
+/// passed to the linker.
+fn local_native_bitcode(sess: &session::Session) -> Vec<(String, Vec<u8>)> {
+    let mut bitcode = Vec::new();
+
+    let search_paths = link::archive_search_paths(sess);
+    for (name, kind) in sess.cstore.used_libraries() {
+        if kind != NativeLibraryKind::NativeStatic {
+            continue
+        }
+        let path = archive::find_library(&name, &search_paths, sess);
+        let archive = match ArchiveRO::open(&path) {
+            Some(archive) => archive,
+            None => sess.fatal(&format!("failed to open native library `{}`", path.display())),
+        };
+        for child in archive.iter().filter_map(|child| child.ok()) {
+            if let Some(member) = child.name() {
+                if is_llvm_bitcode(child.data()) {
+                    bitcode.push((format!("{}({})", name, member), child.data().to_vec()));
+                }
+            }
+        }
+    }
+
+    for arg in bitcode_link_args(sess) {
+        let mut data = Vec::new();
+        if let Err(e) = File::open(&arg).and_then(|mut f| f.read_to_end(&mut data)) {
+            sess.fatal(&format!("failed to read `{}`: {}", arg, e));
+        }
+        bitcode.push((arg, data));
+    }
+
+    bitcode
+}
+
+/// The names of the functions and globals that `llmod` defines with
+/// external linkage.
+unsafe fn defined_symbols(llmod: ModuleRef) -> Vec<String> {
+    base::iter_globals(llmod).chain(base::iter_functions(llmod)).filter(|&val| {
+        llvm::LLVMIsDeclaration(val) == False &&
+            llvm::LLVMGetLinkage(val) == llvm::ExternalLinkage as libc::c_uint
+    }).map(|val| {
+        CStr::from_ptr(llvm::LLVMGetValueName(val)).to_string_lossy().into_owned()
+    }).collect()
+}
+
 fn is_versioned_bytecode_format(bc: &[u8]) -> bool {
     let magic_id_byte_count = link::RLIB_BYTECODE_OBJECT_MAGIC.len();
     return bc.len() > magic_id_byte_count &&
@@ -11,7 +11,7 @@
 use back::lto;
 use back::link::{get_linker, remove};
 use rustc_incremental::save_trans_partition;
-use session::config::{OutputFilenames, OutputTypes, Passes, SomePasses, AllPasses};
+use session::config::{OutputFilenames, OutputTypes, Passes, SomePasses, AllPasses, Sanitizer};
 use session::Session;
 use session::config::{self, OutputType};
 use llvm;
@@ -20,6 +20,7 @@ use llvm::SMDiagnosticRef;
 use {CrateTranslation, ModuleLlvm, ModuleSource, ModuleTranslation};
 use util::common::time;
 use util::common::path2cstr;
+use util::profiling::{self, Category};
 use util::fs::link_or_copy;
 use errors::{self, Handler, Level, DiagnosticBuilder};
 use errors::emitter::Emitter;
@@ -27,13 +28,16 @@ use syntax_pos::MultiSpan;
 use context::{is_pie_binary, get_reloc_model};
 
 use std::ffi::{CStr, CString};
+use std::env;
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::ptr;
 use std::str;
 use std::sync::{Arc, Mutex};
 use std::sync::mpsc::channel;
 use std::thread;
-use libc::{c_uint, c_void};
+use libc::{c_char, c_uint, c_void};
 
 pub const RELOC_MODEL_ARGS : [(&'static str, llvm::RelocMode); 4] = [
     ("pic", llvm::RelocMode::PIC),
@@ -241,6 +245,16 @@ pub struct ModuleConfig {
     /// Some(level) to optimize binary size, or None to not affect program size.
     opt_size: Option<llvm::CodeGenOptSize>,
 
+    /// Some(path) to instrument the code to write its execution profile to,
+    /// for `-C profile-generate`.
+    pgo_gen: Option<PathBuf>,
+    /// Some(path) to the profile data to optimize with, for `-C profile-use`.
+    pgo_use: Option<PathBuf>,
+    /// Whether the gcov instrumentation pass runs, for `-Z instrument-coverage`.
+    instrument_coverage: bool,
+    /// Whether the DWARF is moved out of the object file into a `.dwo` file.
+    split_dwarf: bool,
+
     // Flags indicating which outputs to produce.
     emit_no_opt_bc: bool,
     emit_bc: bool,
@@ -274,6 +288,11 @@ impl ModuleConfig {
             opt_level: None,
             opt_size: None,
 
+            pgo_gen: None,
+            pgo_use: None,
+            instrument_coverage: false,
+            split_dwarf: false,
+
             emit_no_opt_bc: false,
             emit_bc: false,
             emit_lto_bc: false,
@@ -415,6 +434,39 @@ unsafe extern "C" fn diagnostic_handler(info: DiagnosticInfoRef, user: *mut c_vo
     }
 }
 
+/// Tell the gcov instrumentation pass where to put the notes and data files
+/// of each compile unit of the module. Left to itself, it would name them
+/// after the crate's source file, so every codegen unit would write the same
+/// files.
+unsafe fn add_gcov_metadata(llcx: ContextRef,
+                            llmod: ModuleRef,
+                            output_names: &OutputFilenames,
+                            module_name: Option<&str>) {
+    let notes = output_names.temp_path_ext("gcno", module_name);
+    // The data is written wherever the program happens to run from.
+    let data = output_names.temp_path_ext("gcda", module_name);
+    let data = env::current_dir().map(|cwd| cwd.join(&data)).unwrap_or(data);
+
+    let md_string = |path: &Path| {
+        let path = path2cstr(path);
+        let len = path.as_bytes().len() as c_uint;
+        llvm::LLVMMDStringInContext(llcx, path.as_ptr(), len)
+    };
+    let notes = md_string(&notes);
+    let data = md_string(&data);
+
+    let cu_name = "llvm.dbg.cu\0".as_ptr() as *const c_char;
+    let num_cus = llvm::LLVMGetNamedMetadataNumOperands(llmod, cu_name);
+    let mut cus = vec![ptr::null_mut(); num_cus as usize];
+    llvm::LLVMGetNamedMetadataOperands(llmod, cu_name, cus.as_mut_ptr());
+    for cu in cus {
+        let node = [notes, data, cu];
+        let node = llvm::LLVMMDNodeInContext(llcx, node.as_ptr(), node.len() as c_uint);
+        llvm::LLVMAddNamedMetadataOperand(llmod, "llvm.gcov\0".as_ptr() as *const c_char,
+                                          node);
+    }
+}
+
 // Unsafe due to LLVM calls.
 unsafe fn optimize_and_codegen(cgcx: &CodegenContext,
                                mtrans: ModuleTranslation,
@@ -437,6 +489,10 @@ unsafe fn optimize_and_codegen(cgcx: &CodegenContext,
 
     let module_name = Some(&mtrans.name[..]);
 
+    if config.instrument_coverage {
+        add_gcov_metadata(llcx, llmod, &output_names, module_name);
+    }
+
     if config.emit_no_opt_bc {
         let out = output_names.temp_path_ext("no-opt.bc", module_name);
         let out = path2cstr(&out);
@@ -605,6 +661,11 @@ unsafe fn optimize_and_codegen(cgcx: &CodegenContext,
         }
     });
 
+    if write_obj && config.split_dwarf {
+        let dwo_out = output_names.temp_path_ext("dwo", module_name);
+        split_dwarf(cgcx.handler, &obj_out, &dwo_out);
+    }
+
     if copy_bc_to_obj {
         debug!("copying bitcode {:?} to obj {:?}", bc_out, obj_out);
         if let Err(e) = link_or_copy(&bc_out, &obj_out) {
@@ -623,6 +684,34 @@ unsafe fn optimize_and_codegen(cgcx: &CodegenContext,
 }
 
 
+/// Move the DWARF of an object file written with `-split-dwarf` out into the
+/// `.dwo` file its skeleton compile unit names, as `clang -gsplit-dwarf` does.
+fn split_dwarf(handler: &Handler, obj: &Path, dwo: &Path) {
+    let mut extract = Command::new("objcopy");
+    extract.arg("--extract-dwo").arg(obj).arg(dwo);
+    let mut strip = Command::new("objcopy");
+    strip.arg("--strip-dwo").arg(obj);
+
+    for cmd in &mut [extract, strip] {
+        debug!("{:?}", cmd);
+        match cmd.output() {
+            Ok(ref prog) if prog.status.success() => {}
+            Ok(prog) => {
+                handler.struct_err(&format!("splitting debuginfo with `objcopy` failed: {}",
+                                            prog.status))
+                    .note(&format!("{:?}", cmd))
+                    .note(&String::from_utf8_lossy(&prog.stderr))
+                    .emit();
+                return
+            }
+            Err(e) => {
+                handler.err(&format!("could not exec `objcopy`: {}", e));
+                return
+            }
+        }
+    }
+}
+
 pub fn cleanup_llvm(trans: &CrateTranslation) {
     for module in trans.modules.iter() {
         unsafe {
@@ -647,7 +736,7 @@ pub fn run_passes(sess: &Session,
     // case, but it would be confusing to have the validity of
     // `-Z lto -C codegen-units=2` depend on details of the crate being
     // compiled, so we complain regardless.
-    if sess.lto() && sess.opts.cg.codegen_units > 1 {
+    if sess.lto() && sess.opts.codegen_units() > 1 {
         // This case is impossible to handle because LTO expects to be able
         // to combine the entire crate and all its dependencies into a
         // single compilation unit, but each codegen unit is in a separate
@@ -656,19 +745,49 @@ pub fn run_passes(sess: &Session,
     }
 
     // Sanity check
-    assert!(trans.modules.len() == sess.opts.cg.codegen_units ||
+    assert!(trans.modules.len() == sess.opts.codegen_units() ||
             sess.opts.debugging_opts.incremental.is_some());
 
     let tm = create_target_machine(sess);
 
     // Figure out what we actually need to build.
 
-    let mut modules_config = ModuleConfig::new(tm, sess.opts.cg.passes.clone());
+    let mut passes = sess.opts.cg.passes.clone();
+
+    // The sanitizers' instrumentation passes are added after the ones the
+    // optimization level picks. `asan`, `msan` and `tsan` are function passes
+    // though, which all run before the module passes, so they instrument the
+    // code before it is inlined and further optimized; only `asan-module`,
+    // which instruments the globals, runs at the end of the module passes.
+    match sess.opts.debugging_opts.sanitizer {
+        Some(Sanitizer::Address) => {
+            passes.push("asan".to_string());
+            passes.push("asan-module".to_string());
+        }
+        Some(Sanitizer::Memory) => passes.push("msan".to_string()),
+        Some(Sanitizer::Thread) => passes.push("tsan".to_string()),
+        // LeakSanitizer needs no instrumentation, only its runtime.
+        Some(Sanitizer::Leak) | None => {}
+    }
+    if sess.opts.debugging_opts.instrument_coverage {
+        passes.push("insert-gcov-profiling".to_string());
+    }
+
+    let mut modules_config = ModuleConfig::new(tm, passes);
     let mut metadata_config = ModuleConfig::new(tm, vec!());
 
     modules_config.opt_level = Some(get_llvm_opt_level(sess.opts.optimize));
     modules_config.opt_size = Some(get_llvm_opt_size(sess.opts.optimize));
 
+    // `%m` lets the runtime merge the profiles of all the runs of a program
+    // into one file per binary, instead of each run overwriting the last.
+    modules_config.pgo_gen = sess.opts.cg.profile_generate.as_ref().map(|dir| {
+        dir.join("default_%m.profraw")
+    });
+    modules_config.pgo_use = sess.opts.cg.profile_use.clone();
+    modules_config.instrument_coverage = sess.opts.debugging_opts.instrument_coverage;
+    modules_config.split_dwarf = sess.opts.split_dwarf();
+
     // Save all versions of the bytecode if we're saving our temporaries.
     if sess.opts.cg.save_temps {
         modules_config.emit_no_opt_bc = true;
@@ -707,6 +826,7 @@ pub fn run_passes(sess: &Session,
                 modules_config.emit_obj = true;
                 metadata_config.emit_obj = true;
             },
+            OutputType::Metadata |
             OutputType::DepInfo => {}
         }
     }
@@ -844,6 +964,7 @@ pub fn run_passes(sess: &Session,
                 copy_if_one_unit(OutputType::Object, true);
             }
             OutputType::Exe |
+            OutputType::Metadata |
             OutputType::DepInfo => {}
         }
     }
@@ -880,10 +1001,10 @@ pub fn run_passes(sess: &Session,
         // where .#module-name#.bc files are (maybe) deleted after making an
         // rlib.
         let keep_numbered_bitcode = needs_crate_bitcode ||
-                (user_wants_bitcode && sess.opts.cg.codegen_units > 1);
+                (user_wants_bitcode && sess.opts.codegen_units() > 1);
 
         let keep_numbered_objects = needs_crate_object ||
-                (user_wants_objects && sess.opts.cg.codegen_units > 1);
+                (user_wants_objects && sess.opts.codegen_units() > 1);
 
         for module_name in trans.modules.iter().map(|m| Some(&m.name[..])) {
             if modules_config.emit_obj && !keep_numbered_objects {
@@ -912,7 +1033,7 @@ pub fn run_passes(sess: &Session,
 
     // FIXME: time_llvm_passes support - does this use a global context or
     // something?
-    if sess.opts.cg.codegen_units == 1 && sess.time_llvm_passes() {
+    if sess.opts.codegen_units() == 1 && sess.time_llvm_passes() {
         unsafe { llvm::LLVMRustPrintPassTimings(); }
     }
 }
@@ -955,11 +1076,16 @@ fn execute_work_item(cgcx: &CodegenContext,
         match work_item.mtrans.source {
             ModuleSource::Translated(mllvm) => {
                 debug!("llvm-optimizing {:?}", work_item.mtrans.name);
-                optimize_and_codegen(cgcx,
-                                     work_item.mtrans,
-                                     mllvm,
-                                     work_item.config,
-                                     work_item.output_names);
+                // The individual optimization and codegen passes are timed
+                // within, this groups them together by module in profiles.
+                let event_name = format!("module {}", work_item.mtrans.name);
+                profiling::record(Category::Llvm, &event_name, || {
+                    optimize_and_codegen(cgcx,
+                                         work_item.mtrans,
+                                         mllvm,
+                                         work_item.config,
+                                         work_item.output_names)
+                });
             }
             ModuleSource::Preexisting(wp) => {
                 let incremental = cgcx.incremental.as_ref().unwrap();
@@ -1019,8 +1145,12 @@ fn run_work_multithreaded(sess: &Session,
         futures.push(rx);
 
         let incremental = sess.opts.incremental.clone();
+        let profiler = profiling::current();
 
         thread::Builder::new().name(format!("codegen-{}", i)).spawn(move || {
+            // Thread 0 is the main thread in the profile.
+            profiling::enter_thread(profiler, i + 1);
+
             let diag_handler = Handler::with_emitter(true, false, box diag_emitter);
 
             // Must construct cgcx inside the proc because it has non-Send
@@ -1109,10 +1239,17 @@ pub unsafe fn with_llvm_pmb(llmod: ModuleRef,
     let opt_size = config.opt_size.unwrap_or(llvm::CodeGenOptSizeNone);
     let inline_threshold = config.inline_threshold;
 
+    let pgo_gen_path = config.pgo_gen.as_ref().map(|p| path2cstr(p));
+    let pgo_use_path = config.pgo_use.as_ref().map(|p| path2cstr(p));
+    let pgo_gen_ptr = pgo_gen_path.as_ref().map_or(ptr::null(), |s| s.as_ptr());
+    let pgo_use_ptr = pgo_use_path.as_ref().map_or(ptr::null(), |s| s.as_ptr());
+
     llvm::LLVMRustConfigurePassManagerBuilder(builder, opt_level,
                                               config.merge_functions,
                                               config.vectorize_slp,
-                                              config.vectorize_loop);
+                                              config.vectorize_loop,
+                                              pgo_gen_ptr,
+                                              pgo_use_ptr);
     llvm::LLVMPassManagerBuilderSetSizeLevel(builder, opt_size as u32);
 
     if opt_size != llvm::CodeGenOptSizeNone {
@@ -38,14 +38,16 @@ use llvm;
 use rustc::hir::def_id::DefId;
 use middle::lang_items::{LangItem, ExchangeMallocFnLangItem, StartFnLangItem};
 use rustc::ty::subst::Substs;
-use rustc::traits;
+use rustc::traits::{self, Reveal};
 use rustc::ty::{self, Ty, TyCtxt, TypeFoldable};
 use rustc::ty::adjustment::CustomCoerceUnsized;
+use rustc::ty::layout;
 use rustc::dep_graph::{DepNode, WorkProduct};
 use rustc::hir::map as hir_map;
 use rustc::util::common::time;
 use rustc::mir::mir_map::MirMap;
-use session::config::{self, NoDebugInfo};
+use session::config::{self, NoDebugInfo, OutputFilenames, OutputType};
+use session::code_stats::{DataTypeKind, FieldInfo, TypeSizeInfo, VariantInfo};
 use rustc_incremental::IncrementalHashesMap;
 use session::Session;
 use abi::{self, Abi, FnType};
@@ -1340,7 +1342,8 @@ fn write_metadata(cx: &SharedCrateContext,
                         .borrow()
                         .iter()
                         .any(|ty| *ty != config::CrateTypeExecutable);
-    if !any_library {
+    let emit_metadata = cx.sess().opts.output_types.contains_key(&OutputType::Metadata);
+    if !any_library && !emit_metadata {
         return Vec::new();
     }
 
@@ -1520,7 +1523,7 @@ fn create_imps(cx: &CrateContextList) {
     }
 }
 
-struct ValueIter {
+pub struct ValueIter {
     cur: ValueRef,
     step: unsafe extern "C" fn(ValueRef) -> ValueRef,
 }
@@ -1539,7 +1542,7 @@ impl Iterator for ValueIter {
     }
 }
 
-fn iter_globals(llmod: llvm::ModuleRef) -> ValueIter {
+pub fn iter_globals(llmod: llvm::ModuleRef) -> ValueIter {
     unsafe {
         ValueIter {
             cur: llvm::LLVMGetFirstGlobal(llmod),
@@ -1548,7 +1551,7 @@ fn iter_globals(llmod: llvm::ModuleRef) -> ValueIter {
     }
 }
 
-fn iter_functions(llmod: llvm::ModuleRef) -> ValueIter {
+pub fn iter_functions(llmod: llvm::ModuleRef) -> ValueIter {
     unsafe {
         ValueIter {
             cur: llvm::LLVMGetFirstFunction(llmod),
@@ -1605,7 +1608,8 @@ pub fn filter_reachable_ids(tcx: TyCtxt, reachable: NodeSet) -> NodeSet {
 pub fn trans_crate<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
                              mir_map: &MirMap<'tcx>,
                              analysis: ty::CrateAnalysis,
-                             incremental_hashes_map: &IncrementalHashesMap)
+                             incremental_hashes_map: &IncrementalHashesMap,
+                             output_filenames: &OutputFilenames)
                              -> CrateTranslation {
     let _task = tcx.dep_graph.in_task(DepNode::TransCrate);
 
@@ -1632,7 +1636,8 @@ pub fn trans_crate<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
                                              Sha256::new(),
                                              link_meta.clone(),
                                              reachable,
-                                             check_overflow);
+                                             check_overflow,
+                                             output_filenames);
     // Translate the metadata.
     let metadata = time(tcx.sess.time_passes(), "write metadata", || {
         write_metadata(&shared_ccx, shared_ccx.reachable())
@@ -1648,6 +1653,21 @@ pub fn trans_crate<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
     };
     let no_builtins = attr::contains_name(&krate.attrs, "no_builtins");
 
+    // Skip everything but the metadata if we're only emitting metadata, no
+    // translation items will be needed.
+    if !tcx.sess.opts.output_types.should_trans() {
+        let linker_info = LinkerInfo::new(&shared_ccx, &[]);
+        return CrateTranslation {
+            modules: vec![],
+            metadata_module: metadata_module,
+            link: link_meta,
+            metadata: metadata,
+            reachable: vec![],
+            no_builtins: no_builtins,
+            linker_info: linker_info
+        };
+    }
+
     // Run the translation item collector and partition the collected items into
     // codegen units.
     let (codegen_units, symbol_map) = collect_and_partition_translation_items(&shared_ccx);
@@ -1768,6 +1788,10 @@ pub fn trans_crate<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
         }
     }
 
+    if tcx.sess.opts.debugging_opts.print_type_sizes {
+        gather_type_sizes(tcx);
+    }
+
     let sess = shared_ccx.sess();
     let mut reachable_symbols = shared_ccx.reachable().iter().map(|&id| {
         let def_id = shared_ccx.tcx().map.local_def_id(id);
@@ -1826,6 +1850,129 @@ pub fn trans_crate<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
     }
 }
 
+/// Record the layout of each struct, enum and closure type laid out during
+/// compilation, for `-Z print-type-sizes` to print once trans is done.
+fn gather_type_sizes<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>) {
+    // Computing the field layouts below may add to the cache.
+    let layouts = tcx.layout_cache.borrow().iter().map(|(&ty, &layout)| {
+        (ty, layout)
+    }).collect::<Vec<_>>();
+
+    let dl = &tcx.data_layout;
+    tcx.normalizing_infer_ctxt(Reveal::All).enter(|infcx| {
+        // The fields laid out in `st`, starting at its `first`th field.
+        let fields_info = |st: &layout::Struct, first: usize, fields: Vec<(String, Ty<'tcx>)>| {
+            fields.into_iter().enumerate().map(|(i, (name, ty))| {
+                let layout = ty.layout(&infcx).unwrap();
+                FieldInfo {
+                    name: name,
+                    offset: st.field_offset(first + i).bytes(),
+                    size: layout.size(dl).bytes(),
+                    align: layout.align(dl).abi()
+                }
+            }).collect::<Vec<_>>()
+        };
+
+        for (ty, layout) in layouts {
+            if layout.is_unsized() {
+                continue;
+            }
+
+            let (kind, variants) = match ty.sty {
+                ty::TyStruct(def, substs) => {
+                    let fields = def.struct_variant().fields.iter().map(|field| {
+                        (field.name.to_string(), field.ty(tcx, substs))
+                    }).collect();
+                    (DataTypeKind::Struct, vec![(None, fields)])
+                }
+                ty::TyClosure(_, ty::ClosureSubsts { upvar_tys, .. }) => {
+                    let fields = upvar_tys.iter().enumerate().map(|(i, &ty)| {
+                        (i.to_string(), ty)
+                    }).collect();
+                    (DataTypeKind::Closure, vec![(None, fields)])
+                }
+                ty::TyEnum(def, substs) => {
+                    (DataTypeKind::Enum, def.variants.iter().map(|v| {
+                        let fields = v.fields.iter().map(|field| {
+                            (field.name.to_string(), field.ty(tcx, substs))
+                        }).collect();
+                        (Some(v.name.to_string()), fields)
+                    }).collect::<Vec<_>>())
+                }
+                _ => continue
+            };
+
+            let (discr_size, variants) = match *layout {
+                layout::Univariant { ref variant, .. } => {
+                    (None, variants.into_iter().map(|(name, fields)| {
+                        VariantInfo {
+                            name: name,
+                            size: variant.min_size().bytes(),
+                            fields: fields_info(variant, 0, fields)
+                        }
+                    }).collect())
+                }
+                layout::CEnum { discr, .. } => {
+                    (Some(layout::Int(discr).size(dl).bytes()),
+                     variants.into_iter().map(|(name, _)| {
+                        VariantInfo { name: name, size: 0, fields: vec![] }
+                    }).collect())
+                }
+                layout::General { discr, variants: ref structs, .. } => {
+                    let discr_size = layout::Int(discr).size(dl).bytes();
+                    let variants = variants.into_iter().zip(structs);
+                    (Some(discr_size), variants.map(|((name, fields), st)| {
+                        VariantInfo {
+                            name: name,
+                            size: st.min_size().bytes() - discr_size,
+                            fields: fields_info(st, 1, fields)
+                        }
+                    }).collect())
+                }
+                layout::RawNullablePointer { nndiscr, value } => {
+                    (None, variants.into_iter().enumerate().map(|(i, (name, fields))| {
+                        if i as u64 != nndiscr {
+                            return VariantInfo { name: name, size: 0, fields: vec![] };
+                        }
+                        let fields = fields.into_iter().map(|(name, _)| {
+                            FieldInfo {
+                                name: name,
+                                offset: 0,
+                                size: value.size(dl).bytes(),
+                                align: value.align(dl).abi()
+                            }
+                        }).collect();
+                        VariantInfo { name: name, size: value.size(dl).bytes(), fields: fields }
+                    }).collect())
+                }
+                layout::StructWrappedNullablePointer { nndiscr, ref nonnull, .. } |
+                layout::NicheFilling { dataful: nndiscr, variant: ref nonnull, .. } => {
+                    (None, variants.into_iter().enumerate().map(|(i, (name, fields))| {
+                        if i as u64 != nndiscr {
+                            return VariantInfo { name: name, size: 0, fields: vec![] };
+                        }
+                        VariantInfo {
+                            name: name,
+                            size: nonnull.min_size().bytes(),
+                            fields: fields_info(nonnull, 0, fields)
+                        }
+                    }).collect())
+                }
+                _ => continue
+            };
+
+            tcx.sess.code_stats.borrow_mut().record_type_size(TypeSizeInfo {
+                kind: kind,
+                type_description: ty.to_string(),
+                align: layout.align(dl).abi(),
+                overall_size: layout.size(dl).bytes(),
+                discr_size: discr_size,
+                variants: variants
+            });
+        }
+    });
+}
+
 /// For each CGU, identify if we can reuse an existing object file (or
 /// maybe other context).
 fn trans_reuse_previous_work_products(tcx: TyCtxt,
@@ -1882,7 +2029,7 @@ fn collect_and_partition_translation_items<'a, 'tcx>(scx: &SharedCrateContext<'a
         None => TransItemCollectionMode::Lazy
     };
 
-    let (items, inlining_map) =
+    let (items, inlining_map, size_estimates) =
         time(time_passes, "translation item collection", || {
             collector::collect_crate_translation_items(&scx, collection_mode)
     });
@@ -1892,7 +2039,7 @@ fn collect_and_partition_translation_items<'a, 'tcx>(scx: &SharedCrateContext<'a
     let strategy = if scx.sess().opts.debugging_opts.incremental.is_some() {
         PartitioningStrategy::PerModule
     } else {
-        PartitioningStrategy::FixedUnitCount(scx.sess().opts.cg.codegen_units)
+        PartitioningStrategy::FixedUnitCount(scx.sess().opts.codegen_units())
     };
 
     let codegen_units = time(time_passes, "codegen unit partitioning", || {
@@ -1900,10 +2047,11 @@ fn collect_and_partition_translation_items<'a, 'tcx>(scx: &SharedCrateContext<'a
                                 items.iter().cloned(),
                                 strategy,
                                 &inlining_map,
+                                &size_estimates,
                                 scx.reachable())
     });
 
-    assert!(scx.tcx().sess.opts.cg.codegen_units == codegen_units.len() ||
+    assert!(scx.tcx().sess.opts.codegen_units() == codegen_units.len() ||
             scx.tcx().sess.opts.debugging_opts.incremental.is_some());
 
     {
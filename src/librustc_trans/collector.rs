@@ -270,10 +270,15 @@ impl<'tcx> InliningMap<'tcx> {
     }
 }
 
+/// Estimates of how much code each translation item will turn into, for the
+/// partitioner to balance codegen units by.
+pub type SizeEstimates<'tcx> = FnvHashMap<TransItem<'tcx>, usize>;
+
 pub fn collect_crate_translation_items<'a, 'tcx>(scx: &SharedCrateContext<'a, 'tcx>,
                                                  mode: TransItemCollectionMode)
                                                  -> (FnvHashSet<TransItem<'tcx>>,
-                                                     InliningMap<'tcx>) {
+                                                     InliningMap<'tcx>,
+                                                     SizeEstimates<'tcx>) {
     // We are not tracking dependencies of this pass as it has to be re-executed
     // every time no matter what.
     scx.tcx().dep_graph.with_ignore(|| {
@@ -283,16 +288,18 @@ pub fn collect_crate_translation_items<'a, 'tcx>(scx: &SharedCrateContext<'a, 't
         let mut visited = FnvHashSet();
         let mut recursion_depths = DefIdMap();
         let mut inlining_map = InliningMap::new();
+        let mut size_estimates = FnvHashMap();
 
         for root in roots {
             collect_items_rec(scx,
                               root,
                               &mut visited,
                               &mut recursion_depths,
-                              &mut inlining_map);
+                              &mut inlining_map,
+                              &mut size_estimates);
         }
 
-        (visited, inlining_map)
+        (visited, inlining_map, size_estimates)
     })
 }
 
@@ -323,7 +330,8 @@ fn collect_items_rec<'a, 'tcx: 'a>(scx: &SharedCrateContext<'a, 'tcx>,
                                    starting_point: TransItem<'tcx>,
                                    visited: &mut FnvHashSet<TransItem<'tcx>>,
                                    recursion_depths: &mut DefIdMap<usize>,
-                                   inlining_map: &mut InliningMap<'tcx>) {
+                                   inlining_map: &mut InliningMap<'tcx>,
+                                   size_estimates: &mut SizeEstimates<'tcx>) {
     if !visited.insert(starting_point.clone()) {
         // We've been here already, no need to search again.
         return;
@@ -332,11 +340,15 @@ fn collect_items_rec<'a, 'tcx: 'a>(scx: &SharedCrateContext<'a, 'tcx>,
 
     let mut neighbors = Vec::new();
     let recursion_depth_reset;
+    let size_estimate;
 
     match starting_point {
         TransItem::DropGlue(t) => {
             find_drop_glue_neighbors(scx, t, &mut neighbors);
             recursion_depth_reset = None;
+
+            // Drop glue is mostly a call per field or element to drop.
+            size_estimate = 1 + neighbors.len();
         }
         TransItem::Static(node_id) => {
             let def_id = scx.tcx().map.local_def_id(node_id);
@@ -360,6 +372,7 @@ fn collect_items_rec<'a, 'tcx: 'a>(scx: &SharedCrateContext<'a, 'tcx>,
             };
 
             visit_mir_and_promoted(visitor, &mir);
+            size_estimate = mir_size_estimate(&mir);
         }
         TransItem::Fn(instance) => {
             // Keep track of the monomorphization recursion depth
@@ -380,13 +393,17 @@ fn collect_items_rec<'a, 'tcx: 'a>(scx: &SharedCrateContext<'a, 'tcx>,
             };
 
             visit_mir_and_promoted(visitor, &mir);
+            size_estimate = mir_size_estimate(&mir);
         }
     }
 
+    size_estimates.insert(starting_point, size_estimate);
+
     record_inlining_canditates(scx.tcx(), starting_point, &neighbors[..], inlining_map);
 
     for neighbour in neighbors {
-        collect_items_rec(scx, neighbour, visited, recursion_depths, inlining_map);
+        collect_items_rec(scx, neighbour, visited, recursion_depths, inlining_map,
+                          size_estimates);
     }
 
     if let Some((def_id, depth)) = recursion_depth_reset {
@@ -1299,3 +1316,12 @@ fn visit_mir_and_promoted<'tcx, V: MirVisitor<'tcx>>(mut visitor: V, mir: &mir::
         visitor.visit_mir(promoted);
     }
 }
+
+// The number of MIR statements and terminators in `mir` and its promoted
+// constants, as a rough measure of how much LLVM IR it will turn into.
+fn mir_size_estimate(mir: &mir::Mir) -> usize {
+    let size = mir.basic_blocks().iter().map(|block| {
+        block.statements.len() + 1
+    }).sum::<usize>();
+    size + mir.promoted.iter().map(mir_size_estimate).sum::<usize>()
+}
@@ -138,7 +138,8 @@ pub fn type_is_imm_pair<'a, 'tcx>(ccx: &CrateContext<'a, 'tcx>, ty: Ty<'tcx>)
     match *layout {
         Layout::FatPointer { .. } => true,
         Layout::Univariant { ref variant, .. } => {
-            // There must be only 2 fields.
+            // There must be only 2 fields, which are never reordered,
+            // as that couldn't make the pair any smaller.
             if variant.offset_after_field.len() != 2 {
                 return false;
             }
@@ -32,7 +32,7 @@ use trans_item::TransItem;
 use type_::{Type, TypeNames};
 use rustc::ty::subst::Substs;
 use rustc::ty::{self, Ty, TyCtxt};
-use session::config::NoDebugInfo;
+use session::config::{NoDebugInfo, OutputFilenames};
 use session::Session;
 use session::config;
 use symbol_map::SymbolMap;
@@ -79,6 +79,7 @@ pub struct SharedCrateContext<'a, 'tcx: 'a> {
     check_overflow: bool,
     mir_map: &'a MirMap<'tcx>,
     mir_cache: RefCell<DepTrackingMap<MirCache<'tcx>>>,
+    output_filenames: &'a OutputFilenames,
 
     use_dll_storage_attrs: bool,
 
@@ -420,7 +421,8 @@ impl<'b, 'tcx> SharedCrateContext<'b, 'tcx> {
                symbol_hasher: Sha256,
                link_meta: LinkMeta,
                reachable: NodeSet,
-               check_overflow: bool)
+               check_overflow: bool,
+               output_filenames: &'b OutputFilenames)
                -> SharedCrateContext<'b, 'tcx> {
         let (metadata_llcx, metadata_llmod) = unsafe {
             create_context_and_module(&tcx.sess, "metadata")
@@ -481,6 +483,7 @@ impl<'b, 'tcx> SharedCrateContext<'b, 'tcx> {
             tcx: tcx,
             mir_map: mir_map,
             mir_cache: RefCell::new(DepTrackingMap::new(tcx.dep_graph.clone())),
+            output_filenames: output_filenames,
             stats: Stats {
                 n_glues_created: Cell::new(0),
                 n_null_glues: Cell::new(0),
@@ -523,6 +526,10 @@ impl<'b, 'tcx> SharedCrateContext<'b, 'tcx> {
         &self.link_meta
     }
 
+    pub fn output_filenames(&self) -> &OutputFilenames {
+        self.output_filenames
+    }
+
     pub fn tcx<'a>(&'a self) -> TyCtxt<'a, 'tcx, 'tcx> {
         self.tcx
     }
@@ -607,7 +614,10 @@ impl<'tcx> LocalCrateContext<'tcx> {
 
             let dbg_cx = if shared.tcx.sess.opts.debuginfo != NoDebugInfo {
                 let dctx = debuginfo::CrateDebugContext::new(llmod);
-                debuginfo::metadata::compile_unit_metadata(shared, &dctx, shared.tcx.sess);
+                debuginfo::metadata::compile_unit_metadata(shared,
+                                                           &dctx,
+                                                           codegen_unit.name(),
+                                                           shared.tcx.sess);
                 Some(dctx)
             } else {
                 None
@@ -536,14 +536,14 @@ fn vec_slice_metadata<'a, 'tcx>(cx: &CrateContext<'a, 'tcx>,
             name: "data_ptr".to_string(),
             llvm_type: member_llvm_types[0],
             type_metadata: element_type_metadata,
-            offset: ComputedMemberOffset,
+            offset: ComputedMemberOffset { llvm_index: 0 },
             flags: FLAGS_NONE
         },
         MemberDescription {
             name: "length".to_string(),
             llvm_type: member_llvm_types[1],
             type_metadata: type_metadata(cx, cx.tcx().types.usize, span),
-            offset: ComputedMemberOffset,
+            offset: ComputedMemberOffset { llvm_index: 1 },
             flags: FLAGS_NONE
         },
     ];
@@ -940,6 +940,7 @@ fn pointer_type_metadata<'a, 'tcx>(cx: &CrateContext<'a, 'tcx>,
 
 pub fn compile_unit_metadata(scc: &SharedCrateContext,
                              debug_context: &CrateDebugContext,
+                             codegen_unit_name: &str,
                              sess: &Session)
                              -> DIDescriptor {
     let work_dir = &sess.working_dir;
@@ -972,7 +973,13 @@ pub fn compile_unit_metadata(scc: &SharedCrateContext,
     let work_dir = path2cstr(&work_dir);
     let producer = CString::new(producer).unwrap();
     let flags = "\0";
-    let split_name = "\0";
+    // With split DWARF, the skeleton compile unit left in the object file
+    // names the `.dwo` file the debugger will find the rest of it in.
+    let split_name = if sess.opts.split_dwarf() {
+        path2cstr(&scc.output_filenames().temp_path_ext("dwo", Some(codegen_unit_name)))
+    } else {
+        CString::new("").unwrap()
+    };
     return unsafe {
         llvm::LLVMRustDIBuilderCreateCompileUnit(
             debug_context.builder,
@@ -983,7 +990,7 @@ pub fn compile_unit_metadata(scc: &SharedCrateContext,
             sess.opts.optimize != config::OptLevel::No,
             flags.as_ptr() as *const _,
             0,
-            split_name.as_ptr() as *const _)
+            split_name.as_ptr())
     };
 
     fn fallback_path(scc: &SharedCrateContext) -> CString {
@@ -1008,8 +1015,9 @@ impl MetadataCreationResult {
 #[derive(Debug)]
 enum MemberOffset {
     FixedMemberOffset { bytes: usize },
-    // For ComputedMemberOffset, the offset is read from the llvm type definition.
-    ComputedMemberOffset
+    // For ComputedMemberOffset, the offset is read from the llvm type definition,
+    // at the given element index, which may differ from the field's source order.
+    ComputedMemberOffset { llvm_index: usize }
 }
 
 // Description of a type member, which can either be a regular field (as in
@@ -1062,6 +1070,7 @@ impl<'tcx> MemberDescriptionFactory<'tcx> {
 struct StructMemberDescriptionFactory<'tcx> {
     variant: ty::VariantDef<'tcx>,
     substs: &'tcx Substs<'tcx>,
+    memory_index: Vec<u32>,
     is_simd: bool,
     span: Span,
 }
@@ -1096,7 +1105,7 @@ impl<'tcx> StructMemberDescriptionFactory<'tcx> {
             let offset = if self.is_simd {
                 FixedMemberOffset { bytes: i * field_size.unwrap() }
             } else {
-                ComputedMemberOffset
+                ComputedMemberOffset { llvm_index: self.memory_index[i] as usize }
             };
 
             MemberDescription {
@@ -1141,6 +1150,7 @@ fn prepare_struct_metadata<'a, 'tcx>(cx: &CrateContext<'a, 'tcx>,
         StructMDF(StructMemberDescriptionFactory {
             variant: variant,
             substs: substs,
+            memory_index: univariant_memory_index(cx, struct_type),
             is_simd: struct_type.is_simd(),
             span: span,
         })
@@ -1148,6 +1158,16 @@ fn prepare_struct_metadata<'a, 'tcx>(cx: &CrateContext<'a, 'tcx>,
 }
 
 
+/// The LLVM element index of each field of a struct, tuple or closure type,
+/// by source order index.
+fn univariant_memory_index<'a, 'tcx>(cx: &CrateContext<'a, 'tcx>, t: Ty<'tcx>) -> Vec<u32> {
+    match *adt::represent_type(cx, t) {
+        adt::Univariant(ref st) => st.memory_index.clone(),
+        ref repr => bug!("debuginfo: non-univariant representation {:?} of `{}`", repr, t)
+    }
+}
+
+
 //=-----------------------------------------------------------------------------
 // Tuples
 //=-----------------------------------------------------------------------------
@@ -1155,6 +1175,7 @@ fn prepare_struct_metadata<'a, 'tcx>(cx: &CrateContext<'a, 'tcx>,
 // Creates MemberDescriptions for the fields of a tuple
 struct TupleMemberDescriptionFactory<'tcx> {
     component_types: Vec<Ty<'tcx>>,
+    memory_index: Vec<u32>,
     span: Span,
 }
 
@@ -1169,7 +1190,7 @@ impl<'tcx> TupleMemberDescriptionFactory<'tcx> {
                 name: format!("__{}", i),
                 llvm_type: type_of::type_of(cx, component_type),
                 type_metadata: type_metadata(cx, component_type, self.span),
-                offset: ComputedMemberOffset,
+                offset: ComputedMemberOffset { llvm_index: self.memory_index[i] as usize },
                 flags: FLAGS_NONE,
             }
         }).collect()
@@ -1197,6 +1218,7 @@ fn prepare_tuple_metadata<'a, 'tcx>(cx: &CrateContext<'a, 'tcx>,
         tuple_llvm_type,
         TupleMDF(TupleMemberDescriptionFactory {
             component_types: component_types.to_vec(),
+            memory_index: univariant_memory_index(cx, tuple_type),
             span: span,
         })
     )
@@ -1392,8 +1414,8 @@ impl<'tcx> EnumMemberDescriptionFactory<'tcx> {
                 // member's name.
                 let null_variant_index = (1 - nndiscr.0) as usize;
                 let null_variant_name = adt.variants[null_variant_index].name;
+                let discrfield = source_order_path(cx, struct_def, &discrfield[1..]);
                 let discrfield = discrfield.iter()
-                                           .skip(1)
                                            .map(|x| x.to_string())
                                            .collect::<Vec<_>>().join("$");
                 let union_member_name = format!("RUST$ENCODED$ENUM${}${}",
@@ -1411,6 +1433,56 @@ impl<'tcx> EnumMemberDescriptionFactory<'tcx> {
                     }
                 ]
             },
+            adt::NicheFilling { dataful, ref cases, ref niche, niche_start,
+                                niche_variants: (first, last), .. } => {
+                // Create a description of the dataful variant
+                let (variant_type_metadata, variant_llvm_type, member_description_factory) =
+                    describe_enum_variant(cx,
+                                          self.enum_type,
+                                          &cases[dataful.0 as usize],
+                                          &adt.variants[dataful.0 as usize],
+                                          OptimizedDiscriminant,
+                                          self.containing_scope,
+                                          self.span);
+
+                let variant_member_descriptions =
+                    member_description_factory.create_member_descriptions(cx);
+
+                set_members_of_composite_type(cx,
+                                              variant_type_metadata,
+                                              variant_llvm_type,
+                                              &variant_member_descriptions[..]);
+
+                // Encode the path to the niche, the value it starts at and
+                // the variants stored there in the union member's name,
+                // leaving the name of the dataful variant empty.
+                let niche = source_order_path(cx, &cases[dataful.0 as usize], &niche[1..]);
+                let niche = niche.iter()
+                                 .map(|x| x.to_string())
+                                 .collect::<Vec<_>>().join("$");
+                let niche_variant_names = (first.0..last.0 + 1).map(|i| {
+                    if i == dataful.0 {
+                        String::new()
+                    } else {
+                        adt.variants[i as usize].name.to_string()
+                    }
+                }).collect::<Vec<_>>().join("$");
+                let union_member_name = format!("RUST$ENCODED$NICHE${}${}${}",
+                                                niche,
+                                                niche_start,
+                                                niche_variant_names);
+
+                // Create the (singleton) list of descriptions of union members.
+                vec![
+                    MemberDescription {
+                        name: union_member_name,
+                        llvm_type: variant_llvm_type,
+                        type_metadata: variant_type_metadata,
+                        offset: FixedMemberOffset { bytes: 0 },
+                        flags: FLAGS_NONE
+                    }
+                ]
+            },
             adt::CEnum(..) => span_bug!(self.span, "This should be unreachable.")
         }
     }
@@ -1419,6 +1491,7 @@ impl<'tcx> EnumMemberDescriptionFactory<'tcx> {
 // Creates MemberDescriptions for the fields of a single enum variant.
 struct VariantMemberDescriptionFactory<'tcx> {
     args: Vec<(String, Ty<'tcx>)>,
+    memory_index: Vec<u32>,
     discriminant_type_metadata: Option<DIType>,
     span: Span,
 }
@@ -1434,13 +1507,54 @@ impl<'tcx> VariantMemberDescriptionFactory<'tcx> {
                     Some(metadata) if i == 0 => metadata,
                     _ => type_metadata(cx, ty, self.span)
                 },
-                offset: ComputedMemberOffset,
+                offset: ComputedMemberOffset { llvm_index: self.memory_index[i] as usize },
                 flags: FLAGS_NONE
             }
         }).collect()
     }
 }
 
+/// Translate a path of LLVM element indices into the struct `st`, as used to
+/// find the discriminant of an optimized enum, into the source order field
+/// indices the debugger sees. Fields of other enums are left as they are.
+fn source_order_path<'a, 'tcx>(cx: &CrateContext<'a, 'tcx>,
+                               st: &adt::Struct<'tcx>,
+                               path: &[usize])
+                               -> Vec<usize> {
+    let (llix, mut rest) = match path.split_first() {
+        Some((&llix, rest)) => (llix, rest),
+        None => return vec![]
+    };
+    let i = st.memory_index.iter().position(|&m| m as usize == llix).unwrap();
+    let mut source_path = vec![i];
+
+    // Array elements are in order either way.
+    let mut ty = st.fields[i];
+    while let ty::TyArray(element, _) = ty.sty {
+        match rest.split_first() {
+            Some((&j, elements_rest)) => {
+                source_path.push(j);
+                ty = element;
+                rest = elements_rest;
+            }
+            None => break
+        }
+    }
+
+    match ty.sty {
+        ty::TyStruct(..) | ty::TyTuple(..) | ty::TyClosure(..) | ty::TyEnum(..) => {
+            match *adt::represent_type(cx, ty) {
+                adt::Univariant(ref inner) => {
+                    source_path.extend(source_order_path(cx, inner, rest));
+                }
+                _ => source_path.extend_from_slice(rest)
+            }
+        }
+        _ => source_path.extend_from_slice(rest)
+    }
+    source_path
+}
+
 #[derive(Copy, Clone)]
 enum EnumDiscriminantInfo {
     RegularDiscriminant(DIType),
@@ -1461,7 +1575,7 @@ fn describe_enum_variant<'a, 'tcx>(cx: &CrateContext<'a, 'tcx>,
                                    span: Span)
                                    -> (DICompositeType, Type, MemberDescriptionFactory<'tcx>) {
     let variant_llvm_type =
-        Type::struct_(cx, &struct_def.fields
+        Type::struct_(cx, &adt::in_memory_order(&struct_def.memory_index, &struct_def.fields)
                                     .iter()
                                     .map(|&t| type_of::type_of(cx, t))
                                     .collect::<Vec<_>>()
@@ -1516,6 +1630,7 @@ fn describe_enum_variant<'a, 'tcx>(cx: &CrateContext<'a, 'tcx>,
     let member_description_factory =
         VariantMDF(VariantMemberDescriptionFactory {
             args: args,
+            memory_index: struct_def.memory_index.clone(),
             discriminant_type_metadata: match discriminant_info {
                 RegularDiscriminant(discriminant_type_metadata) => {
                     Some(discriminant_type_metadata)
@@ -1609,6 +1724,7 @@ fn prepare_enum_metadata<'a, 'tcx>(cx: &CrateContext<'a, 'tcx>,
         },
         adt::RawNullablePointer { .. }           |
         adt::StructWrappedNullablePointer { .. } |
+        adt::NicheFilling { .. }                 |
         adt::Univariant(..)                      => None,
         adt::General(inttype, _) => Some(discriminant_type_metadata(inttype)),
     };
@@ -1715,12 +1831,13 @@ fn set_members_of_composite_type(cx: &CrateContext,
 
     let member_metadata: Vec<DIDescriptor> = member_descriptions
         .iter()
-        .enumerate()
-        .map(|(i, member_description)| {
+        .map(|member_description| {
             let (member_size, member_align) = size_and_align_of(cx, member_description.llvm_type);
             let member_offset = match member_description.offset {
                 FixedMemberOffset { bytes } => bytes as u64,
-                ComputedMemberOffset => machine::llelement_offset(cx, composite_llvm_type, i)
+                ComputedMemberOffset { llvm_index } => {
+                    machine::llelement_offset(cx, composite_llvm_type, llvm_index)
+                }
             };
 
             let member_name = member_description.name.as_bytes();
@@ -690,9 +690,11 @@ pub fn trans_intrinsic_call<'a, 'blk, 'tcx>(mut bcx: Block<'blk, 'tcx>,
                     // the output is a tuple so we need to munge it properly
                     assert!(!flatten);
 
+                    let repr = adt::represent_type(ccx, ret_ty);
+                    let result = adt::MaybeSizedValue::sized(llresult);
                     for i in 0..elems.len() {
                         let val = ExtractValue(bcx, val, i);
-                        Store(bcx, val, StructGEP(bcx, llresult, i));
+                        Store(bcx, val, adt::trans_field_ptr(bcx, &repr, result, Disr(0), i));
                     }
                     C_nil(ccx)
                 }
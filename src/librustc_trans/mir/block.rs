@@ -9,7 +9,6 @@
 // except according to those terms.
 
 use llvm::{self, ValueRef};
-use rustc_const_eval::{ErrKind, ConstEvalErr, note_const_eval_err};
 use rustc::middle::lang_items;
 use rustc::ty;
 use rustc::mir::repr as mir;
@@ -309,20 +308,11 @@ impl<'bcx, 'tcx> MirContext<'bcx, 'tcx> {
                 let line = C_u32(bcx.ccx(), loc.line as u32);
 
                 // Put together the arguments to the panic entry point.
-                let (lang_item, args, const_err) = match *msg {
+                let (lang_item, args) = match *msg {
                     mir::AssertMessage::BoundsCheck { ref len, ref index } => {
                         let len = self.trans_operand(&mut bcx, len).immediate();
                         let index = self.trans_operand(&mut bcx, index).immediate();
 
-                        let const_err = common::const_to_opt_uint(len).and_then(|len| {
-                            common::const_to_opt_uint(index).map(|index| {
-                                ErrKind::IndexOutOfBounds {
-                                    len: len,
-                                    index: index
-                                }
-                            })
-                        });
-
                         let file_line = C_struct(bcx.ccx(), &[filename, line], false);
                         let align = llalign_of_min(bcx.ccx(), common::val_ty(file_line));
                         let file_line = consts::addr_of(bcx.ccx(),
@@ -330,8 +320,7 @@ impl<'bcx, 'tcx> MirContext<'bcx, 'tcx> {
                                                         align,
                                                         "panic_bounds_check_loc");
                         (lang_items::PanicBoundsCheckFnLangItem,
-                         vec![file_line, index, len],
-                         const_err)
+                         vec![file_line, index, len])
                     }
                     mir::AssertMessage::Math(ref err) => {
                         let msg_str = token::intern_and_get_ident(err.description());
@@ -344,24 +333,10 @@ impl<'bcx, 'tcx> MirContext<'bcx, 'tcx> {
                                                             msg_file_line,
                                                             align,
                                                             "panic_loc");
-                        (lang_items::PanicFnLangItem,
-                         vec![msg_file_line],
-                         Some(ErrKind::Math(err.clone())))
+                        (lang_items::PanicFnLangItem, vec![msg_file_line])
                     }
                 };
 
-                // If we know we always panic, and the error message
-                // is also constant, then we can produce a warning.
-                if const_cond == Some(!expected) {
-                    if let Some(err) = const_err {
-                        let err = ConstEvalErr{ span: span, kind: err };
-                        let mut diag = bcx.tcx().sess.struct_span_warn(
-                            span, "this expression will panic at run-time");
-                        note_const_eval_err(bcx.tcx(), &err, span, "expression", &mut diag);
-                        diag.emit();
-                    }
-                }
-
                 // Obtain the panic entry point.
                 let def_id = common::langcall(bcx.tcx(), Some(span), "", lang_item);
                 let callee = Callee::def(bcx.ccx(), def_id,
@@ -703,8 +678,10 @@ impl<'bcx, 'tcx> MirContext<'bcx, 'tcx> {
 
             }
             Immediate(llval) => {
+                let repr = adt::represent_type(bcx.ccx(), tuple.ty);
                 for (n, &ty) in arg_types.iter().enumerate() {
-                    let mut elem = bcx.extract_value(llval, n);
+                    let mut elem = bcx.extract_value(llval,
+                                                     adt::univariant_llfield_index(&repr, n));
                     // Truncate bools to i1, if needed
                     if ty.is_bool() && common::val_ty(elem) != Type::i1(bcx.ccx()) {
                         elem = bcx.trunc(elem, Type::i1(bcx.ccx()));
@@ -11,6 +11,7 @@
 use llvm::{self, ValueRef};
 use rustc::middle::const_val::ConstVal;
 use rustc_const_eval::{ErrKind, ConstEvalErr, report_const_eval_err};
+use rustc_const_eval::interpret::{self, EvalContext};
 use rustc_const_math::ConstInt::*;
 use rustc_const_math::ConstFloat::*;
 use rustc_const_math::{ConstInt, ConstIsize, ConstUsize, ConstMathErr};
@@ -28,7 +29,7 @@ use callee::Callee;
 use common::{self, BlockAndBuilder, CrateContext, const_get_elt, val_ty};
 use common::{C_array, C_bool, C_bytes, C_floating_f64, C_integral};
 use common::{C_null, C_struct, C_str_slice, C_undef, C_uint};
-use common::{const_to_opt_int, const_to_opt_uint};
+use common::{const_to_opt_int, const_to_opt_uint, const_to_uint};
 use consts;
 use monomorphize::{self, Instance};
 use type_of;
@@ -38,6 +39,7 @@ use value::Value;
 use syntax::ast;
 use syntax_pos::{Span, DUMMY_SP};
 
+use std::char;
 use std::ptr;
 
 use super::operand::{OperandRef, OperandValue};
@@ -252,9 +254,33 @@ impl<'a, 'tcx> MirConstContext<'a, 'tcx> {
         let mir = ccx.get_mir(instance.def).unwrap_or_else(|| {
             bug!("missing constant MIR for {}", instance)
         });
+        if !interpret::is_straight_line(&mir) {
+            return MirConstContext::interpret(ccx, instance, &mir, args);
+        }
         MirConstContext::new(ccx, &mir, instance.substs, args).trans()
     }
 
+    /// Evaluates a `const fn` with control flow, which can't be translated
+    /// block by block, with the MIR interpreter.
+    fn interpret(ccx: &'a CrateContext<'a, 'tcx>,
+                 instance: Instance<'tcx>,
+                 mir: &mir::Mir<'tcx>,
+                 args: IndexVec<mir::Arg, Const<'tcx>>)
+                 -> Result<Const<'tcx>, ConstEvalErr> {
+        let tcx = ccx.tcx();
+        let mut ecx = EvalContext::new(tcx, Some(ccx.shared().mir_map()), mir.span);
+        let mut arg_values = Vec::with_capacity(args.len());
+        for arg in args {
+            let value = const_to_value(ccx, &mut ecx.memory, arg.llval, arg.ty, mir.span)?;
+            arg_values.push(value);
+        }
+        let value = ecx.call_const_fn(instance.def, instance.substs, arg_values)?;
+
+        let return_ty = monomorphize::apply_param_substs(tcx, instance.substs, &mir.return_ty);
+        let llval = lower_value(ccx, &ecx, &value, return_ty, mir.span)?;
+        Ok(Const::new(llval, return_ty))
+    }
+
     fn monomorphize<T>(&self, value: &T) -> T
         where T: TransNormalize<'tcx>
     {
@@ -560,7 +586,9 @@ impl<'a, 'tcx> MirConstContext<'a, 'tcx> {
                         C_struct(self.ccx, &fields, false)
                     }
                 } else {
-                    C_struct(self.ccx, &fields, false)
+                    // Tuples and closures may have their fields reordered.
+                    let repr = adt::represent_type(self.ccx, dest_ty);
+                    adt::trans_const(self.ccx, &repr, Disr(0), &fields)
                 };
                 Const::new(val, dest_ty)
             }
@@ -791,6 +819,185 @@ impl<'a, 'tcx> MirConstContext<'a, 'tcx> {
 
 }
 
+/// Converts a constant argument of a `const fn` to a value of the MIR
+/// interpreter, allocating the values its references point to in `memory`.
+fn const_to_value<'a, 'tcx>(ccx: &CrateContext<'a, 'tcx>,
+                            memory: &mut interpret::Memory<'tcx>,
+                            llval: ValueRef,
+                            ty: Ty<'tcx>,
+                            span: Span)
+                            -> Result<interpret::Value<'tcx>, ConstEvalErr> {
+    let tcx = ccx.tcx();
+    let unimplemented = || ConstEvalErr {
+        span: span,
+        kind: ErrKind::UnimplementedConstVal("enum, float, string and function pointer \
+                                              arguments to `const fn`s with control flow")
+    };
+    // Like the dereference of a constant pointer in `const_lvalue`.
+    let pointee = |llptr: ValueRef| {
+        let llptr = ccx.const_unsized().borrow().get(&llptr).map_or(llptr, |&v| v);
+        let llval = unsafe { llvm::LLVMGetInitializer(llptr) };
+        if llval.is_null() {
+            span_bug!(span, "dereference of non-constant pointer `{:?}`", Value(llptr));
+        }
+        llval
+    };
+
+    Ok(match ty.sty {
+        ty::TyBool => interpret::Value::Bool(const_to_uint(llval) != 0),
+        ty::TyChar => {
+            let c = char::from_u32(const_to_uint(llval) as u32);
+            c.map(interpret::Value::Char).ok_or_else(&unimplemented)?
+        }
+        ty::TyInt(_) | ty::TyUint(_) => {
+            let i = to_const_int(llval, ty, tcx);
+            i.map(interpret::Value::Int).ok_or_else(&unimplemented)?
+        }
+        ty::TyStruct(..) | ty::TyTuple(_) => {
+            let field_tys: Vec<Ty<'tcx>> = match ty.sty {
+                ty::TyStruct(adt, substs) => {
+                    adt.struct_variant().fields.iter().map(|f| {
+                        monomorphize::field_ty(tcx, substs, f)
+                    }).collect()
+                }
+                ty::TyTuple(tys) => tys.to_vec(),
+                _ => bug!()
+            };
+            let repr = adt::represent_type(ccx, ty);
+            let mut fields = Vec::with_capacity(field_tys.len());
+            for (i, &field_ty) in field_tys.iter().enumerate() {
+                let llfield = adt::const_get_field(&repr, llval, Disr(0), i);
+                fields.push(const_to_value(ccx, memory, llfield, field_ty, span)?);
+            }
+            interpret::Value::Aggregate(0, fields)
+        }
+        ty::TyArray(elem_ty, len) => {
+            let mut elems = Vec::with_capacity(len);
+            for i in 0..len {
+                let llelem = const_get_elt(llval, &[i as u32]);
+                elems.push(const_to_value(ccx, memory, llelem, elem_ty, span)?);
+            }
+            interpret::Value::Aggregate(0, elems)
+        }
+        ty::TyRef(_, ty::TypeAndMut { ty: pointee_ty, .. }) => {
+            if let ty::TySlice(elem_ty) = pointee_ty.sty {
+                let lldata = const_get_elt(llval, &[abi::FAT_PTR_ADDR as u32]);
+                let len = const_to_uint(const_get_elt(llval, &[abi::FAT_PTR_EXTRA as u32]));
+                let array_ty = tcx.mk_array(elem_ty, len as usize);
+                let array = const_to_value(ccx, memory, pointee(lldata), array_ty, span)?;
+                interpret::Value::Slice(memory.allocate(array), 0, len)
+            } else if common::type_is_sized(tcx, pointee_ty) {
+                let value = const_to_value(ccx, memory, pointee(llval), pointee_ty, span)?;
+                interpret::Value::Ptr(memory.allocate(value))
+            } else {
+                return Err(unimplemented());
+            }
+        }
+        _ => return Err(unimplemented())
+    })
+}
+
+/// Lowers a value computed by the MIR interpreter to an LLVM constant.
+fn lower_value<'a, 'tcx>(ccx: &CrateContext<'a, 'tcx>,
+                         ecx: &EvalContext<'a, 'tcx>,
+                         value: &interpret::Value<'tcx>,
+                         ty: Ty<'tcx>,
+                         span: Span)
+                         -> Result<ValueRef, ConstEvalErr> {
+    use rustc_const_eval::interpret::Value;
+
+    let tcx = ccx.tcx();
+    let wrap = |kind| ConstEvalErr { span: span, kind: kind };
+    let pointee_ty = |ty: Ty<'tcx>| {
+        ty.builtin_deref(true, ty::NoPreference)
+          .unwrap_or_else(|| span_bug!(span, "pointer with non-pointer type `{}`", ty)).ty
+    };
+
+    Ok(match *value {
+        Value::Undef => C_undef(type_of::type_of(ccx, ty)),
+        Value::Bool(_) | Value::Char(_) | Value::Int(_) | Value::Float(_) | Value::Str(_) => {
+            let cv = ecx.to_const_val(value).map_err(&wrap)?;
+            Const::from_constval(ccx, cv, ty).llval
+        }
+        Value::Fn(def_id, substs) => {
+            if let ty::TyFnPtr(_) = ty.sty {
+                Callee::def(ccx, def_id, substs).reify(ccx)
+            } else {
+                C_null(type_of::type_of(ccx, ty))
+            }
+        }
+        Value::Ptr(ref ptr) => {
+            let pointee_ty = pointee_ty(ty);
+            let pointee = ecx.memory.read(ptr).map_err(&wrap)?;
+            let llpointee = lower_value(ccx, ecx, pointee, pointee_ty, span)?;
+            let align = type_of::align_of(ccx, pointee_ty);
+            let llptr = consts::addr_of(ccx, llpointee, align, "const");
+            consts::ptrcast(llptr, type_of::type_of(ccx, pointee_ty).ptr_to())
+        }
+        Value::Slice(ref ptr, start, len) => {
+            let elem_ty = match pointee_ty(ty).sty {
+                ty::TySlice(elem_ty) => elem_ty,
+                _ => span_bug!(span, "slice with non-slice type `{}`", ty)
+            };
+            let elems = match *ecx.memory.read(ptr).map_err(&wrap)? {
+                Value::Aggregate(_, ref elems) => elems,
+                _ => return Err(wrap(ErrKind::InvalidMemoryAccess)),
+            };
+            let mut llelems = Vec::with_capacity(len as usize);
+            for i in start..start + len {
+                let elem = elems.get(i as usize).unwrap_or(&Value::Undef);
+                llelems.push(lower_value(ccx, ecx, elem, elem_ty, span)?);
+            }
+            let llunitty = type_of::type_of(ccx, elem_ty);
+            let llarray = if llelems.iter().all(|&e| val_ty(e) == llunitty) {
+                C_array(llunitty, &llelems)
+            } else {
+                C_struct(ccx, &llelems, false)
+            };
+            let align = type_of::align_of(ccx, elem_ty);
+            let llptr = consts::addr_of(ccx, llarray, align, "const");
+            let llptr = consts::ptrcast(llptr, llunitty.ptr_to());
+            C_struct(ccx, &[llptr, C_uint(ccx, len)], false)
+        }
+        Value::Aggregate(variant, ref fields) => {
+            let field_tys: Vec<Ty<'tcx>> = match ty.sty {
+                ty::TyStruct(adt, substs) | ty::TyEnum(adt, substs) => {
+                    adt.variants[variant].fields.iter().map(|f| {
+                        monomorphize::field_ty(tcx, substs, f)
+                    }).collect()
+                }
+                ty::TyTuple(tys) => tys.to_vec(),
+                ty::TyArray(elem_ty, len) => vec![elem_ty; len],
+                _ => span_bug!(span, "aggregate with non-aggregate type `{}`", ty)
+            };
+            let mut llfields = Vec::with_capacity(field_tys.len());
+            for (i, &field_ty) in field_tys.iter().enumerate() {
+                let field = fields.get(i).unwrap_or(&Value::Undef);
+                llfields.push(lower_value(ccx, ecx, field, field_ty, span)?);
+            }
+
+            match ty.sty {
+                ty::TyStruct(adt, _) | ty::TyEnum(adt, _) => {
+                    let repr = adt::represent_type(ccx, ty);
+                    let disr = Disr::from(adt.variants[variant].disr_val);
+                    adt::trans_const(ccx, &repr, disr, &llfields)
+                }
+                ty::TyArray(elem_ty, _) => {
+                    let llunitty = type_of::type_of(ccx, elem_ty);
+                    // If the array contains enums, an LLVM array won't work.
+                    if llfields.iter().all(|&f| val_ty(f) == llunitty) {
+                        C_array(llunitty, &llfields)
+                    } else {
+                        C_struct(ccx, &llfields, false)
+                    }
+                }
+                // Tuples may have their fields reordered.
+                _ => adt::trans_const(ccx, &adt::represent_type(ccx, ty), Disr(0), &llfields)
+            }
+        }
+    })
+}
+
 fn to_const_int(value: ValueRef, t: Ty, tcx: TyCtxt) -> Option<ConstInt> {
     match t.sty {
         ty::TyInt(int_type) => const_to_opt_int(value).and_then(|input| match int_type {
@@ -14,6 +14,7 @@ use rustc::ty;
 use rustc::mir::repr as mir;
 use rustc::mir::tcx::LvalueTy;
 use session::config::FullDebugInfo;
+use adt;
 use base;
 use common::{self, Block, BlockAndBuilder, CrateContext, FunctionContext, C_null};
 use debuginfo::{self, declare_local, DebugLoc, VariableAccess, VariableKind, FunctionDebugContext};
@@ -361,11 +362,13 @@ fn arg_local_refs<'bcx, 'tcx>(bcx: &BlockAndBuilder<'bcx, 'tcx>,
             };
 
             let lltuplety = type_of::type_of(bcx.ccx(), arg_ty);
+            let repr = adt::represent_type(bcx.ccx(), arg_ty);
             let lltemp = bcx.with_block(|bcx| {
                 base::alloc_ty(bcx, arg_ty, &format!("arg{}", arg_index))
             });
             for (i, &tupled_arg_ty) in tupled_arg_tys.iter().enumerate() {
-                let dst = bcx.struct_gep(lltemp, i);
+                let llix = adt::univariant_llfield_index(&repr, i);
+                let dst = bcx.struct_gep(lltemp, llix);
                 let arg = &fcx.fn_ty.args[idx];
                 idx += 1;
                 if common::type_is_fat_ptr(tcx, tupled_arg_ty) {
@@ -381,7 +384,7 @@ fn arg_local_refs<'bcx, 'tcx>(bcx: &BlockAndBuilder<'bcx, 'tcx>,
 
                 bcx.with_block(|bcx| arg_scope.map(|scope| {
                     let byte_offset_of_var_in_tuple =
-                        machine::llelement_offset(bcx.ccx(), lltuplety, i);
+                        machine::llelement_offset(bcx.ccx(), lltuplety, llix);
 
                     let ops = unsafe {
                         [llvm::LLVMRustDIBuilderCreateOpDeref(),
@@ -505,9 +508,11 @@ fn arg_local_refs<'bcx, 'tcx>(bcx: &BlockAndBuilder<'bcx, 'tcx>,
             };
 
             let llclosurety = type_of::type_of(bcx.ccx(), closure_ty);
+            let closure_repr = adt::represent_type(bcx.ccx(), closure_ty);
             for (i, (decl, ty)) in mir.upvar_decls.iter().zip(upvar_tys).enumerate() {
+                let llix = adt::univariant_llfield_index(&closure_repr, i);
                 let byte_offset_of_var_in_env =
-                    machine::llelement_offset(bcx.ccx(), llclosurety, i);
+                    machine::llelement_offset(bcx.ccx(), llclosurety, llix);
 
                 let ops = unsafe {
                     [llvm::LLVMRustDIBuilderCreateOpDeref(),
@@ -137,15 +137,26 @@ impl<'bcx, 'tcx> MirContext<'bcx, 'tcx> {
                                                                 bcx.monomorphize(&substs));
                         }
 
+                        // Tuples and closures may have their fields reordered,
+                        // so only arrays can be indexed directly.
+                        let dest_ty = dest.ty.to_ty(bcx.tcx());
+                        let repr = match *kind {
+                            mir::AggregateKind::Vec => None,
+                            _ => Some(adt::represent_type(bcx.ccx(), dest_ty)),
+                        };
                         for (i, operand) in operands.iter().enumerate() {
                             let op = self.trans_operand(&bcx, operand);
                             // Do not generate stores and GEPis for zero-sized fields.
                             if !common::type_is_zero_size(bcx.ccx(), op.ty) {
-                                // Note: perhaps this should be StructGep, but
-                                // note that in some cases the values here will
-                                // not be structs but arrays.
-                                let dest = bcx.gepi(dest.llval, &[0, i]);
-                                self.store_operand(&bcx, dest, op);
+                                let lldest_i = match repr {
+                                    Some(ref repr) => {
+                                        let val = adt::MaybeSizedValue::sized(dest.llval);
+                                        adt::trans_field_ptr_builder(&bcx, repr, val,
+                                                                     Disr(0), i)
+                                    }
+                                    None => bcx.gepi(dest.llval, &[0, i]),
+                                };
+                                self.store_operand(&bcx, lldest_i, op);
                             }
                         }
                     }
@@ -115,8 +115,18 @@
 //! Note though that as a side-effect of creating a codegen units per
 //! source-level module, functions from the same module will be available for
 //! inlining, even when they are not marked #[inline].
-
-use collector::InliningMap;
+//!
+//! A Fixed Number of Codegen Units
+//! -------------------------------
+//! Outside of incremental compilation, the crate is split into a fixed number
+//! of codegen units, for LLVM to work on in parallel. Since that takes about as
+//! long as its largest unit takes, the per-module units are distributed so as
+//! to balance their sizes, estimated from the number of MIR statements of the
+//! translation items in them: the largest ones go first, each into the unit
+//! that is smallest so far. Modules larger than the average unit are split up
+//! item by item, at the cost of inlining between their functions.
+
+use collector::{InliningMap, SizeEstimates};
 use llvm;
 use monomorphize;
 use rustc::dep_graph::{DepNode, WorkProductId};
@@ -125,7 +135,7 @@ use rustc::hir::map::DefPathData;
 use rustc::session::config::NUMBERED_CODEGEN_UNIT_MARKER;
 use rustc::ty::TyCtxt;
 use rustc::ty::item_path::characteristic_def_id_of_type;
-use std::cmp::Ordering;
+use std::cmp::{self, Ordering};
 use std::hash::{Hash, Hasher, SipHasher};
 use std::sync::Arc;
 use symbol_map::SymbolMap;
@@ -138,7 +148,8 @@ pub enum PartitioningStrategy {
     /// Generate one codegen unit per source-level module.
     PerModule,
 
-    /// Partition the whole crate into a fixed number of codegen units.
+    /// Partition the whole crate into a fixed number of codegen units,
+    /// balanced by their estimated size.
     FixedUnitCount(usize)
 }
 
@@ -254,6 +265,7 @@ pub fn partition<'a, 'tcx, I>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
                               trans_items: I,
                               strategy: PartitioningStrategy,
                               inlining_map: &InliningMap<'tcx>,
+                              size_estimates: &SizeEstimates<'tcx>,
                               reachable: &NodeSet)
                               -> Vec<CodegenUnit<'tcx>>
     where I: Iterator<Item = TransItem<'tcx>>
@@ -274,9 +286,13 @@ pub fn partition<'a, 'tcx, I>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
     debug_dump(tcx, "INITIAL PARTITONING:", initial_partitioning.codegen_units.iter());
 
     // If the partitioning should produce a fixed count of codegen units, merge
-    // until that count is reached.
+    // and split them until that count is reached.
     if let PartitioningStrategy::FixedUnitCount(count) = strategy {
-        merge_codegen_units(&mut initial_partitioning, count, &tcx.crate_name[..]);
+        merge_codegen_units(tcx,
+                            &mut initial_partitioning,
+                            count,
+                            size_estimates,
+                            &tcx.crate_name[..]);
 
         debug_dump(tcx, "POST MERGING:", initial_partitioning.codegen_units.iter());
     }
@@ -378,38 +394,61 @@ fn place_root_translation_items<'a, 'tcx, I>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
     }
 }
 
-fn merge_codegen_units<'tcx>(initial_partitioning: &mut PreInliningPartitioning<'tcx>,
-                             target_cgu_count: usize,
-                             crate_name: &str) {
+fn merge_codegen_units<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
+                                 initial_partitioning: &mut PreInliningPartitioning<'tcx>,
+                                 target_cgu_count: usize,
+                                 size_estimates: &SizeEstimates<'tcx>,
+                                 crate_name: &str) {
     assert!(target_cgu_count >= 1);
     let codegen_units = &mut initial_partitioning.codegen_units;
 
-    // Merge the two smallest codegen units until the target size is reached.
-    // Note that "size" is estimated here rather inaccurately as the number of
-    // translation items in a given unit. This could be improved on.
-    while codegen_units.len() > target_cgu_count {
-        // Sort small cgus to the back
-        codegen_units.sort_by_key(|cgu| -(cgu.items.len() as i64));
-        let smallest = codegen_units.pop().unwrap();
-        let second_smallest = codegen_units.last_mut().unwrap();
-
-        for (k, v) in smallest.items.into_iter() {
-            second_smallest.items.insert(k, v);
+    let size_estimate = |trans_item: &TransItem<'tcx>| {
+        size_estimates.get(trans_item).cloned().unwrap_or(1)
+    };
+
+    // Start from a deterministic order, for ties to be broken the same way
+    // every time.
+    codegen_units.sort_by(|cgu1, cgu2| (&cgu1.name[..]).cmp(&cgu2.name[..]));
+
+    let total_size = codegen_units.iter().flat_map(|cgu| cgu.items.keys())
+                                  .map(&size_estimate)
+                                  .sum::<usize>();
+    let target_size = cmp::max(1, (total_size + target_cgu_count - 1) / target_cgu_count);
+
+    // Keep modules together, unless they wouldn't fit in a single unit.
+    let mut pieces = vec![];
+    for cgu in codegen_units.drain(..) {
+        let size = cgu.items.keys().map(&size_estimate).sum::<usize>();
+        if size <= target_size || cgu.items.len() == 1 {
+            pieces.push((size, cgu.items.into_iter().collect::<Vec<_>>()));
+            continue;
         }
-    }
 
-    for (index, cgu) in codegen_units.iter_mut().enumerate() {
-        cgu.name = numbered_codegen_unit_name(crate_name, index);
+        let mut items = cgu.items.into_iter().map(|(trans_item, linkage)| {
+            (trans_item.to_string(tcx), trans_item, linkage)
+        }).collect::<Vec<_>>();
+        items.sort_by(|&(ref name1, _, _), &(ref name2, _, _)| name1.cmp(name2));
+        pieces.extend(items.into_iter().map(|(_, trans_item, linkage)| {
+            (size_estimate(&trans_item), vec![(trans_item, linkage)])
+        }));
     }
 
-    // If the initial partitioning contained less than target_cgu_count to begin
-    // with, we won't have enough codegen units here, so add a empty units until
-    // we reach the target count
-    while codegen_units.len() < target_cgu_count {
-        let index = codegen_units.len();
-        codegen_units.push(
-            CodegenUnit::empty(numbered_codegen_unit_name(crate_name, index)));
+    // Place the largest pieces first, each into the smallest unit so far,
+    // which keeps the final sizes within a piece of each other.
+    pieces.sort_by(|&(size1, _), &(size2, _)| size2.cmp(&size1));
+
+    let mut sizes = vec![0; target_cgu_count];
+    let mut merged = (0..target_cgu_count).map(|index| {
+        CodegenUnit::empty(numbered_codegen_unit_name(crate_name, index))
+    }).collect::<Vec<_>>();
+
+    for (size, items) in pieces {
+        let smallest = (0..target_cgu_count).min_by_key(|&index| sizes[index]).unwrap();
+        sizes[smallest] += size;
+        merged[smallest].items.extend(items);
     }
+
+    *codegen_units = merged;
 }
 
 fn place_inlined_translation_items<'tcx>(initial_partitioning: PreInliningPartitioning<'tcx>,
@@ -105,6 +105,7 @@ use TypeAndSubsts;
 use lint;
 use util::common::{block_query, ErrorReported, indenter, loop_query};
 use util::nodemap::{DefIdMap, FnvHashMap, NodeMap};
+use util::profiling::{self, Category};
 
 use std::cell::{Cell, Ref, RefCell};
 use std::collections::{HashSet};
@@ -120,7 +121,7 @@ use syntax::parse::token::{self, InternedString, keywords};
 use syntax::ptr::P;
 use syntax::util::lev_distance::find_best_match_for_name;
 use syntax_pos::{self, Span};
-use errors::DiagnosticBuilder;
+use errors::{Applicability, DiagnosticBuilder};
 
 use rustc::hir::intravisit::{self, Visitor};
 use rustc::hir::{self, PatKind};
@@ -444,7 +445,11 @@ impl<'a, 'gcx, 'tcx> Inherited<'a, 'gcx, 'tcx> {
 }
 
 struct CheckItemTypesVisitor<'a, 'tcx: 'a> { ccx: &'a CrateCtxt<'a, 'tcx> }
-struct CheckItemBodiesVisitor<'a, 'tcx: 'a> { ccx: &'a CrateCtxt<'a, 'tcx> }
+struct CheckItemBodiesVisitor<'a, 'tcx: 'a> {
+    ccx: &'a CrateCtxt<'a, 'tcx>,
+    /// Whether to check the bodies of `const fn`s, or of everything else.
+    const_fns: bool,
+}
 
 impl<'a, 'tcx> Visitor<'tcx> for CheckItemTypesVisitor<'a, 'tcx> {
     fn visit_item(&mut self, i: &'tcx hir::Item) {
@@ -466,7 +471,10 @@ impl<'a, 'tcx> Visitor<'tcx> for CheckItemTypesVisitor<'a, 'tcx> {
 
 impl<'a, 'tcx> Visitor<'tcx> for CheckItemBodiesVisitor<'a, 'tcx> {
     fn visit_item(&mut self, i: &'tcx hir::Item) {
-        check_item_body(self.ccx, i);
+        let ccx = self.ccx;
+        profiling::record_with(Category::Item,
+                               || format!("typeck {}", ccx.tcx.node_path_str(i.id)),
+                               || check_item_body(ccx, i, self.const_fns));
     }
 }
 
@@ -486,7 +494,11 @@ pub fn check_item_types(ccx: &CrateCtxt) -> CompileResult {
 
 pub fn check_item_bodies(ccx: &CrateCtxt) -> CompileResult {
     ccx.tcx.sess.track_errors(|| {
-        let mut visit = CheckItemBodiesVisitor { ccx: ccx };
+        // Check the bodies of `const fn`s first, so that their MIR can be
+        // built to evaluate calls to them in array lengths and repeat counts.
+        let mut visit = CheckItemBodiesVisitor { ccx: ccx, const_fns: true };
+        ccx.tcx.visit_all_items_in_krate(DepNode::TypeckItemBody, &mut visit);
+        visit.const_fns = false;
         ccx.tcx.visit_all_items_in_krate(DepNode::TypeckItemBody, &mut visit);
 
         // Process deferred obligations, now that all functions
@@ -803,14 +815,18 @@ pub fn check_item_type<'a,'tcx>(ccx: &CrateCtxt<'a,'tcx>, it: &'tcx hir::Item) {
     }
 }
 
-pub fn check_item_body<'a,'tcx>(ccx: &CrateCtxt<'a,'tcx>, it: &'tcx hir::Item) {
+/// Checks the bodies of the `const fn`s of `it` if `const_fns` is set, and
+/// its other bodies otherwise.
+pub fn check_item_body<'a,'tcx>(ccx: &CrateCtxt<'a,'tcx>, it: &'tcx hir::Item, const_fns: bool) {
     debug!("check_item_body(it.id={}, it.name={})",
            it.id,
            ccx.tcx.item_path_str(ccx.tcx.map.local_def_id(it.id)));
     let _indenter = indenter();
     match it.node {
-      hir::ItemFn(ref decl, _, _, _, _, ref body) => {
-        check_bare_fn(ccx, &decl, &body, it.id);
+      hir::ItemFn(ref decl, _, constness, _, _, ref body) => {
+        if (constness == hir::Constness::Const) == const_fns {
+            check_bare_fn(ccx, &decl, &body, it.id);
+        }
       }
       hir::ItemImpl(_, _, _, _, _, ref impl_items) => {
         debug!("ItemImpl {} with id {}", it.name, it.id);
@@ -818,10 +834,14 @@ pub fn check_item_body<'a,'tcx>(ccx: &CrateCtxt<'a,'tcx>, it: &'tcx hir::Item) {
         for impl_item in impl_items {
             match impl_item.node {
                 hir::ImplItemKind::Const(_, ref expr) => {
-                    check_const(ccx, &expr, impl_item.id)
+                    if !const_fns {
+                        check_const(ccx, &expr, impl_item.id)
+                    }
                 }
                 hir::ImplItemKind::Method(ref sig, ref body) => {
-                    check_bare_fn(ccx, &sig.decl, body, impl_item.id);
+                    if (sig.constness == hir::Constness::Const) == const_fns {
+                        check_bare_fn(ccx, &sig.decl, body, impl_item.id);
+                    }
                 }
                 hir::ImplItemKind::Type(_) => {
                     // Nothing to do here.
@@ -829,7 +849,7 @@ pub fn check_item_body<'a,'tcx>(ccx: &CrateCtxt<'a,'tcx>, it: &'tcx hir::Item) {
             }
         }
       }
-      hir::ItemTrait(_, _, _, ref trait_items) => {
+      hir::ItemTrait(_, _, _, ref trait_items) if !const_fns => {
         for trait_item in trait_items {
             match trait_item.node {
                 hir::ConstTraitItem(_, Some(ref expr)) => {
@@ -3771,11 +3791,12 @@ impl<'a, 'gcx, 'tcx> FnCtxt<'a, 'gcx, 'tcx> {
                                             ast::LitIntType::Unsuffixed) = lit.node {
                                       let snip = tcx.sess.codemap().span_to_snippet(base.span);
                                       if let Ok(snip) = snip {
-                                          err.span_suggestion(expr.span,
-                                                              "to access tuple elements, \
-                                                               use tuple indexing syntax \
-                                                               as shown",
-                                                              format!("{}.{}", snip, i));
+                                          err.span_suggestion_with_applicability(
+                                              expr.span,
+                                              "to access tuple elements, use tuple \
+                                               indexing syntax as shown",
+                                              format!("{}.{}", snip, i),
+                                              Applicability::MachineApplicable);
                                           needs_note = false;
                                       }
                                   }
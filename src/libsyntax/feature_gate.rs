@@ -280,7 +280,11 @@ declare_features! (
     (active, relaxed_adts, "1.12.0", Some(35626)),
 
     // The `!` type
-    (active, never_type, "1.13.0", Some(35121))
+    (active, never_type, "1.13.0", Some(35121)),
+
+    // Allows keeping the fields of a struct in source order
+    // without otherwise changing its representation.
+    (active, no_field_reorder, "1.13.0", None)
 );
 
 declare_features! (
@@ -556,6 +560,10 @@ pub const KNOWN_ATTRIBUTES: &'static [(&'static str, AttributeType, AttributeGat
                                     "the `#[no_debug]` attribute \
                                      is an experimental feature",
                                     cfg_fn!(no_debug))),
+    ("no_field_reorder", Whitelisted, Gated("no_field_reorder",
+                                            "the `#[no_field_reorder]` attribute \
+                                             is an experimental feature",
+                                            cfg_fn!(no_field_reorder))),
     ("omit_gdb_pretty_printer_section", Whitelisted, Gated("omit_gdb_pretty_printer_section",
                                                        "the `#[omit_gdb_pretty_printer_section]` \
                                                         attribute is just used for the Rust test \
@@ -0,0 +1,210 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Collecting and applying machine-applicable suggestions, as done by
+//! `-Z apply-suggestions`.
+//!
+//! While compiling, a `SuggestionCollector` sits in front of the real
+//! emitter and records the edits of every suggestion marked as
+//! `Applicability::MachineApplicable`. Once compilation is over, the driver
+//! hands the recorded edits to `apply_replacements`, which rewrites the
+//! affected source files in place.
+
+use codemap::CodeMap;
+use errors::{Applicability, DiagnosticBuilder, RenderSpan};
+use errors::emitter::Emitter;
+use syntax_pos::{Pos, Span, NO_EXPANSION};
+
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{self, Read, Write};
+use std::rc::Rc;
+
+/// An edit to a source file: the bytes between `byte_start` and `byte_end`
+/// are to be replaced by `text`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Replacement {
+    pub file_name: String,
+    /// 0-based, relative to the start of the file.
+    pub byte_start: usize,
+    pub byte_end: usize,
+    /// The source text being replaced, as seen during compilation. Edits are
+    /// only applied if the file still contains this text at that position.
+    pub original: String,
+    pub text: String,
+}
+
+/// An emitter recording the machine-applicable suggestions of every
+/// diagnostic it sees, before passing the diagnostic on to `inner`.
+pub struct SuggestionCollector {
+    inner: Box<Emitter>,
+    cm: Rc<CodeMap>,
+    replacements: Rc<RefCell<Vec<Replacement>>>,
+}
+
+impl SuggestionCollector {
+    pub fn new(inner: Box<Emitter>,
+               cm: Rc<CodeMap>,
+               replacements: Rc<RefCell<Vec<Replacement>>>)
+               -> SuggestionCollector {
+        SuggestionCollector {
+            inner: inner,
+            cm: cm,
+            replacements: replacements,
+        }
+    }
+
+    fn replacement(&self, span: Span, substitute: &str) -> Option<Replacement> {
+        // Code resulting from a macro expansion can't be edited where it was
+        // written.
+        if span.expn_id != NO_EXPANSION {
+            return None;
+        }
+        let original = match self.cm.span_to_snippet(span) {
+            Ok(original) => original,
+            Err(_) => return None,
+        };
+        let lo = self.cm.lookup_byte_offset(span.lo);
+        if !lo.fm.is_real_file() {
+            return None;
+        }
+        Some(Replacement {
+            file_name: lo.fm.name.clone(),
+            byte_start: lo.pos.to_usize(),
+            byte_end: lo.pos.to_usize() + original.len(),
+            original: original,
+            text: substitute.to_string(),
+        })
+    }
+}
+
+impl Emitter for SuggestionCollector {
+    fn emit(&mut self, db: &DiagnosticBuilder) {
+        for child in &db.children {
+            let suggestion = match child.render_span {
+                Some(RenderSpan::Suggestion(ref suggestion)) => suggestion,
+                _ => continue,
+            };
+            if suggestion.applicability != Applicability::MachineApplicable {
+                continue;
+            }
+
+            // A suggestion is only useful if all of its edits can be made.
+            let edits = suggestion.msp.primary_spans().iter()
+                                  .zip(&suggestion.substitutes)
+                                  .map(|(&span, substitute)| self.replacement(span, substitute))
+                                  .collect::<Option<Vec<_>>>();
+
+            if let Some(edits) = edits {
+                self.replacements.borrow_mut().extend(edits);
+            }
+        }
+
+        self.inner.emit(db);
+    }
+
+    fn emit_unused_externs(&mut self, lint_level: &str, unused_externs: &[String]) {
+        self.inner.emit_unused_externs(lint_level, unused_externs);
+    }
+}
+
+/// Rewrites the files referred to by `replacements` in place, returning the
+/// number of replacements which were made.
+///
+/// Replacements overlapping one which was already made, and replacements
+/// whose original text is no longer found in the file, are skipped.
+pub fn apply_replacements(replacements: &[Replacement]) -> io::Result<usize> {
+    let mut by_file = BTreeMap::new();
+    for replacement in replacements {
+        by_file.entry(&replacement.file_name[..])
+               .or_insert(Vec::new())
+               .push(replacement);
+    }
+
+    let mut applied = 0;
+    for (file_name, replacements) in by_file {
+        let mut src = String::new();
+        File::open(file_name)?.read_to_string(&mut src)?;
+        let (new_src, n) = apply_to_source(&src, replacements);
+        if n > 0 {
+            File::create(file_name)?.write_all(new_src.as_bytes())?;
+        }
+        applied += n;
+    }
+    Ok(applied)
+}
+
+fn apply_to_source(src: &str, mut replacements: Vec<&Replacement>) -> (String, usize) {
+    replacements.sort_by_key(|r| (r.byte_start, r.byte_end));
+    // The same diagnostic may well have been reported more than once.
+    replacements.dedup();
+
+    let mut out = String::with_capacity(src.len());
+    let mut pos = 0;
+    let mut applied = 0;
+    for r in replacements {
+        if r.byte_start < pos ||
+           r.byte_end > src.len() ||
+           !src.is_char_boundary(r.byte_start) ||
+           !src.is_char_boundary(r.byte_end) ||
+           src[r.byte_start..r.byte_end] != r.original[..] {
+            continue;
+        }
+        out.push_str(&src[pos..r.byte_start]);
+        out.push_str(&r.text);
+        pos = r.byte_end;
+        applied += 1;
+    }
+    out.push_str(&src[pos..]);
+    (out, applied)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::apply_to_source;
+
+    fn replacement(byte_start: usize, original: &str, text: &str) -> Replacement {
+        Replacement {
+            file_name: "lib.rs".to_string(),
+            byte_start: byte_start,
+            byte_end: byte_start + original.len(),
+            original: original.to_string(),
+            text: text.to_string(),
+        }
+    }
+
+    #[test]
+    fn applies_in_order() {
+        let src = "let x = t[0] + t[1];";
+        let a = replacement(8, "t[0]", "t.0");
+        let b = replacement(15, "t[1]", "t.1");
+        assert_eq!(apply_to_source(src, vec![&b, &a]),
+                   ("let x = t.0 + t.1;".to_string(), 2));
+    }
+
+    #[test]
+    fn skips_duplicates_and_overlaps() {
+        let src = "let x = t[0];";
+        let a = replacement(8, "t[0]", "t.0");
+        let b = replacement(8, "t[0]", "t.0");
+        let c = replacement(10, "0]", "1]");
+        assert_eq!(apply_to_source(src, vec![&a, &b, &c]),
+                   ("let x = t.0;".to_string(), 1));
+    }
+
+    #[test]
+    fn skips_stale() {
+        let src = "let x = t[1];";
+        let a = replacement(8, "t[0]", "t.0");
+        assert_eq!(apply_to_source(src, vec![&a]), (src.to_string(), 0));
+    }
+}
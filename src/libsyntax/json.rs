@@ -23,6 +23,7 @@ use codemap::CodeMap;
 use syntax_pos::{self, MacroBacktrace, Span, SpanLabel, MultiSpan};
 use errors::registry::Registry;
 use errors::{DiagnosticBuilder, SubDiagnostic, RenderSpan, CodeSuggestion, CodeMapper};
+use errors::Applicability;
 use errors::emitter::Emitter;
 
 use std::rc::Rc;
@@ -59,6 +60,16 @@ impl Emitter for JsonEmitter {
             panic!("failed to print diagnostics: {:?}", e);
         }
     }
+
+    fn emit_unused_externs(&mut self, lint_level: &str, unused_externs: &[String]) {
+        let data = UnusedExterns {
+            lint_level: lint_level,
+            unused_extern_names: unused_externs,
+        };
+        if let Err(e) = writeln!(&mut self.dst, "{}", as_json(&data)) {
+            panic!("failed to print unused externs: {:?}", e);
+        }
+    }
 }
 
 // The following data types are provided just for serialisation.
@@ -102,6 +113,14 @@ struct DiagnosticSpan {
     /// load the fully rendered version from the parent `Diagnostic`,
     /// however.
     suggested_replacement: Option<String>,
+    /// If the suggested replacement is present, how confident we are that
+    /// it is correct: "MachineApplicable" if it can be applied by tools
+    /// without human review, otherwise "MaybeIncorrect" or "Unspecified".
+    suggestion_applicability: Option<&'static str>,
+    /// If the suggested replacement is present, the range of bytes it
+    /// replaces, 0-based and relative to the start of the file.
+    suggestion_byte_start: Option<u32>,
+    suggestion_byte_end: Option<u32>,
     /// Macro invocations that created the code at this span, if any.
     expansion: Option<Box<DiagnosticSpanMacroExpansion>>,
 }
@@ -138,6 +157,16 @@ struct DiagnosticCode {
     explanation: Option<&'static str>,
 }
 
+/// Emitted on a line of its own, after the diagnostics.
+#[derive(RustcEncodable)]
+struct UnusedExterns<'a> {
+    /// The level of the `unused_crate_dependencies` lint: "warn", "deny" or
+    /// "forbid".
+    lint_level: &'a str,
+    /// The names of the crates passed with `--extern` which are never used.
+    unused_extern_names: &'a [String],
+}
+
 impl<'a> Diagnostic<'a> {
     fn from_diagnostic_builder<'c>(db: &'c DiagnosticBuilder,
                                    je: &JsonEmitter)
@@ -171,7 +200,7 @@ impl<'a> Diagnostic<'a> {
 
 impl DiagnosticSpan {
     fn from_span_label(span: SpanLabel,
-                       suggestion: Option<&String>,
+                       suggestion: Option<(&String, Applicability)>,
                        je: &JsonEmitter)
                        -> DiagnosticSpan {
         Self::from_span_etc(span.span,
@@ -184,7 +213,7 @@ impl DiagnosticSpan {
     fn from_span_etc(span: Span,
                      is_primary: bool,
                      label: Option<String>,
-                     suggestion: Option<&String>,
+                     suggestion: Option<(&String, Applicability)>,
                      je: &JsonEmitter)
                      -> DiagnosticSpan {
         // obtain the full backtrace from the `macro_backtrace`
@@ -204,7 +233,7 @@ impl DiagnosticSpan {
     fn from_span_full(span: Span,
                       is_primary: bool,
                       label: Option<String>,
-                      suggestion: Option<&String>,
+                      suggestion: Option<(&String, Applicability)>,
                       mut backtrace: vec::IntoIter<MacroBacktrace>,
                       je: &JsonEmitter)
                       -> DiagnosticSpan {
@@ -242,7 +271,10 @@ impl DiagnosticSpan {
             column_end: end.col.0 + 1,
             is_primary: is_primary,
             text: DiagnosticSpanLine::from_span(span, je),
-            suggested_replacement: suggestion.cloned(),
+            suggested_replacement: suggestion.map(|x| x.0.clone()),
+            suggestion_applicability: suggestion.map(|x| x.1.to_str()),
+            suggestion_byte_start: suggestion.map(|_| span.lo.0 - start.file.start_pos.0),
+            suggestion_byte_end: suggestion.map(|_| span.hi.0 - start.file.start_pos.0),
             expansion: backtrace_step,
             label: label,
         }
@@ -261,9 +293,10 @@ impl DiagnosticSpan {
         suggestion.msp.span_labels()
                       .into_iter()
                       .zip(&suggestion.substitutes)
-                      .map(|(span_label, suggestion)| {
+                      .map(|(span_label, substitute)| {
                           DiagnosticSpan::from_span_label(span_label,
-                                                          Some(suggestion),
+                                                          Some((substitute,
+                                                                suggestion.applicability)),
                                                           je)
                       })
                       .collect()
@@ -92,6 +92,7 @@ pub mod util {
     pub use self::thin_vec::ThinVec;
 }
 
+pub mod fixit;
 pub mod json;
 
 pub mod syntax {
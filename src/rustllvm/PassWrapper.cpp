@@ -373,12 +373,29 @@ LLVMRustConfigurePassManagerBuilder(LLVMPassManagerBuilderRef PMB,
 				    LLVMRustCodeGenOptLevel OptLevel,
                                     bool MergeFunctions,
                                     bool SLPVectorize,
-                                    bool LoopVectorize) {
+                                    bool LoopVectorize,
+                                    const char* PGOGenPath,
+                                    const char* PGOUsePath) {
     // Ignore mergefunc for now as enabling it causes crashes.
     //unwrap(PMB)->MergeFunctions = MergeFunctions;
     unwrap(PMB)->SLPVectorize = SLPVectorize;
     unwrap(PMB)->OptLevel = from_rust(OptLevel);
     unwrap(PMB)->LoopVectorize = LoopVectorize;
+
+#if LLVM_VERSION_MINOR >= 9
+    if (PGOGenPath) {
+        assert(!PGOUsePath);
+        unwrap(PMB)->EnablePGOInstrGen = true;
+        unwrap(PMB)->PGOInstrGen = PGOGenPath;
+    }
+    if (PGOUsePath) {
+        assert(!PGOGenPath);
+        unwrap(PMB)->PGOInstrUse = PGOUsePath;
+    }
+#else
+    if (PGOGenPath || PGOUsePath)
+        report_fatal_error("profile-guided optimization requires LLVM 3.9 or later");
+#endif
 }
 
 // Unfortunately, the LLVM C API doesn't provide a way to set the `LibraryInfo`
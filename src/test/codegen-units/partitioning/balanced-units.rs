@@ -0,0 +1,51 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// ignore-tidy-linelength
+// compile-flags:-Zprint-trans-items=eager -Ccodegen-units=2
+
+#![allow(dead_code)]
+#![crate_type="lib"]
+
+// The root module is larger than half of the crate, so its functions get
+// split up between the two units, while the small module is kept together
+// and goes into the unit that is smaller at that point.
+
+//~ TRANS_ITEM fn balanced_units::large_a[0] @@ balanced_units.cgu-0[External]
+fn large_a(x: u32) -> u32 {
+    let mut y = x;
+    y = y ^ 3;
+    y = y | 5;
+    y = y & 7;
+    y = y ^ 11;
+    y = y | 13;
+    y = y & 17;
+    y
+}
+
+//~ TRANS_ITEM fn balanced_units::large_b[0] @@ balanced_units.cgu-1[External]
+fn large_b(x: u32) -> u32 {
+    let mut y = x;
+    y = y ^ 3;
+    y = y | 5;
+    y = y & 7;
+    y = y ^ 11;
+    y = y | 13;
+    y = y & 17;
+    y
+}
+
+mod small {
+    //~ TRANS_ITEM fn balanced_units::small[0]::foo[0] @@ balanced_units.cgu-0[External]
+    fn foo() {}
+
+    //~ TRANS_ITEM fn balanced_units::small[0]::bar[0] @@ balanced_units.cgu-0[External]
+    fn bar() {}
+}
@@ -0,0 +1,62 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// force-host
+
+#![feature(plugin_registrar)]
+#![feature(rustc_private)]
+
+extern crate rustc_plugin;
+
+use rustc_plugin::Registry;
+use rustc_plugin::lint_api::{self, Context, Expr, ExprKind, Item, ItemKind, Level};
+use rustc_plugin::lint_api::{LintDecl, LintVisitor};
+
+static LINTME_FN: LintDecl = LintDecl {
+    name: "lintme_fn",
+    default_level: Level::Warn,
+    description: "functions named 'lintme' taking a u32",
+};
+
+static OPTION_UNWRAP: LintDecl = LintDecl {
+    name: "option_unwrap",
+    default_level: Level::Allow,
+    description: "calls to `unwrap` on an `Option`",
+};
+
+struct Visitor;
+
+impl LintVisitor for Visitor {
+    fn lints(&self) -> Vec<&'static LintDecl> {
+        vec![&LINTME_FN, &OPTION_UNWRAP]
+    }
+
+    fn check_item(&mut self, cx: &Context, item: &Item) {
+        if let ItemKind::Fn { ref inputs, .. } = item.kind {
+            if item.name == "lintme" && inputs == &["u32".to_string()] {
+                cx.span_lint(&LINTME_FN, item.span, "function is named 'lintme'");
+            }
+        }
+    }
+
+    fn check_expr(&mut self, cx: &Context, expr: &Expr) {
+        if let ExprKind::MethodCall { ref name, receiver_ty: Some(ref ty), .. } = expr.kind {
+            if name == "unwrap" && ty.starts_with("std::option::Option<") {
+                cx.span_lint_help(&OPTION_UNWRAP, expr.span, "called `unwrap` on an `Option`",
+                                  "use `expect` instead");
+            }
+        }
+    }
+}
+
+#[plugin_registrar]
+pub fn plugin_registrar(reg: &mut Registry) {
+    reg.register_lint_visitor(lint_api::API_VERSION, Box::new(Visitor));
+}
@@ -0,0 +1,44 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// force-host
+
+#![feature(plugin_registrar)]
+#![feature(rustc_private)]
+
+extern crate rustc_plugin;
+
+use rustc_plugin::Registry;
+use rustc_plugin::lint_api::{self, Context, Item, Level, LintDecl, LintVisitor};
+
+static UNDECLARED: LintDecl = LintDecl {
+    name: "undeclared",
+    default_level: Level::Warn,
+    description: "a lint missing from `LintVisitor::lints`",
+};
+
+struct Visitor;
+
+impl LintVisitor for Visitor {
+    fn lints(&self) -> Vec<&'static LintDecl> {
+        vec![]
+    }
+
+    fn check_item(&mut self, cx: &Context, item: &Item) {
+        if item.name == "lintme" {
+            cx.span_lint(&UNDECLARED, item.span, "item is named 'lintme'");
+        }
+    }
+}
+
+#[plugin_registrar]
+pub fn plugin_registrar(reg: &mut Registry) {
+    reg.register_lint_visitor(lint_api::API_VERSION, Box::new(Visitor));
+}
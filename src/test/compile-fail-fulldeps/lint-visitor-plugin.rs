@@ -0,0 +1,27 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// aux-build:lint_visitor_test.rs
+// ignore-stage1
+
+#![feature(plugin)]
+#![plugin(lint_visitor_test)]
+#![deny(lintme_fn, option_unwrap)]
+
+fn lintme(_: u32) { } //~ ERROR function is named 'lintme'
+
+mod inner {
+    pub fn lintme(_: &str) { }
+}
+
+pub fn main() {
+    lintme(Some(1).unwrap()); //~ ERROR called `unwrap` on an `Option`
+    inner::lintme(Ok::<_, ()>("").unwrap());
+}
@@ -0,0 +1,27 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// aux-build:lint_visitor_undeclared.rs
+// ignore-stage1
+// error-pattern:plugin `lint_visitor_undeclared` reported the lint `undeclared`
+
+#![feature(plugin)]
+#![plugin(lint_visitor_undeclared)]
+
+fn lintme() { }
+
+mod inner {
+    pub fn lintme() { }
+}
+
+pub fn main() {
+    lintme();
+    inner::lintme();
+}
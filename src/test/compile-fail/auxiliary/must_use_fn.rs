@@ -0,0 +1,30 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![crate_type = "lib"]
+
+#[must_use]
+pub fn make() -> u32 { 0 }
+
+pub struct Builder;
+
+impl Builder {
+    #[must_use = "the builder is returned, not modified in place"]
+    pub fn with_capacity(self, _: usize) -> Builder { self }
+}
+
+pub trait Check {
+    #[must_use]
+    fn check(&self) -> bool;
+}
+
+impl Check for Builder {
+    fn check(&self) -> bool { true }
+}
@@ -0,0 +1,81 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// compile-flags: -Z borrowck-mir
+
+// Check that the MIR borrow checker reports the same conflicts as the AST
+// one, at the same places.
+
+struct Point {
+    x: i32,
+    y: i32,
+}
+
+fn assign_borrowed() {
+    let mut x = 1;
+    let r = &x;
+    x = 2; //~ ERROR cannot assign to `x` because it is borrowed (Mir)
+           //~| ERROR cannot assign to `x` because it is borrowed
+    drop(r);
+}
+
+fn move_borrowed() {
+    let s = String::new();
+    let r = &s;
+    let t = s; //~ ERROR cannot move out of `s` because it is borrowed (Mir)
+               //~| ERROR cannot move out of `s` because it is borrowed
+    drop(r);
+    drop(t);
+}
+
+fn use_mutably_borrowed() {
+    let mut x = 1;
+    let r = &mut x;
+    let y = x; //~ ERROR cannot use `x` because it was mutably borrowed (Mir)
+               //~| ERROR cannot use `x` because it was mutably borrowed
+    *r = y;
+}
+
+fn borrow_mut_twice() {
+    let mut v = vec![1];
+    let a = &mut v;
+    let b = &mut v; //~ ERROR cannot borrow `v` as mutable more than once at a time (Mir)
+                    //~| ERROR cannot borrow `v` as mutable more than once at a time
+    a.push(2);
+    b.push(3);
+}
+
+fn borrow_mut_of_field_borrowed() {
+    let mut p = Point { x: 1, y: 2 };
+    let a = &p.x;
+    let b = &mut p;
+    //~^ ERROR cannot borrow `p` as mutable because `p.x` is also borrowed as immutable (Mir)
+    //~| ERROR cannot borrow `p` as mutable because `p.x` is also borrowed as immutable
+    b.y = *a;
+}
+
+fn borrow_in_loop() {
+    let mut x = 1;
+    let mut refs = vec![];
+    loop {
+        refs.push(&mut x);
+        //~^ ERROR cannot borrow `x` as mutable more than once at a time (Mir)
+        //~| ERROR cannot borrow `x` as mutable more than once at a time
+    }
+}
+
+fn main() {
+    assign_borrowed();
+    move_borrowed();
+    use_mutably_borrowed();
+    borrow_mut_twice();
+    borrow_mut_of_field_borrowed();
+    borrow_in_loop();
+}
@@ -0,0 +1,31 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Test that errors in the evaluation of a const fn with control flow
+// point at the failing expression inside of the const fn.
+
+#![feature(const_fn)]
+
+const fn factorial(n: u32) -> u32 {
+    let mut acc = 1;
+    let mut i = 0;
+    while i < n {
+        i += 1;
+        acc *= i; //~ ERROR constant evaluation error
+                  //~| attempt to multiply with overflow
+    }
+    acc
+}
+
+static BIG: u32 = factorial(20);
+
+fn main() {
+    let _ = BIG;
+}
@@ -0,0 +1,32 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![deny(const_err)]
+
+fn main() {
+    let x = 255u8;
+    let _y = x + 1;
+    //~^ ERROR attempt to add with overflow
+
+    let shift = 40;
+    let _z = 1u32 << shift;
+    //~^ ERROR attempt to shift left with overflow
+
+    // Overflow on code that is never reached is not reported.
+    if false {
+        let _w = x * 2;
+    }
+
+    // Nor is overflow on values that depend on control flow.
+    let mut n = 250u8;
+    for _ in 0..3 {
+        n = n + 1;
+    }
+}
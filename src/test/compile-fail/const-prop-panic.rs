@@ -0,0 +1,40 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![deny(const_err)]
+
+fn divide(x: i32) -> i32 {
+    x / 0
+    //~^ ERROR attempt to divide by zero
+}
+
+fn main() {
+    let a = [0; 3];
+    let _b = a[5];
+    //~^ ERROR index out of bounds: the len is 3 but the index is 5
+
+    let i = 3;
+    let _c = a[i];
+    //~^ ERROR index out of bounds: the len is 3 but the index is 3
+
+    let zero = 0;
+    let _d = 10 % zero;
+    //~^ ERROR attempt to calculate the remainder with a divisor of zero
+
+    // Checks which may pass are not reported.
+    let _e = a[2];
+    let mut j = 0;
+    while j < 3 {
+        let _f = a[j];
+        j = j + 1;
+    }
+
+    divide(1);
+}
@@ -0,0 +1,55 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// aux-build:must_use_fn.rs
+
+#![deny(unused_must_use)]
+#![allow(dead_code)]
+
+extern crate must_use_fn;
+
+use must_use_fn::{Builder, Check};
+
+#[must_use]
+fn local() -> bool { true }
+
+struct Local;
+
+impl Local {
+    #[must_use = "it's pure"]
+    fn double(&self, x: u32) -> u32 { x * 2 }
+}
+
+trait Pure {
+    #[must_use]
+    fn pure(&self) -> u8;
+}
+
+impl Pure for Local {
+    fn pure(&self) -> u8 { 0 }
+}
+
+#[must_use] //~ WARN `must_use` attribute should be applied to function, method, struct or enum
+static S: u8 = 0;
+
+fn main() {
+    local(); //~ ERROR unused return value of `local` which must be used
+    Local.double(1); //~ ERROR which must be used: it's pure
+    Local.pure(); //~ ERROR unused return value of `Pure::pure` which must be used
+    Local::pure(&Local); //~ ERROR unused return value of `Pure::pure` which must be used
+
+    must_use_fn::make(); //~ ERROR unused return value of `must_use_fn::make` which must be used
+    Builder.with_capacity(1);
+    //~^ ERROR which must be used: the builder is returned, not modified in place
+    Builder.check(); //~ ERROR unused return value of `must_use_fn::Check::check` which must be used
+
+    let _ = local();
+    let _ = Builder.with_capacity(1).check();
+}
@@ -26,6 +26,8 @@ fn main() {
         a::try_foo(foo2);
         //~^ ERROR mismatched types
         //~| Perhaps two different versions of crate `crate_a1`
+        //~| `main::a::Foo` (expected) is defined in crate `crate_a1` with SVH
+        //~| `main::a::Foo` (found) is defined in crate `crate_a2` with SVH
         //~| expected struct `main::a::Foo`
         //~| expected type `main::a::Foo`
         //~| found type `main::a::Foo`
@@ -0,0 +1,58 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// ignore-lldb
+// ignore-windows
+// ignore-macos
+// ignore-android
+// min-gdb-version 7.7
+
+// compile-flags:-g -C split-debuginfo=packed -C codegen-units=2
+
+// gdb-command:run
+
+// gdb-command:print a
+// gdb-check:$1 = 5
+// gdb-command:print b
+// gdb-check:$2 = {x = 1, y = 2}
+// gdb-command:continue
+
+// gdb-command:print *p
+// gdb-check:$3 = {x = 1, y = 2}
+
+#![allow(unused_variables)]
+#![feature(omit_gdb_pretty_printer_section)]
+#![omit_gdb_pretty_printer_section]
+
+struct Point {
+    x: i32,
+    y: i32,
+}
+
+// Put in another codegen unit than `main`, so that it ends up in another
+// `.dwo` file.
+mod other {
+    use super::Point;
+
+    pub fn sum(p: &Point) -> i32 {
+        super::zzz(); // #break
+        p.x + p.y
+    }
+}
+
+fn main() {
+    let a = 5;
+    let b = Point { x: 1, y: 2 };
+    zzz(); // #break
+    other::sum(&b);
+}
+
+#[inline(never)]
+fn zzz() { () }
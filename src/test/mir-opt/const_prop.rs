@@ -0,0 +1,37 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+fn main() {
+    let x = 2;
+    let y = x * 3;
+    let z = y + x;
+    consume(z);
+}
+
+#[inline(never)]
+fn consume(_: i32) {}
+
+// END RUST SOURCE
+// START rustc.node4.ConstPropagation.before.mir
+//     var0 = const 2i32;
+//     tmp0 = var0;
+//     tmp1 = CheckedMul(tmp0, const 3i32);
+//     var1 = (tmp1.0: i32);
+// END rustc.node4.ConstPropagation.before.mir
+// START rustc.node4.ConstPropagation.after.mir
+//     var0 = const 2i32;
+//     tmp0 = const 2i32;
+//     tmp1 = CheckedMul(const 2i32, const 3i32);
+//     var1 = const 6i32;
+//     tmp2 = const 6i32;
+//     tmp3 = const 2i32;
+//     tmp4 = CheckedAdd(const 6i32, const 2i32);
+//     var2 = const 8i32;
+// END rustc.node4.ConstPropagation.after.mir
@@ -0,0 +1,33 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+fn main() {
+    let x = 2;
+    let y = x * 3;
+    consume(y);
+}
+
+#[inline(never)]
+fn consume(_: i32) {}
+
+// END RUST SOURCE
+// START rustc.node4.DeadStoreElimination.before.mir
+//     var0 = const 2i32;
+//     tmp0 = const 2i32;
+//     tmp1 = CheckedMul(const 2i32, const 3i32);
+//     var1 = const 6i32;
+//     tmp3 = const 6i32;
+//     tmp2 = consume(const 6i32) -> bb2;
+// END rustc.node4.DeadStoreElimination.before.mir
+// START rustc.node4.DeadStoreElimination.after.mir
+//     StorageLive(var0);
+//     StorageLive(var1);
+//     tmp0 = consume(const 6i32) -> bb2;
+// END rustc.node4.DeadStoreElimination.after.mir
@@ -0,0 +1,22 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+fn consume(_: Box<u32>) {}
+
+// `x` is only moved out of on one path, so dropping it needs a drop flag.
+fn maybe_drop(x: Box<u32>, c: bool) {
+    if c {
+        consume(x);
+    }
+}
+
+fn main() {
+    maybe_drop(Box::new(1), true);
+}
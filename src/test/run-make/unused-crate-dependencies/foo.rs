@@ -0,0 +1,29 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+extern crate bar;
+extern crate baz;
+#[macro_use]
+extern crate mac;
+
+struct Local;
+
+impl Local {
+    fn get(&self) -> u32 {
+        1
+    }
+}
+
+fn main() {
+    assert_eq!(Local.get(), 1);
+    bar::bar();
+    assert_eq!(bar::make_qux().value(), 1);
+    assert_eq!(answer!(), 42);
+}
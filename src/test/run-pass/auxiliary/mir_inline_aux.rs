@@ -0,0 +1,29 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// compile-flags: -Z mir-opt-level=2
+
+static COUNT: usize = 3;
+
+fn private_add(a: usize, b: usize) -> usize {
+    a + b
+}
+
+#[inline]
+pub fn add_count(x: usize) -> usize {
+    private_add(x, COUNT)
+}
+
+pub fn first<T: Clone>(xs: &[T]) -> Option<T> {
+    match xs.first() {
+        Some(x) => Some(x.clone()),
+        None => None,
+    }
+}
@@ -0,0 +1,87 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// compile-flags: -Z borrowck-mir
+
+// Check that the MIR borrow checker accepts loans which end, or which do
+// not overlap, the same way the AST one does.
+
+struct Point {
+    x: i32,
+    y: i32,
+}
+
+fn bump(x: &mut i32) -> i32 {
+    *x += 1;
+    *x
+}
+
+fn disjoint_fields() -> i32 {
+    let mut p = Point { x: 1, y: 2 };
+    let a = &mut p.x;
+    let b = &mut p.y;
+    *a += *b;
+    p.x
+}
+
+fn scoped_loan() -> i32 {
+    let mut x = 1;
+    {
+        let r = &mut x;
+        *r += 1;
+    }
+    x += 1;
+    x
+}
+
+fn loan_in_call_argument() -> i32 {
+    let mut x = 1;
+    x = bump(&mut x);
+    x
+}
+
+fn loans_in_loops() -> i32 {
+    let mut x = 0;
+    let mut i = 0;
+    while i < 10 {
+        i += 1;
+        let r = &mut x;
+        if i % 2 == 0 {
+            continue;
+        }
+        *r += 1;
+    }
+    for _ in 0..3 {
+        bump(&mut x);
+    }
+    loop {
+        let r = &mut x;
+        *r += 1;
+        if *r > 10 {
+            break;
+        }
+    }
+    x
+}
+
+fn shared_loans() -> usize {
+    let v = vec![1, 2, 3];
+    let a = &v;
+    let b = &v;
+    a.len() + b.len() + v.len()
+}
+
+fn main() {
+    assert_eq!(disjoint_fields(), 3);
+    assert_eq!(scoped_loan(), 3);
+    assert_eq!(loan_in_call_argument(), 2);
+    assert_eq!(loans_in_loops(), 11);
+    assert_eq!(shared_loans(), 9);
+}
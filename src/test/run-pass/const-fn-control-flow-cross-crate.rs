@@ -0,0 +1,29 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// aux-build:const_fn_control_flow_lib.rs
+
+// Test that const fns with control flow from other crates can be used in
+// array lengths, which are evaluated during type checking.
+
+#![feature(const_fn)]
+
+extern crate const_fn_control_flow_lib as lib;
+
+const CAPACITY: usize = lib::next_power_of_two(5);
+
+static LIMITED: u32 = lib::clamp(300, 255);
+
+fn main() {
+    let buf: [u8; lib::next_power_of_two(100)] = [0; 128];
+    assert_eq!(buf.len(), 128);
+    let _: [u8; CAPACITY] = [0; 8];
+    assert_eq!(LIMITED, 255);
+}
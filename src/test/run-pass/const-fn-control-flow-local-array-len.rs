@@ -0,0 +1,41 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Array lengths and repeat counts in function bodies can call the const fns
+// with control flow of the current crate, whose MIR is built on demand.
+
+#![feature(const_fn)]
+
+const fn double(n: usize) -> usize {
+    let mut m = n;
+    m += n;
+    m
+}
+
+const fn bytes(words: usize) -> usize {
+    let mut bytes = 0;
+    let mut i = 0;
+    while i < words {
+        bytes += 4;
+        i += 1;
+    }
+    bytes
+}
+
+fn main() {
+    let a: [u8; double(2)] = [0; 4];
+    assert_eq!(a.len(), 4);
+
+    let b = [1u8; double(3)];
+    assert_eq!(b.len(), 6);
+
+    let c: [u8; bytes(2)] = [0; double(4)];
+    assert_eq!(c.len(), 8);
+}
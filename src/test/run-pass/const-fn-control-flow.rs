@@ -0,0 +1,104 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Test that const fns can have locals, branches and loops, in constants,
+// statics and at runtime.
+
+#![feature(const_fn)]
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+enum Parity {
+    Even,
+    Odd,
+}
+
+const fn parity(n: u32) -> Parity {
+    if n % 2 == 0 { Parity::Even } else { Parity::Odd }
+}
+
+const fn weight(p: Parity) -> u8 {
+    match p {
+        Parity::Even => 2,
+        Parity::Odd => 1,
+    }
+}
+
+const fn sum_to(n: u32) -> u32 {
+    let mut sum = 0;
+    let mut i = 0;
+    while i <= n {
+        sum += i;
+        i += 1;
+    }
+    sum
+}
+
+const fn make_table() -> [u8; 8] {
+    let mut table = [0; 8];
+    let mut i = 0;
+    loop {
+        if i == 8 {
+            break;
+        }
+        table[i] = weight(parity(i as u32)) * i as u8;
+        i += 1;
+    }
+    table
+}
+
+const fn first_odd(start: usize) -> Option<u32> {
+    let values = [2, 4, 7, 9];
+    let mut i = start;
+    while i < 4 {
+        if let Parity::Odd = parity(values[i]) {
+            return Some(values[i]);
+        }
+        i += 1;
+    }
+    None
+}
+
+struct Bounds {
+    lo: u8,
+    hi: u32,
+}
+
+const fn count_within(bounds: &Bounds, values: (&[u32], usize)) -> u32 {
+    let mut count = 0;
+    let mut i = 0;
+    while i < values.1 {
+        if bounds.lo as u32 <= values.0[i] && values.0[i] <= bounds.hi {
+            count += 1;
+        }
+        i += 1;
+    }
+    count
+}
+
+const VALUES: &'static [u32] = &[1, 5, 9, 12];
+
+static TABLE: [u8; 8] = make_table();
+const SUM: u32 = sum_to(10);
+static FIRST_ODD: Option<u32> = first_odd(0);
+static LAST_ODD: Option<u32> = first_odd(3);
+static WITHIN: u32 = count_within(&Bounds { lo: 4, hi: 10 }, (VALUES, 4));
+
+fn main() {
+    assert_eq!(TABLE, [0, 1, 4, 3, 8, 5, 12, 7]);
+    assert_eq!(SUM, 55);
+    assert_eq!(FIRST_ODD, Some(7));
+    assert_eq!(LAST_ODD, Some(9));
+    assert_eq!(first_odd(4), None);
+    assert_eq!(WITHIN, 2);
+
+    assert_eq!(make_table(), TABLE);
+    assert_eq!(sum_to(10), SUM);
+    assert_eq!(parity(3), Parity::Odd);
+}
@@ -0,0 +1,92 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Enums whose other variants are dataless store their discriminant in
+// values a field of the dataful variant never holds.
+
+use std::mem::size_of;
+
+#[derive(PartialEq, Debug)]
+enum E { A(char), B, C }
+
+#[derive(PartialEq, Debug)]
+enum F { B, A(bool, u8), C }
+
+#[derive(PartialEq, Debug)]
+enum Inner { X(u32), Y(u16), Z }
+
+#[derive(PartialEq, Debug)]
+enum Outer { Data(Inner), Nothing, Unit(()) }
+
+macro_rules! check {
+    ($t:ty, $sz:expr, $($e:expr, $s:expr),*) => {{
+        assert_eq!(size_of::<$t>(), $sz);
+        $({
+            static S: $t = $e;
+            let v: $t = $e;
+            assert_eq!(S, v);
+            assert_eq!(format!("{:?}", v), $s);
+            assert_eq!(format!("{:?}", S), $s);
+        });*
+    }}
+}
+
+pub fn main() {
+    check!(Option<bool>, 1,
+           None, "None",
+           Some(true), "Some(true)",
+           Some(false), "Some(false)");
+    check!(Option<Option<bool>>, 1,
+           None, "None",
+           Some(None), "Some(None)",
+           Some(Some(true)), "Some(Some(true))",
+           Some(Some(false)), "Some(Some(false))");
+    check!(Option<Option<Option<bool>>>, 1,
+           None, "None",
+           Some(None), "Some(None)",
+           Some(Some(None)), "Some(Some(None))",
+           Some(Some(Some(true))), "Some(Some(Some(true)))");
+    check!(E, 4,
+           E::A('\u{10FFFF}'), "A('\\u{10ffff}')",
+           E::A('x'), "A('x')",
+           E::B, "B",
+           E::C, "C");
+    check!(Option<E>, 4,
+           None, "None",
+           Some(E::C), "Some(C)",
+           Some(E::A('y')), "Some(A('y'))");
+    check!(F, 2,
+           F::A(true, 255), "A(true, 255)",
+           F::B, "B",
+           F::C, "C");
+    check!(Outer, size_of::<Inner>(),
+           Outer::Data(Inner::X(7)), "Data(X(7))",
+           Outer::Data(Inner::Z), "Data(Z)",
+           Outer::Nothing, "Nothing",
+           Outer::Unit(()), "Unit(())");
+    check!(Option<std::cmp::Ordering>, 1,
+           None, "None",
+           Some(std::cmp::Ordering::Less), "Some(Less)");
+
+    let mut x = Some(Some(false));
+    if let Some(ref mut inner) = x {
+        *inner = None;
+    }
+    assert_eq!(x, Some(None));
+    match x {
+        Some(Some(_)) | None => panic!(),
+        Some(None) => {}
+    }
+
+    let mut e = E::A('z');
+    assert!(match e { E::A('z') => true, _ => false });
+    e = E::C;
+    assert!(match e { E::C => true, _ => false });
+}
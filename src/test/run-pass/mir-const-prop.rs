@@ -0,0 +1,62 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// compile-flags: -Z mir-opt-level=2
+
+// Check that constant propagation and dead store elimination keep values
+// which depend on control flow, and stores which are observed.
+
+fn loop_sum(n: u32) -> u32 {
+    let mut total = 0;
+    let step = 2;
+    for _ in 0..n {
+        total = total + step;
+    }
+    total
+}
+
+fn branch(flag: bool) -> i32 {
+    let x;
+    if flag {
+        x = 1;
+    } else {
+        x = 2;
+    }
+    let y = x * 10;
+    y
+}
+
+fn through_borrow() -> i32 {
+    let mut x = 1;
+    {
+        let r = &mut x;
+        *r = 5;
+    }
+    x + 1
+}
+
+fn unused_drop() -> usize {
+    let v = vec![1, 2, 3];
+    let _unused = 4;
+    v.len()
+}
+
+fn main() {
+    assert_eq!(loop_sum(5), 10);
+    assert_eq!(branch(true), 10);
+    assert_eq!(branch(false), 20);
+    assert_eq!(through_borrow(), 6);
+    assert_eq!(unused_drop(), 3);
+
+    let a = 7u8;
+    let b = a / 2;
+    assert_eq!(b, 3);
+    assert!(a > b);
+}
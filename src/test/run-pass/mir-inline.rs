@@ -0,0 +1,83 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// compile-flags: -Z mir-opt-level=2
+// aux-build:mir_inline_aux.rs
+
+// Check that code inlined by the MIR inliner behaves like the call it
+// replaces, including calls of functions from other crates.
+
+extern crate mir_inline_aux;
+
+use std::panic;
+
+static mut DROPS: usize = 0;
+
+struct Droppy(usize);
+
+impl Drop for Droppy {
+    fn drop(&mut self) {
+        unsafe { DROPS += self.0; }
+    }
+}
+
+fn double(x: usize) -> usize {
+    x * 2
+}
+
+#[inline]
+fn swap<T>(pair: (T, T)) -> (T, T) {
+    (pair.1, pair.0)
+}
+
+fn promoted() -> &'static [usize] {
+    &[1, 2, 3]
+}
+
+fn fact(n: u64) -> u64 {
+    if n == 0 { 1 } else { n * fact(n - 1) }
+}
+
+fn checked_div(a: usize, b: usize) -> usize {
+    let _guard = Droppy(1);
+    a / b
+}
+
+struct Pair {
+    a: usize,
+    b: usize,
+}
+
+fn main() {
+    assert_eq!(double(21), 42);
+    assert_eq!(swap((1u8, 2u8)), (2, 1));
+    assert_eq!(swap(("a".to_string(), "b".to_string())).0, "b");
+    assert_eq!(promoted().len(), 3);
+    assert_eq!(fact(5), 120);
+
+    // The return value of the callee goes to a projection.
+    let mut pair = Pair { a: 0, b: 0 };
+    pair.a = double(2);
+    pair.b = double(pair.a);
+    assert_eq!((pair.a, pair.b), (4, 8));
+
+    // Unwinding out of the inlined code runs the cleanup of both the
+    // callee and the caller.
+    let result = panic::catch_unwind(|| {
+        let _outer = Droppy(10);
+        checked_div(1, 0)
+    });
+    assert!(result.is_err());
+    assert_eq!(unsafe { DROPS }, 11);
+
+    assert_eq!(mir_inline_aux::add_count(4), 7);
+    assert_eq!(mir_inline_aux::first(&[5, 6]), Some(5));
+    assert_eq!(mir_inline_aux::first::<String>(&[]), None);
+}
@@ -0,0 +1,67 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Fields are reordered to minimize padding, unless the representation
+// is fixed or the struct opts out with `#[no_field_reorder]`.
+
+#![feature(no_field_reorder)]
+
+use std::mem::{size_of, size_of_val};
+
+#[derive(PartialEq, Debug)]
+struct S { a: u8, b: u64, c: u8 }
+
+#[repr(C)]
+struct C { a: u8, b: u64, c: u8 }
+
+#[no_field_reorder]
+struct N { a: u8, b: u64, c: u8 }
+
+#[derive(PartialEq, Debug)]
+enum E { A(u8, u32, u8), B }
+
+struct G<T: ?Sized> { a: u8, b: u32, c: u8, t: T }
+
+static S0: S = S { a: 1, b: 2, c: 3 };
+static E0: E = E::A(4, 5, 6);
+
+pub fn main() {
+    assert_eq!(size_of::<S>(), 16);
+    assert_eq!(size_of::<C>(), 24);
+    assert_eq!(size_of::<N>(), 24);
+    assert_eq!(size_of::<E>(), 8);
+
+    // The last element of a tuple stays last, as it may be unsized.
+    assert_eq!(size_of::<(u8, u64, u8, u64)>(), 24);
+
+    assert_eq!(S0, S { a: 1, b: 2, c: 3 });
+    assert_eq!(format!("{:?}", S0), "S { a: 1, b: 2, c: 3 }");
+    assert_eq!(E0, E::A(4, 5, 6));
+    assert_eq!(format!("{:?}", E0), "A(4, 5, 6)");
+    match E0 {
+        E::A(a, b, c) => assert_eq!((a, b, c), (4, 5, 6)),
+        E::B => panic!()
+    }
+
+    let t = (7u8, 8u64, 9u8, 10u64);
+    assert_eq!(t, (7, 8, 9, 10));
+
+    let (a, b, c) = (11u8, 12u64, 13u8);
+    let f = move || a as u64 + b + c as u64;
+    assert_eq!(size_of_val(&f), 16);
+    assert_eq!(f(), 36);
+
+    // The unsized field stays last, after the same prefix either way.
+    let g = G { a: 14, b: 15, c: 16, t: [17u8, 18] };
+    assert_eq!(size_of_val(&g), 8);
+    let g: &G<[u8]> = &g;
+    assert_eq!((g.a, g.b, g.c), (14, 15, 16));
+    assert_eq!(&g.t, &[17, 18]);
+}
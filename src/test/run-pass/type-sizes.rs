@@ -52,5 +52,6 @@ pub fn main() {
 
     assert_eq!(size_of::<e1>(), 8 as usize);
     assert_eq!(size_of::<e2>(), 8 as usize);
-    assert_eq!(size_of::<e3>(), 4 as usize);
+    // The u8 goes right after the discriminant, before the empty array.
+    assert_eq!(size_of::<e3>(), 2 as usize);
 }
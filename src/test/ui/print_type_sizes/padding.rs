@@ -0,0 +1,38 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// compile-flags: -Z print-type-sizes
+
+// This file illustrates how padding is handled: struct fields are
+// reordered to reduce it, while enum variant fields follow the
+// discriminant, smallest first.
+
+#![feature(start)]
+
+struct S {
+    a: u8,
+    b: u32,
+    c: u16,
+}
+
+enum E {
+    A(u8, u32),
+    B(u16),
+    C,
+}
+
+#[start]
+fn start(_: isize, _: *const *const u8) -> isize {
+    let _s = S { a: 1, b: 2, c: 3 };
+    let _e = E::A(4, 5);
+    let _f = E::B(6);
+    let _g = E::C;
+    0
+}
@@ -2077,6 +2077,11 @@ actual:\n\
            .env("LLVM_COMPONENTS", &self.config.llvm_components)
            .env("LLVM_CXXFLAGS", &self.config.llvm_cxxflags);
 
+        // The other LLVM tools, like `llvm-profdata`, are next to FileCheck.
+        if let Some(ref filecheck) = self.config.llvm_filecheck {
+            cmd.env("LLVM_BIN_DIR", filecheck.parent().unwrap());
+        }
+
         if self.config.target.contains("msvc") {
             // We need to pass a path to `lib.exe`, so assume that `cc` is `cl.exe`
             // and that `lib.exe` lives next to it.